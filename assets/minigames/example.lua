@@ -0,0 +1,49 @@
+-- Survival: every second alive is worth a point, the round ends after a
+-- minute or once everyone is dead. Type !jump to get thrown up.
+
+local ROUND_TIME = 60 * 1000
+
+function eventNewPlayer(name)
+    print(name .. " joined the game")
+    ui.addTextArea(0, "<a href='event:close'>Survive for a minute, !jump to jump</a>", name, 200, 30, 400, 0, nil, nil, 0.8, true)
+end
+
+function eventTextAreaCallback(id, name, callback)
+    if callback == "close" then
+        ui.removeTextArea(id, name)
+    end
+end
+
+function eventNewGame()
+    for name in pairs(tfm.get.room.playerList) do
+        tfm.exec.setPlayerScore(name, 0)
+    end
+end
+
+function eventLoop(elapsed, remaining)
+    for name, player in pairs(tfm.get.room.playerList) do
+        if not player.isDead then
+            tfm.exec.setPlayerScore(name, 1, true)
+        end
+    end
+
+    if elapsed >= ROUND_TIME then
+        tfm.exec.newGame()
+    end
+end
+
+function eventPlayerDied(name)
+    print(name .. " is out")
+end
+
+function eventPlayerWon(name, time)
+    tfm.exec.setPlayerScore(name, 10, true)
+end
+
+function eventChatCommand(name, command)
+    if command == "jump" then
+        tfm.exec.movePlayer(name, 0, 0, true, 0, -60, false)
+    end
+end
+
+tfm.exec.newGame()
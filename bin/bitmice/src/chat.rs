@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::collections::VecDeque;
+
+use crate::config::ChatConfig;
+
+/// Escapes the text typed by a player, the client renders html.
+pub fn escape(text: &str) -> String {
+    text.replace('<', "&lt;")
+}
+
+/// Replaces the banned words by `*`, ignoring the case.
+pub fn censor(message: &str, banned_words: &[String]) -> String {
+    let mut chars = message.chars().collect::<Vec<char>>();
+    let lowercase = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect::<Vec<char>>();
+
+    for word in banned_words {
+        let word = word.to_lowercase().chars().collect::<Vec<char>>();
+        if word.is_empty() || word.len() > lowercase.len() {
+            continue;
+        }
+
+        for start in 0..=lowercase.len() - word.len() {
+            if lowercase[start..start + word.len()] == word[..] {
+                chars[start..start + word.len()].fill('*');
+            }
+        }
+    }
+
+    chars.into_iter().collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    Allowed,
+    /// Sent before the minimum interval, it's dropped.
+    TooFast,
+    /// Seconds left of the mute.
+    Muted(u64),
+}
+
+/// Messages recently sent by a player, used to limit the rate of its messages
+/// and to mute it when flooding.
+#[derive(Debug, Default)]
+pub struct FloodControl {
+    sent: VecDeque<u128>,
+    muted_until: u128,
+}
+
+impl FloodControl {
+    /// Checks a message sent at `now`, in milliseconds, and records it when
+    /// it's allowed.
+    pub fn check(&mut self, now: u128, config: &ChatConfig) -> Check {
+        if now < self.muted_until {
+            return Check::Muted(((self.muted_until - now) as u64).div_ceil(1000));
+        }
+
+        if let Some(last) = self.sent.back() {
+            if now.saturating_sub(*last) < config.min_interval as u128 {
+                return Check::TooFast;
+            }
+        }
+
+        let window = config.flood_window as u128 * 1000;
+        while let Some(first) = self.sent.front() {
+            if now.saturating_sub(*first) < window {
+                break;
+            }
+            self.sent.pop_front();
+        }
+
+        self.sent.push_back(now);
+        if self.sent.len() > config.flood_messages {
+            self.sent.clear();
+            self.muted_until = now + config.mute_time as u128 * 1000;
+
+            return Check::Muted(config.mute_time);
+        }
+
+        Check::Allowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{censor, escape, Check, FloodControl};
+    use crate::config::ChatConfig;
+
+    #[test]
+    fn escape_html() {
+        assert_eq!(escape("<b>hi</b>"), "&lt;b>hi&lt;/b>");
+    }
+
+    #[test]
+    fn censor_words() {
+        let words = vec![String::from("cheese"), String::from("Bad")];
+
+        assert_eq!(censor("I want CHEESE", &words), "I want ******");
+        assert_eq!(censor("bad, BAD, bAdly", &words), "***, ***, ***ly");
+        assert_eq!(censor("good", &words), "good");
+        assert_eq!(censor("", &words), "");
+    }
+
+    #[test]
+    fn limit_rate_and_mute() {
+        let config = ChatConfig {
+            min_interval: 500,
+            flood_messages: 3,
+            flood_window: 5,
+            mute_time: 10,
+            ..Default::default()
+        };
+        let mut flood = FloodControl::default();
+
+        assert_eq!(flood.check(1000, &config), Check::Allowed);
+        assert_eq!(flood.check(1200, &config), Check::TooFast);
+        assert_eq!(flood.check(1600, &config), Check::Allowed);
+        assert_eq!(flood.check(2200, &config), Check::Allowed);
+        assert_eq!(flood.check(2800, &config), Check::Muted(10));
+        assert_eq!(flood.check(3800, &config), Check::Muted(9));
+
+        // the mute ends and the old messages are forgotten
+        assert_eq!(flood.check(12800, &config), Check::Allowed);
+        assert_eq!(flood.check(13400, &config), Check::Allowed);
+    }
+
+    #[test]
+    fn forget_old_messages() {
+        let config = ChatConfig {
+            min_interval: 0,
+            flood_messages: 2,
+            flood_window: 1,
+            ..Default::default()
+        };
+        let mut flood = FloodControl::default();
+
+        for i in 0..10 {
+            assert_eq!(flood.check(i * 600, &config), Check::Allowed);
+        }
+    }
+}
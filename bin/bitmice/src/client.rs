@@ -1,28 +1,51 @@
 // SPDX-License-Identifier: BSD-3-Clause
 // Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
 
-use std::{io, net::SocketAddr, sync::Arc, time::UNIX_EPOCH};
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    net::SocketAddr,
+    sync::Arc,
+    time::UNIX_EPOCH,
+};
 use tokio::{
     io::AsyncWriteExt,
-    net::tcp::{OwnedReadHalf, OwnedWriteHalf},
+    net::tcp::OwnedWriteHalf,
     sync::{mpsc::Sender, Mutex},
 };
 
 use crate::{
+    chat::{self, Check, FloodControl},
+    commands::Reply,
+    config::ChatConfig,
+    packets::{
+        send,
+        tribulle::{self, Tribulle},
+    },
+    protocol::Protocol,
     room::{MapType, RoomType},
-    tokens, Result, Room, Server,
+    shaman::ShamanMode,
+    tokens, tribe,
+    tribulle::WhisperState,
+    Result, Room, Server,
+};
+use bitmice_database::{Account, Stats, Tribe, TribePermission, TribeRank};
+use bitmice_utils::{
+    encode_zlib, language_id,
+    packet::{Blob, Packet, Rest},
+    ByteArray,
 };
-use bitmice_utils::{encode_zlib, ByteArray};
 
 #[derive(Debug)]
 pub struct Client {
     address: SocketAddr,
-    pub(super) reader: Arc<Mutex<OwnedReadHalf>>,
     pub(super) writer: Arc<Mutex<OwnedWriteHalf>>,
     pub(super) server: Arc<Mutex<Server>>,
+    pub protocol: Arc<Protocol>,
     pub room: Option<Arc<Mutex<Room>>>,
     pub(super) data_sender: Option<Sender<ByteArray>>,
 
+    pub captcha_code: String,
     pub color: String,
     pub name: String,
     pub nick_color: String,
@@ -33,11 +56,15 @@ pub struct Client {
     pub computer_os: String,
     pub last_room: String,
     pub shaman_color: String,
+    pub tribe_name: String,
+    pub whisper_message: String,
 
     pub id: u32,
+    pub account_id: u32,
     pub auth_key: i32,
     pub gender: u8,
     pub last_response: u128,
+    pub login_time: u64,
     pub(super) packet_id: u8,
     pub position_x: u64,
     pub position_y: u64,
@@ -45,7 +72,15 @@ pub struct Client {
     pub time_played: u64,
     pub title_number: u16,
     pub title_stars: u8,
+    pub tribe_id: u32,
     pub score: u16,
+    /// Points collected on the round of a defilante room.
+    pub defilante_points: u16,
+    /// Milliseconds since the unix epoch of the last defilante point.
+    pub last_defilante_point: u128,
+    /// Milliseconds since the unix epoch of the last answer to the interface
+    /// of a minigame.
+    pub last_ui_answer: u128,
     pub speed_x: u16,
     pub speed_y: u16,
     pub start_time: u128,
@@ -58,27 +93,51 @@ pub struct Client {
     pub is_moving_right: bool,
     pub is_moving_left: bool,
     pub is_shaman: bool,
+    /// The shaman summoned something on the round.
+    pub has_summoned: bool,
+    /// Milliseconds since the unix epoch of the last summon of the round.
+    pub last_summon: u128,
     pub version_validated: bool,
     pub last_ping: bool,
 
     pub ping: (u8, u128),
+    /// Milliseconds taken to answer the last ping.
+    pub latency: u128,
+
+    /// Account ids of the friends and of the ignored players.
+    pub friends: HashSet<u32>,
+    pub ignored: HashSet<u32>,
+    pub whisper_state: WhisperState,
+    /// Mode preferred by the player when it's the shaman.
+    pub shaman_mode: ShamanMode,
+    /// Where the player respawns on bootcamp, saved by `/checkpoint`.
+    pub checkpoint: Option<(i16, i16)>,
+
+    pub tribe_rank: TribeRank,
+    /// Pending tribe invitations, by inviter name.
+    pub tribe_invites: HashMap<String, u32>,
+
+    pub chat_flood: FloodControl,
+
+    pub stats: Stats,
 }
 
 impl Client {
     pub fn new(
         address: SocketAddr,
-        reader: Arc<Mutex<OwnedReadHalf>>,
         writer: Arc<Mutex<OwnedWriteHalf>>,
         server: Arc<Mutex<Server>>,
+        protocol: Arc<Protocol>,
     ) -> Self {
         Self {
             address,
-            reader,
             writer,
             server,
+            protocol,
             room: None,
             data_sender: None,
 
+            captcha_code: String::new(),
             color: String::from("95d9d6"),
             name: String::new(),
             nick_color: String::from("953"),
@@ -89,19 +148,27 @@ impl Client {
             computer_os: String::new(),
             last_room: String::new(),
             shaman_color: String::from("95fe3f"),
+            tribe_name: String::new(),
+            whisper_message: String::new(),
 
             id: 0,
+            account_id: 0,
             auth_key: 0,
             gender: 2,
             last_response: 0,
+            login_time: 0,
             packet_id: 0,
             position_x: 0,
             position_y: 0,
             priv_level: 1,
             title_number: 5,
             title_stars: 3,
+            tribe_id: 0,
             time_played: 0,
             score: 0,
+            defilante_points: 0,
+            last_defilante_point: 0,
+            last_ui_answer: 0,
             speed_x: 0,
             speed_y: 0,
             start_time: 0,
@@ -114,10 +181,26 @@ impl Client {
             is_moving_right: false,
             is_moving_left: false,
             is_shaman: false,
+            has_summoned: false,
+            last_summon: 0,
             version_validated: false,
             last_ping: false,
 
             ping: (0, 0),
+            latency: 0,
+
+            friends: HashSet::new(),
+            ignored: HashSet::new(),
+            whisper_state: WhisperState::Enabled,
+            shaman_mode: ShamanMode::Normal,
+            checkpoint: None,
+
+            tribe_rank: TribeRank::Initiate,
+            tribe_invites: HashMap::new(),
+
+            chat_flood: FloodControl::default(),
+
+            stats: Stats::default(),
         }
     }
 
@@ -139,38 +222,138 @@ impl Client {
         self.is_guest
     }
 
-    pub fn player_data(&self) -> ByteArray {
-        ByteArray::new()
-            .write_utf(&self.full_name()) // player name
-            .write_u32(self.id) // player code
-            .write_bool(self.is_shaman) // is shaman
-            .write_bool(self.is_dead) // is dead
-            .write_u16(self.score) // player score
-            .write_bool(self.has_cheese) // has cheese
-            .write_u16(self.title_number) // title number
-            .write_u8(self.title_stars) // title stars
-            .write_u8(self.gender) // gender
-            .write_utf("") // ??
-            .write_utf(&self.look) // player look
-            .write_bool(false) // ??
-            .write_u32(u32::from_str_radix(&self.color, 16).unwrap()) // mouse color
-            .write_u32(u32::from_str_radix(&self.shaman_color, 16).unwrap()) // shaman color
-            .write_u32(0) // ??
-            .write_u32(u32::from_str_radix(&self.nick_color, 16).unwrap()) // nick color
+    /// Checks a name typed by another player, accounts are only found with
+    /// their tag and guests can be found without their `*`.
+    pub fn has_name(&self, name: &str) -> bool {
+        if self.name.is_empty() {
+            return false;
+        } else if self.is_guest {
+            return self.name.eq_ignore_ascii_case(name)
+                || self.name.eq_ignore_ascii_case(&format!("*{}", name));
+        }
+
+        self.full_name().eq_ignore_ascii_case(name)
+    }
+
+    pub fn load_account(&mut self, account: Account) {
+        self.account_id = account.id;
+        self.name = account.name;
+        self.tag = account.tag;
+
+        self.color = account.color;
+        self.look = account.look;
+        self.nick_color = account.nick_color;
+        self.shaman_color = account.shaman_color;
+
+        self.gender = account.gender;
+        self.priv_level = account.priv_level;
+        self.score = account.score;
+        self.time_played = account.time_played;
+        self.title_number = account.title_number;
+        self.title_stars = account.title_stars;
+        self.shaman_mode = ShamanMode::from_id(account.shaman_mode).unwrap_or_default();
+
+        self.login_time = UNIX_EPOCH.elapsed().unwrap().as_secs();
+    }
+
+    /// Profile to be saved, `None` for guests.
+    pub fn account(&self) -> Option<Account> {
+        if self.is_guest || self.account_id == 0 {
+            return None;
+        }
+
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+        Some(Account {
+            id: self.account_id,
+            name: self.name.clone(),
+            tag: self.tag.clone(),
+
+            color: self.color.clone(),
+            look: self.look.clone(),
+            nick_color: self.nick_color.clone(),
+            shaman_color: self.shaman_color.clone(),
+
+            gender: self.gender,
+            priv_level: self.priv_level,
+            score: self.score,
+            time_played: self.time_played + now.saturating_sub(self.login_time),
+            title_number: self.title_number,
+            title_stars: self.title_stars,
+            shaman_mode: self.shaman_mode.id(),
+            ..Default::default()
+        })
+    }
+
+    pub fn player_data(&self) -> send::PlayerData {
+        send::PlayerData {
+            name: self.full_name(),
+            player_id: self.id,
+            is_shaman: self.is_shaman,
+            is_dead: self.is_dead,
+            score: self.score,
+            has_cheese: self.has_cheese,
+            title_number: self.title_number,
+            title_stars: self.title_stars,
+            gender: self.gender,
+            unknown: String::new(),
+            look: self.look.clone(),
+            unknown_2: false,
+            color: u32::from_str_radix(&self.color, 16).unwrap(),
+            shaman_color: u32::from_str_radix(&self.shaman_color, 16).unwrap(),
+            unknown_3: 0,
+            nick_color: u32::from_str_radix(&self.nick_color, 16).unwrap(),
+        }
+    }
+
+    pub fn join_tribe(&mut self, tribe: &Tribe, rank: TribeRank) {
+        self.tribe_id = tribe.id;
+        self.tribe_name = tribe.name.clone();
+        self.tribe_rank = rank;
+    }
+
+    pub fn leave_tribe(&mut self) {
+        self.tribe_id = 0;
+        self.tribe_name = String::new();
+        self.tribe_rank = TribeRank::Initiate;
+    }
+
+    pub fn can_in_tribe(&self, permission: TribePermission) -> bool {
+        self.tribe_id != 0 && self.tribe_rank.can(permission)
+    }
+
+    /// How this client is shown in the friend list of others.
+    pub fn friend_info(&self) -> tribulle::send::FriendInfo {
+        tribulle::send::FriendInfo {
+            id: self.account_id,
+            name: self.full_name(),
+            gender: self.gender,
+            avatar: 0,
+            is_mutual: false,
+            is_connected: true,
+            community: language_id(&self.lang) as u8,
+            room: self.last_room.clone(),
+            last_connection: self.login_time as u32,
+        }
     }
 
     pub async fn enter_room(&mut self, name: &str) -> Result {
         let server = Arc::clone(&self.server);
         let s = server.lock().await;
 
+        let community = if name.starts_with(tribe::HOUSE_PREFIX) {
+            tribe::HOUSE_COMMUNITY.to_string()
+        } else {
+            self.lang.clone()
+        };
+
         // get room
-        let room = match s.get_room(name.to_string(), self.lang.clone()).await {
+        let room = match s.get_room(name.to_string(), community.clone()).await {
             Some(r) => r,
             None => {
                 if name.is_empty() {
                     s.get_recommended_room(self.lang.clone()).await
                 } else {
-                    let room = Room::new(name.to_string(), self.lang.clone());
+                    let room = Room::new(name.to_string(), community);
                     let r = Arc::new(Mutex::new(room));
                     s.add_room(Arc::clone(&r)).await;
                     r
@@ -180,8 +363,8 @@ impl Client {
         drop(s);
 
         // parse room's name
-        let mut name = name.replace("<", "&lt;");
-        if !name.starts_with("*") && !(name.len() > 3 && name.contains("-") && self.priv_level >= 7)
+        let mut name = chat::escape(name);
+        if !name.starts_with('*') && (name.len() <= 3 || !name.contains('-') || self.priv_level < 7)
         {
             name = format!("{}-{}", self.lang, name);
         }
@@ -210,34 +393,34 @@ impl Client {
         }
 
         // let room be int
-        if name.starts_with("*") {
+        if let Some(int_name) = name.strip_prefix('*') {
             let mut r = room.lock().await;
 
-            r.name = name[1..].to_string();
+            r.name = int_name.to_string();
             r.lang = "int".to_string();
         }
 
         // enter room
-        self.send_data(
-            tokens::send::ENTER_ROOM,
-            ByteArray::new()
-                .write_bool(standard_room)
-                .write_utf(&name)
-                .write_utf(if name.starts_with("*") {
-                    "int"
-                } else {
-                    &self.lang
-                }),
-        )
+        self.send_packet(send::EnterRoom {
+            is_standard: standard_room,
+            name: name.clone(),
+            community: if name.starts_with("*") {
+                String::from("int")
+            } else {
+                self.lang.clone()
+            },
+        })
         .await?;
 
         // update client game type
-        self.send_data(tokens::send::ROOM_SERVER, ByteArray::new().write_i8(0))
-            .await?;
-        self.send_data(
-            tokens::send::ROOM_TYPE,
-            ByteArray::new().write_i8(if name.contains("music") { 11 } else { 4 }),
-        )
+        self.send_packet(send::RoomServer { server: 0 }).await?;
+        self.send_packet(send::RoomType {
+            game_type: if crate::music::is_music_room(&name) {
+                11
+            } else {
+                4
+            },
+        })
         .await?;
 
         self.last_room = name.clone();
@@ -253,6 +436,10 @@ impl Client {
         self.is_moving_right = false;
         self.is_moving_left = false;
         self.is_shaman = false;
+        self.has_summoned = false;
+        self.last_summon = 0;
+        self.checkpoint = None;
+        self.defilante_points = 0;
     }
 
     pub async fn load_map(&mut self, new_map: bool, custom_map: bool) -> Result {
@@ -264,34 +451,34 @@ impl Client {
         };
         let xml = encode_zlib(xml).unwrap();
 
-        let data = ByteArray::new()
-            .write_i32(if new_map || custom_map {
+        let packet = send::NewMap {
+            map_code: if new_map || custom_map {
                 r.map_code
             } else {
                 -1
-            })
-            .write_i16(r.players().len() as i16)
-            .write_i8(r.last_round_code)
-            .write_i32(xml.len() as i32)
-            .write_bytes(xml)
-            .write_utf(if new_map {
+            },
+            players: r.players().len() as i16,
+            round_code: r.last_round_code,
+            xml: Blob(xml),
+            author: String::from(if new_map {
                 ""
             } else if custom_map {
                 &r.name
             } else {
                 "-"
-            })
-            .write_i8(if new_map {
+            }),
+            perma: if new_map {
                 0
             } else if custom_map {
                 r.map_perma
             } else {
                 100
-            })
-            .write_bool(if custom_map { r.is_inverted_map } else { false });
+            },
+            is_inverted: custom_map && r.is_inverted_map,
+        };
 
         drop(r);
-        self.send_data(tokens::send::NEW_MAP, data).await?;
+        self.send_packet(packet).await?;
 
         Ok(())
     }
@@ -306,26 +493,16 @@ impl Client {
         Ok(())
     }
 
-    pub async fn get_cheese(&mut self) -> Result {
-        let room = self.room.as_mut().unwrap().lock().await;
-
-        if !self.has_cheese {
-            self.has_cheese = true;
-            room.send_data(
-                tokens::send::PLAYER_GET_CHEESE,
-                ByteArray::new().write_u32(self.id).write_bool(true),
-            )
-            .await?;
-
-            let map_type = room.map_type;
-            drop(room);
-            if map_type == MapType::Tutorial {
-                self.send_data(tokens::send::TUTORIAL, ByteArray::new().write_i8(1))
-                    .await?;
-            }
-        }
+    pub async fn send_packet<P: Packet>(&mut self, packet: P) -> Result {
+        self.send_data(P::TOKENS, packet.encode()).await
+    }
 
-        Ok(())
+    pub async fn send_tribulle<T: Tribulle>(&mut self, packet: T) -> Result {
+        self.send_packet(send::Tribulle {
+            code: T::CODE,
+            data: Rest(packet.encode().to_vec()),
+        })
+        .await
     }
 
     pub async fn send_data(&mut self, tokens: (u8, u8), data: ByteArray) -> Result {
@@ -340,17 +517,18 @@ impl Client {
         let mut calc = length >> 7;
         while calc != 0 {
             b2 = b2.write_u8(((length & 127) | 128) as u8);
-            length = calc.clone();
-            calc = calc >> 7;
+            length = calc;
+            calc >>= 7;
         }
         b2 = b2.write_u8((length & 127) as u8);
 
+        let tokens = self.protocol.send_tokens(tokens);
         b = b
             .write_bytes(b2)
             .write_u8(tokens.0)
             .write_u8(tokens.1)
             .write_bytes(data);
-        if let Err(_) = self.data_sender.as_ref().unwrap().send(b).await {
+        if self.data_sender.as_ref().unwrap().send(b).await.is_err() {
             log::error!("failed to send data to writer");
         }
 
@@ -385,28 +563,130 @@ impl Client {
     pub fn update_last_response(&mut self) {
         self.last_response = UNIX_EPOCH.elapsed().unwrap().as_millis();
     }
+
+    /// Sends a reply in the language of the client.
+    pub async fn reply(&mut self, reply: Reply) -> Result {
+        let message = reply.text(&self.lang);
+
+        self.send_packet(send::Message { message }).await
+    }
+
+    /// Checks a message of the player against its flood control, telling it
+    /// when it's muted. Messages that aren't allowed must be dropped.
+    pub async fn can_chat(
+        &mut self,
+        config: &ChatConfig,
+    ) -> std::result::Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let now = UNIX_EPOCH.elapsed().unwrap().as_millis();
+        match self.chat_flood.check(now, config) {
+            Check::Allowed => Ok(true),
+            Check::TooFast => Ok(false),
+            Check::Muted(seconds) => {
+                self.reply(Reply::Muted(seconds)).await?;
+                Ok(false)
+            }
+        }
+    }
 }
 
 pub async fn die(client_: Arc<Mutex<Client>>) -> Result {
     let mut client = client_.lock().await;
+    let was_dead = client.is_dead;
 
     client.score += 1;
     client.has_cheese = false;
     client.is_dead = true;
 
     let room = client.room.clone();
-    let r = room.as_ref().unwrap().lock().await;
-
     let data = format!("{}{}", client.id, client.score).as_bytes().to_vec();
     drop(client);
 
+    let r = room.as_ref().unwrap().lock().await;
+
     r.send_old_data(tokens::old::send::PLAYER_DIED, ByteArray::with(data))
         .await?;
+    let respawns = !was_dead && matches!(r.room_type, RoomType::Bootcamp | RoomType::Village);
+    drop(r);
+
+    if respawns {
+        respawn(client_).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn get_cheese(client: Arc<Mutex<Client>>) -> Result {
+    let mut c = client.lock().await;
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    if c.has_cheese {
+        return Ok(());
+    }
+
+    c.has_cheese = true;
+    let player_id = c.id;
+    drop(c);
+
+    let r = room.lock().await;
+    r.send_packet(send::PlayerGetCheese {
+        player_id,
+        has_cheese: true,
+    })
+    .await?;
+    let map_type = r.map_type;
+    drop(r);
+
+    if map_type == MapType::Tutorial {
+        let mut c = client.lock().await;
+        c.send_packet(send::Tutorial { step: 1 }).await?;
+    }
+
+    Ok(())
+}
+
+/// Brings a dead player back to the round, at its checkpoint if it has one.
+pub async fn respawn(client: Arc<Mutex<Client>>) -> Result {
+    let mut c = client.lock().await;
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+
+    c.has_cheese = false;
+    c.is_dead = false;
+    let packet = send::PlayerRespawn {
+        player: c.player_data(),
+        is_reborn: true,
+        display: true,
+    };
+    let checkpoint = c.checkpoint;
+    drop(c);
+
+    room.lock().await.send_packet(packet).await?;
+
+    if let Some((position_x, position_y)) = checkpoint {
+        let mut c = client.lock().await;
+        c.send_packet(send::MovePlayer {
+            position_x,
+            position_y,
+            position_offset: false,
+            speed_x: 0,
+            speed_y: 0,
+            speed_offset: false,
+        })
+        .await?;
+    }
 
     Ok(())
 }
 
 pub async fn start_play(client: Arc<Mutex<Client>>) -> Result {
+    // the server isn't locked with the client, `Server::get_player` locks the clients
+    let server = Arc::clone(&client.lock().await.server);
+    let village = Arc::clone(&server.lock().await.village);
+
     let mut c = client.lock().await;
 
     // load map
@@ -430,15 +710,48 @@ pub async fn start_play(client: Arc<Mutex<Client>>) -> Result {
     drop(c);
     let players = r.players();
 
-    let mut data = ByteArray::new().write_i16(players.len() as i16);
+    let mut player_list = Vec::new();
     for player in players {
         let player = player.lock().await;
 
-        data = data.write_bytes(player.player_data());
+        player_list.push(player.player_data());
     }
 
     let mut c = client.lock().await;
-    c.send_data(tokens::send::PLAYER_LIST, data).await?;
+    c.send_packet(send::PlayerList {
+        players: player_list,
+    })
+    .await?;
+
+    // shamans and their anchors
+    if let Some(shaman) = r.shamans.first() {
+        let second = r.shamans.get(1);
+        c.send_packet(send::ShamanInfo {
+            shaman_id: shaman.id,
+            second_shaman_id: second.map(|s| s.id).unwrap_or_default(),
+            shaman_mode: shaman.mode.id(),
+            second_shaman_mode: second.map(|s| s.mode.id()).unwrap_or_default(),
+            shaman_level: 1,
+            second_shaman_level: 1,
+            shaman_badge: 0,
+            second_shaman_badge: 0,
+        })
+        .await?;
+    }
+    if !r.anchors.is_empty() {
+        c.send_old_data(
+            tokens::old::send::ANCHORS,
+            ByteArray::with(r.anchors.join("\x01").into_bytes()),
+        )
+        .await?;
+    }
+    // the video playing on music rooms, from where it's at
+    if let Some(packet) = crate::music::video_packet(&r.playlist) {
+        c.send_packet(packet).await?;
+    }
+    if r.room_type == RoomType::Village {
+        crate::village::send_npcs(&mut c, &village).await?;
+    }
     drop(c);
 
     // sync users
@@ -451,10 +764,9 @@ pub async fn start_play(client: Arc<Mutex<Client>>) -> Result {
     let r = room.lock().await;
     let round_time = r.round_time;
     drop(r);
-    c.send_data(
-        tokens::send::ROUND_TIME,
-        ByteArray::new().write_i16(if round_time < 0 { 0 } else { round_time }),
-    )
+    c.send_packet(send::RoundTime {
+        time: round_time.max(0),
+    })
     .await?;
 
     // map start time
@@ -475,3 +787,48 @@ pub async fn start_play(client: Arc<Mutex<Client>>) -> Result {
 
     Ok(())
 }
+
+/// Moves a client from its room to the room called `name`, a recommended one
+/// is chosen when it's empty.
+pub async fn join_room(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    name: &str,
+) -> Result {
+    if let Some(reply) = crate::minigame::check_room(&client, &server, name).await {
+        return crate::commands::reply(&client, reply).await;
+    }
+
+    let mut c = client.lock().await;
+    let client_id = c.id;
+    let player_name = c.full_name();
+    let old_room = c.room.take();
+    drop(c);
+
+    if let Some(room) = old_room {
+        room.lock().await.remove_client(client_id).await;
+        crate::minigame::emit(&room, "eventPlayerLeft", vec![player_name.as_str().into()]).await?;
+    }
+
+    let mut c = client.lock().await;
+    c.enter_room(name).await?;
+    let room = Arc::clone(c.room.as_ref().unwrap());
+    drop(c);
+
+    let mut r = room.lock().await;
+    r.add_client(Arc::clone(&client)).await?;
+    let is_new = r.is_new;
+    drop(r);
+
+    if is_new {
+        if let Err(e) = crate::minigame::start(&room, &server).await {
+            log::error!("failed to start the minigame of [{}]: {}", name, e);
+        }
+        crate::room::trigger(room).await?;
+    } else {
+        start_play(Arc::clone(&client)).await?;
+        crate::minigame::emit(&room, "eventNewPlayer", vec![player_name.into()]).await?;
+    }
+
+    crate::tribulle::update(client, server).await
+}
@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::{Args, Command, Failure, Outcome, Reply, GUEST};
+use crate::{room::RoomType, Client, Server};
+
+pub const COMMAND: Command = Command {
+    name: "checkpoint",
+    aliases: &["cp"],
+    priv_level: GUEST,
+    usage: "/checkpoint [off]",
+    help: "saves your position to respawn there on bootcamp",
+};
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    args: Args,
+) -> Outcome {
+    let remove = match args.get(0) {
+        Some("off") => true,
+        Some(_) => return Err(Failure::Usage),
+        None => false,
+    };
+
+    let c = client.lock().await;
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    let position = (c.position_x as i16, c.position_y as i16);
+    let is_dead = c.is_dead;
+    drop(c);
+
+    if room.lock().await.room_type != RoomType::Bootcamp {
+        return Err(Reply::BootcampOnly.into());
+    }
+
+    let reply = if remove {
+        client.lock().await.checkpoint = None;
+        Reply::CheckpointRemoved
+    } else if is_dead {
+        return Ok(());
+    } else {
+        client.lock().await.checkpoint = Some(position);
+        Reply::CheckpointSaved
+    };
+
+    super::reply(&client, reply).await?;
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::{Args, Command, Outcome, Reply, COMMANDS, GUEST};
+use crate::{Client, Server};
+
+pub const COMMAND: Command = Command {
+    name: "help",
+    aliases: &["commands"],
+    priv_level: GUEST,
+    usage: "/help",
+    help: "lists the commands you can use",
+};
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    _args: Args,
+) -> Outcome {
+    let priv_level = client.lock().await.priv_level;
+
+    let commands = COMMANDS
+        .iter()
+        .filter(|c| priv_level >= c.priv_level)
+        .map(|c| (c.usage, c.help))
+        .collect();
+    super::reply(&client, Reply::Help(commands)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::{Args, Command, Outcome, Reply, GUEST};
+use crate::{Client, Server};
+
+pub const COMMAND: Command = Command {
+    name: "minigames",
+    aliases: &["modules"],
+    priv_level: GUEST,
+    usage: "/minigames",
+    help: "lists the minigames, played on the rooms named #name",
+};
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    _args: Args,
+) -> Outcome {
+    let registry = Arc::clone(&server.lock().await.minigames);
+    let minigames = registry
+        .minigames
+        .iter()
+        .map(|m| (m.name.clone(), m.description.clone(), m.max_players))
+        .collect();
+
+    super::reply(&client, Reply::Minigames(minigames)).await?;
+
+    Ok(())
+}
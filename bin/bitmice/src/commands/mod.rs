@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+mod checkpoint;
+mod help;
+mod minigames;
+mod module;
+mod mort;
+mod np;
+mod ping;
+mod profile;
+mod reply;
+mod room;
+mod skip;
+mod time;
+mod title;
+mod who;
+
+pub use reply::Reply;
+
+use std::{error::Error, sync::Arc};
+
+use tokio::sync::Mutex;
+
+use crate::{Client, Result, Server};
+
+/// Levels of `Client::priv_level`, as handled by `login::identification`.
+pub const GUEST: i8 = 0;
+pub const PLAYER: i8 = 1;
+pub const MAPCREW: i8 = 6;
+pub const ADMIN: i8 = 9;
+
+#[derive(Debug)]
+pub struct Command {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    /// Lowest `priv_level` allowed to use it.
+    pub priv_level: i8,
+    pub usage: &'static str,
+    pub help: &'static str,
+}
+
+/// Why a command didn't run, replied to the player unless it's an error.
+#[derive(Debug)]
+pub enum Failure {
+    /// The arguments don't match the usage of the command.
+    Usage,
+    Reply(Reply),
+    Error(Box<dyn Error + Send + Sync>),
+}
+
+impl From<Reply> for Failure {
+    fn from(reply: Reply) -> Self {
+        Self::Reply(reply)
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for Failure {
+    fn from(error: Box<dyn Error + Send + Sync>) -> Self {
+        Self::Error(error)
+    }
+}
+
+pub type Outcome = std::result::Result<(), Failure>;
+
+/// Arguments typed after the name of a command.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Args(Vec<String>);
+
+impl Args {
+    /// Splits a command line into its lowercase name and its arguments.
+    pub fn parse(line: &str) -> (String, Self) {
+        let mut words = line.trim().trim_start_matches('/').split_whitespace();
+        let name = words.next().unwrap_or_default().to_lowercase();
+
+        (name, Self(words.map(String::from).collect()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.0.get(index).map(String::as_str)
+    }
+
+    /// Arguments from `index` joined back by spaces.
+    pub fn rest(&self, index: usize) -> String {
+        self.0.get(index..).unwrap_or_default().join(" ")
+    }
+}
+
+/// Lists the commands and generates their dispatcher.
+macro_rules! commands {
+    ($($module:ident,)*) => {
+        pub const COMMANDS: &[&Command] = &[$(&$module::COMMAND,)*];
+
+        async fn dispatch(
+            command: &Command,
+            client: Arc<Mutex<Client>>,
+            server: Arc<Mutex<Server>>,
+            args: Args,
+        ) -> Outcome {
+            $(
+                if command.name == $module::COMMAND.name {
+                    return $module::handle(client, server, args).await;
+                }
+            )*
+
+            Ok(())
+        }
+    };
+}
+
+commands! {
+    help,
+    room,
+    mort,
+    np,
+    time,
+    ping,
+    who,
+    title,
+    profile,
+    checkpoint,
+    skip,
+    module,
+    minigames,
+}
+
+/// Command called by `name`, or by one of its aliases.
+pub fn find(name: &str) -> Option<&'static Command> {
+    COMMANDS
+        .iter()
+        .find(|c| c.name == name || c.aliases.contains(&name))
+        .copied()
+}
+
+/// Runs a command typed by a player.
+pub async fn run(client: Arc<Mutex<Client>>, server: Arc<Mutex<Server>>, line: &str) -> Result {
+    let (name, args) = Args::parse(line);
+    if name.is_empty() {
+        return Ok(());
+    }
+
+    let c = client.lock().await;
+    let priv_level = c.priv_level;
+    log::info!("[{}] used the command [{}]", c.full_name(), line.trim());
+    drop(c);
+
+    let r = match find(&name) {
+        Some(command) if priv_level >= command.priv_level => {
+            match dispatch(command, Arc::clone(&client), server, args).await {
+                Ok(()) => return Ok(()),
+                Err(Failure::Usage) => Reply::Usage(command.usage),
+                Err(Failure::Reply(r)) => r,
+                Err(Failure::Error(e)) => return Err(e),
+            }
+        }
+        Some(_) => Reply::NotAllowed,
+        None => Reply::UnknownCommand(name),
+    };
+
+    reply(&client, r).await
+}
+
+/// Sends a reply in the language of the client.
+pub async fn reply(client: &Arc<Mutex<Client>>, reply: Reply) -> Result {
+    client.lock().await.reply(reply).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, Args, COMMANDS};
+
+    #[test]
+    fn parse_args() {
+        let (name, args) = Args::parse("/NP  @123 now");
+        assert_eq!(name, "np");
+        assert_eq!(args.len(), 2);
+        assert_eq!(args.get(0), Some("@123"));
+        assert_eq!(args.rest(1), "now");
+        assert_eq!(args.rest(5), "");
+
+        let (name, args) = Args::parse("mort");
+        assert_eq!(name, "mort");
+        assert!(args.is_empty());
+    }
+
+    #[test]
+    fn find_commands() {
+        assert_eq!(find("mort").map(|c| c.name), Some("mort"));
+        assert_eq!(find("kill").map(|c| c.name), Some("mort"));
+        assert!(find("unknown").is_none());
+
+        for command in COMMANDS {
+            assert_eq!(find(command.name).map(|c| c.name), Some(command.name));
+            assert!(command.usage.starts_with(&format!("/{}", command.name)));
+        }
+    }
+}
@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::{Args, Command, Failure, Outcome, Reply, ADMIN};
+use crate::{minigame, Client, Server};
+
+pub const COMMAND: Command = Command {
+    name: "module",
+    aliases: &["lua"],
+    priv_level: ADMIN,
+    usage: "/module <name|reload>",
+    help: "runs a minigame on your room, or loads its script again",
+};
+
+pub async fn handle(client: Arc<Mutex<Client>>, server: Arc<Mutex<Server>>, args: Args) -> Outcome {
+    let name = match args.get(0) {
+        Some(n) if args.len() == 1 => n.to_lowercase(),
+        _ => return Err(Failure::Usage),
+    };
+
+    let room = match client.lock().await.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+
+    // the script is read again, the players stay on the room
+    let name = if name == "reload" {
+        match minigame::running(&room).await {
+            Some(n) => n,
+            None => return Err(Reply::NoModule.into()),
+        }
+    } else {
+        match server.lock().await.minigames.get(&name) {
+            Some(m) => m.name.clone(),
+            None => return Err(Reply::ModuleNotFound(name).into()),
+        }
+    };
+
+    let limits = server.lock().await.config.minigames.limits();
+    if let Err(e) = minigame::load(&room, &name, limits).await {
+        return Err(Reply::ModuleFailed(name, e.to_string()).into());
+    }
+
+    super::reply(&client, Reply::ModuleLoaded(name)).await?;
+
+    Ok(())
+}
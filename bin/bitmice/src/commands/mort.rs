@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::{Args, Command, Outcome, GUEST};
+use crate::{client, minigame, Client, Server};
+
+pub const COMMAND: Command = Command {
+    name: "mort",
+    aliases: &["kill", "die"],
+    priv_level: GUEST,
+    usage: "/mort",
+    help: "kills your mouse",
+};
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    _args: Args,
+) -> Outcome {
+    let c = client.lock().await;
+    let room = match c.room.clone() {
+        Some(r) if !c.is_dead => r,
+        _ => return Ok(()),
+    };
+    let name = c.full_name();
+    drop(c);
+
+    client::die(client).await?;
+    minigame::emit(&room, "eventPlayerDied", vec![name.into()]).await?;
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::{Args, Command, Failure, Outcome, MAPCREW};
+use crate::{room, Client, Server};
+
+pub const COMMAND: Command = Command {
+    name: "np",
+    aliases: &["map"],
+    priv_level: MAPCREW,
+    usage: "/np <@code|#perm>",
+    help: "starts a new round with the given map",
+};
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    args: Args,
+) -> Outcome {
+    if args.len() != 1 {
+        return Err(Failure::Usage);
+    }
+    let next_map = match args.get(0).and_then(parse_map) {
+        Some(m) => m,
+        None => return Err(Failure::Usage),
+    };
+
+    let room = match client.lock().await.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    room.lock().await.next_map = next_map;
+    room::change_map(room).await?;
+
+    Ok(())
+}
+
+/// Checks a map typed by the player, only custom maps and categories can be
+/// chosen.
+fn parse_map(map: &str) -> Option<String> {
+    let is_allowed = map.starts_with('@') || map.starts_with('#');
+
+    (is_allowed && room::is_valid_map(map)).then(|| map.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_map;
+
+    #[test]
+    fn parse_maps() {
+        assert_eq!(parse_map("@123"), Some(String::from("@123")));
+        assert_eq!(parse_map("#7"), Some(String::from("#7")));
+        assert_eq!(parse_map("@-1"), None);
+        assert_eq!(parse_map("#999"), None);
+        assert_eq!(parse_map("123"), None);
+        assert_eq!(parse_map("@abc"), None);
+    }
+}
@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::{Args, Command, Outcome, Reply, GUEST};
+use crate::{Client, Server};
+
+pub const COMMAND: Command = Command {
+    name: "ping",
+    aliases: &[],
+    priv_level: GUEST,
+    usage: "/ping",
+    help: "shows your latency",
+};
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    _args: Args,
+) -> Outcome {
+    let latency = client.lock().await.latency;
+    super::reply(&client, Reply::Ping(latency)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::{Args, Command, Outcome, Reply, PLAYER};
+use crate::{Client, Server};
+
+pub const COMMAND: Command = Command {
+    name: "profile",
+    aliases: &["perfil"],
+    priv_level: PLAYER,
+    usage: "/profile [player]",
+    help: "shows the statistics of a player in the game",
+};
+
+pub async fn handle(client: Arc<Mutex<Client>>, server: Arc<Mutex<Server>>, args: Args) -> Outcome {
+    let player = match args.get(0) {
+        Some(name) => {
+            let player = server.lock().await.get_player(name.to_string()).await;
+            match player {
+                Some(p) => p,
+                None => return Err(Reply::PlayerNotFound(name.to_string()).into()),
+            }
+        }
+        None => Arc::clone(&client),
+    };
+
+    let p = player.lock().await;
+    let profile = Reply::Profile(p.full_name(), p.stats.clone());
+    drop(p);
+
+    super::reply(&client, profile).await?;
+
+    Ok(())
+}
@@ -0,0 +1,445 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use bitmice_database::Stats;
+
+/// Messages sent to a player in its language, english is used when there's no
+/// translation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    UnknownCommand(String),
+    NotAllowed,
+    Usage(&'static str),
+    InvalidRoom,
+    PlayerNotFound(String),
+    /// Usage and help of the commands a player can use.
+    Help(Vec<(&'static str, &'static str)>),
+    Ping(u128),
+    TimePlayed(u64),
+    Title(u16),
+    TitleChanged(u16),
+    CheckpointSaved,
+    CheckpointRemoved,
+    /// The command can only be used on bootcamp rooms.
+    BootcampOnly,
+    MusicRoomOnly,
+    InvalidVideo,
+    VideoQueued,
+    /// The player already has a video waiting on the playlist.
+    VideoAlreadyQueued,
+    PlaylistFull,
+    NothingPlaying,
+    /// Votes to skip the video playing and the players on the room.
+    SkipVoted(usize, usize),
+    /// Something said by an NPC.
+    NpcTalk(String, String),
+    /// Description of a quest, the progress and the amount to reach.
+    QuestProgress(String, u32, u32),
+    QuestCompleted(String),
+    ModuleLoaded(String),
+    ModuleNotFound(String),
+    /// Name of the module and why it couldn't run.
+    ModuleFailed(String, String),
+    /// The room isn't running a module.
+    NoModule,
+    RoomFull(String),
+    /// Name, description and player cap of the minigames.
+    Minigames(Vec<(String, String, usize)>),
+    Who(String, Vec<String>),
+    Profile(String, Stats),
+    /// Seconds left before the player can chat again.
+    Muted(u64),
+    /// The first mouse to finish a race, its time and the record of the map,
+    /// the times are in centiseconds.
+    RaceFirst(String, u32, Option<(String, u32)>),
+    RaceRecord(String, u32),
+    /// Points collected on a defilante round, from the highest to the lowest.
+    DefilanteTally(Vec<(String, u16)>),
+}
+
+impl Reply {
+    pub fn text(&self, lang: &str) -> String {
+        match self {
+            Self::UnknownCommand(name) => match lang {
+                "br" => format!("Comando desconhecido: /{}", name),
+                "es" => format!("Comando desconocido: /{}", name),
+                _ => format!("Unknown command: /{}", name),
+            },
+            Self::NotAllowed => match lang {
+                "br" => "Você não tem permissão para usar este comando.",
+                "es" => "No tienes permiso para usar este comando.",
+                _ => "You aren't allowed to use this command.",
+            }
+            .to_string(),
+            Self::Usage(usage) => match lang {
+                "br" | "es" => format!("Uso: {}", usage),
+                _ => format!("Usage: {}", usage),
+            },
+            Self::InvalidRoom => match lang {
+                "br" => "Nome de sala inválido.",
+                "es" => "Nombre de sala inválido.",
+                _ => "Invalid room name.",
+            }
+            .to_string(),
+            Self::PlayerNotFound(name) => match lang {
+                "br" => format!("O jogador {} não está online.", name),
+                "es" => format!("El jugador {} no está conectado.", name),
+                _ => format!("The player {} isn't online.", name),
+            },
+            Self::Help(commands) => {
+                let mut text = match lang {
+                    "br" => "Comandos disponíveis:",
+                    "es" => "Comandos disponibles:",
+                    _ => "Available commands:",
+                }
+                .to_string();
+                for (usage, help) in commands {
+                    text.push_str(&format!("\n{} - {}", usage, help));
+                }
+
+                text
+            }
+            Self::Ping(ms) => format!("Ping: {} ms", ms),
+            Self::TimePlayed(seconds) => {
+                let (d, h, m, s) = (
+                    seconds / 86400,
+                    seconds / 3600 % 24,
+                    seconds / 60 % 60,
+                    seconds % 60,
+                );
+
+                match lang {
+                    "br" => format!("Tempo jogado: {}d {}h {}min {}s", d, h, m, s),
+                    "es" => format!("Tiempo jugado: {}d {}h {}min {}s", d, h, m, s),
+                    _ => format!("Time played: {}d {}h {}min {}s", d, h, m, s),
+                }
+            }
+            Self::Title(number) => match lang {
+                "br" => format!("Título atual: {}", number),
+                "es" => format!("Título actual: {}", number),
+                _ => format!("Current title: {}", number),
+            },
+            Self::TitleChanged(number) => match lang {
+                "br" => format!("Título alterado para {}.", number),
+                "es" => format!("Título cambiado a {}.", number),
+                _ => format!("Title changed to {}.", number),
+            },
+            Self::CheckpointSaved => match lang {
+                "br" => "Checkpoint salvo.",
+                "es" => "Checkpoint guardado.",
+                _ => "Checkpoint saved.",
+            }
+            .to_string(),
+            Self::CheckpointRemoved => match lang {
+                "br" => "Checkpoint removido.",
+                "es" => "Checkpoint eliminado.",
+                _ => "Checkpoint removed.",
+            }
+            .to_string(),
+            Self::BootcampOnly => match lang {
+                "br" => "Este comando só pode ser usado no bootcamp.",
+                "es" => "Este comando solo se puede usar en bootcamp.",
+                _ => "This command can only be used on bootcamp.",
+            }
+            .to_string(),
+            Self::MusicRoomOnly => match lang {
+                "br" => "Este comando só pode ser usado em salas de música.",
+                "es" => "Este comando solo se puede usar en salas de música.",
+                _ => "This command can only be used on music rooms.",
+            }
+            .to_string(),
+            Self::InvalidVideo => match lang {
+                "br" => "Vídeo inválido.",
+                "es" => "Video inválido.",
+                _ => "Invalid video.",
+            }
+            .to_string(),
+            Self::VideoQueued => match lang {
+                "br" => "Vídeo adicionado à playlist.",
+                "es" => "Video añadido a la lista.",
+                _ => "Video added to the playlist.",
+            }
+            .to_string(),
+            Self::VideoAlreadyQueued => match lang {
+                "br" => "Você já tem um vídeo na playlist.",
+                "es" => "Ya tienes un video en la lista.",
+                _ => "You already have a video on the playlist.",
+            }
+            .to_string(),
+            Self::PlaylistFull => match lang {
+                "br" => "A playlist está cheia.",
+                "es" => "La lista está llena.",
+                _ => "The playlist is full.",
+            }
+            .to_string(),
+            Self::NothingPlaying => match lang {
+                "br" => "Nenhum vídeo está tocando.",
+                "es" => "No se está reproduciendo ningún video.",
+                _ => "No video is playing.",
+            }
+            .to_string(),
+            Self::SkipVoted(votes, players) => match lang {
+                "br" => format!("Votos para pular o vídeo: {}/{}", votes, players),
+                "es" => format!("Votos para saltar el video: {}/{}", votes, players),
+                _ => format!("Votes to skip the video: {}/{}", votes, players),
+            },
+            Self::NpcTalk(name, text) => format!("[{}] {}", name, text),
+            Self::QuestProgress(quest, progress, amount) => match lang {
+                "br" => format!("Missão: {} ({}/{})", quest, progress, amount),
+                "es" => format!("Misión: {} ({}/{})", quest, progress, amount),
+                _ => format!("Quest: {} ({}/{})", quest, progress, amount),
+            },
+            Self::QuestCompleted(quest) => match lang {
+                "br" => format!("Missão concluída: {}", quest),
+                "es" => format!("Misión completada: {}", quest),
+                _ => format!("Quest completed: {}", quest),
+            },
+            Self::ModuleLoaded(name) => match lang {
+                "br" => format!("Módulo {} carregado.", name),
+                "es" => format!("Módulo {} cargado.", name),
+                _ => format!("Module {} loaded.", name),
+            },
+            Self::ModuleNotFound(name) => match lang {
+                "br" => format!("O módulo {} não existe.", name),
+                "es" => format!("El módulo {} no existe.", name),
+                _ => format!("The module {} doesn't exist.", name),
+            },
+            Self::ModuleFailed(name, error) => match lang {
+                "br" => format!("Falha ao carregar o módulo {}: {}", name, error),
+                "es" => format!("Error al cargar el módulo {}: {}", name, error),
+                _ => format!("Failed to load the module {}: {}", name, error),
+            },
+            Self::NoModule => match lang {
+                "br" => "Esta sala não está rodando um módulo.",
+                "es" => "Esta sala no está ejecutando un módulo.",
+                _ => "This room isn't running a module.",
+            }
+            .to_string(),
+            Self::RoomFull(name) => match lang {
+                "br" => format!("A sala {} está cheia.", name),
+                "es" => format!("La sala {} está llena.", name),
+                _ => format!("The room {} is full.", name),
+            },
+            Self::Minigames(minigames) => {
+                let mut text = match lang {
+                    "br" => "Minigames disponíveis:",
+                    "es" => "Minijuegos disponibles:",
+                    _ => "Available minigames:",
+                }
+                .to_string();
+                for (name, description, max_players) in minigames {
+                    text.push_str(&format!("\n#{} ({}) - {}", name, max_players, description));
+                }
+
+                text
+            }
+            Self::Who(room, players) => {
+                let (count, names) = (players.len(), players.join(", "));
+
+                match lang {
+                    "br" => format!("Jogadores na sala {} ({}): {}", room, count, names),
+                    "es" => format!("Jugadores en la sala {} ({}): {}", room, count, names),
+                    _ => format!("Players in the room {} ({}): {}", room, count, names),
+                }
+            }
+            Self::Profile(name, stats) => {
+                let label = |name| stat_label(lang, name);
+
+                let mut text = name.clone();
+                text.push_str(&format!(
+                    "\n{}: {} / {} / {}",
+                    label("saves"),
+                    stats.saves,
+                    stats.hard_saves,
+                    stats.divine_saves
+                ));
+                text.push_str(&format!(
+                    "\n{}: {}, {}: {}",
+                    label("cheese"),
+                    stats.cheese,
+                    label("firsts"),
+                    stats.first
+                ));
+                text.push_str(&format!(
+                    "\n{}: {} / {}, {}: {}, {}: {}",
+                    label("races"),
+                    stats.racing_finishes,
+                    stats.racing_rounds,
+                    label("podiums"),
+                    stats.racing_podiums,
+                    label("firsts"),
+                    stats.racing_firsts
+                ));
+                text.push_str(&format!("\n{}: {}", label("bootcamp"), stats.bootcamp));
+                text.push_str(&format!(
+                    "\n{}: {} / {}, {}: {}",
+                    label("survived"),
+                    stats.survivor_survived,
+                    stats.survivor_rounds,
+                    label("kills"),
+                    stats.survivor_kills
+                ));
+                text.push_str(&format!(
+                    "\n{}: {} / {}",
+                    label("defilante"),
+                    stats.defilante_points,
+                    stats.defilante_rounds
+                ));
+
+                text
+            }
+            Self::Muted(seconds) => match lang {
+                "br" => format!("Você está silenciado por {} segundos.", seconds),
+                "es" => format!("Estás silenciado por {} segundos.", seconds),
+                _ => format!("You are muted for {} seconds.", seconds),
+            },
+            Self::RaceFirst(name, time, record) => {
+                let time = format_time(*time);
+                let mut text = match lang {
+                    "br" => format!("{} chegou em primeiro em {}s.", name, time),
+                    "es" => format!("{} llegó primero en {}s.", name, time),
+                    _ => format!("{} finished first in {}s.", name, time),
+                };
+
+                if let Some((name, time)) = record {
+                    let time = format_time(*time);
+                    text.push_str(&match lang {
+                        "br" => format!(" Recorde do mapa: {} ({}s).", name, time),
+                        "es" => format!(" Récord del mapa: {} ({}s).", name, time),
+                        _ => format!(" Map record: {} ({}s).", name, time),
+                    });
+                }
+
+                text
+            }
+            Self::RaceRecord(name, time) => {
+                let time = format_time(*time);
+
+                match lang {
+                    "br" => format!("Novo recorde do mapa por {}: {}s!", name, time),
+                    "es" => format!("¡Nuevo récord del mapa de {}: {}s!", name, time),
+                    _ => format!("New map record by {}: {}s!", name, time),
+                }
+            }
+            Self::DefilanteTally(points) => {
+                let mut text = match lang {
+                    "br" => "Pontos da rodada:",
+                    "es" => "Puntos de la ronda:",
+                    _ => "Points of the round:",
+                }
+                .to_string();
+                for (i, (name, points)) in points.iter().enumerate() {
+                    text.push_str(&format!("\n{}. {} - {}", i + 1, name, points));
+                }
+
+                text
+            }
+        }
+    }
+}
+
+/// Formats centiseconds as seconds, like `12.34`.
+fn format_time(time: u32) -> String {
+    format!("{}.{:02}", time / 100, time % 100)
+}
+
+/// Names of the counters shown on the profile.
+fn stat_label(lang: &str, name: &str) -> &'static str {
+    match (lang, name) {
+        ("br", "saves") => "Salvos",
+        ("br", "cheese") => "Queijos",
+        ("br", "firsts") => "Primeiros",
+        ("br", "races") => "Corridas",
+        ("br", "podiums") => "Pódios",
+        ("br", "survived") => "Sobrevivências",
+        ("br", "kills") => "Mortes",
+        ("es", "saves") => "Salvados",
+        ("es", "cheese") => "Quesos",
+        ("es", "firsts") => "Primeros",
+        ("es", "races") => "Carreras",
+        ("es", "podiums") => "Podios",
+        ("es", "survived") => "Supervivencias",
+        ("es", "kills") => "Muertes",
+        (_, "saves") => "Saves",
+        (_, "cheese") => "Cheese",
+        (_, "firsts") => "Firsts",
+        (_, "races") => "Races",
+        (_, "podiums") => "Podiums",
+        (_, "bootcamp") => "Bootcamp",
+        (_, "defilante") => "Defilante",
+        (_, "survived") => "Survived",
+        (_, "kills") => "Kills",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitmice_database::Stats;
+
+    use super::{format_time, Reply};
+
+    #[test]
+    fn translate() {
+        let reply = Reply::UnknownCommand(String::from("foo"));
+        assert_eq!(reply.text("br"), "Comando desconhecido: /foo");
+        assert_eq!(reply.text("es"), "Comando desconocido: /foo");
+        // english is used when there's no translation
+        assert_eq!(reply.text("de"), "Unknown command: /foo");
+    }
+
+    #[test]
+    fn format_profile() {
+        let stats = Stats {
+            racing_rounds: 10,
+            racing_finishes: 8,
+            racing_podiums: 5,
+            racing_firsts: 2,
+            ..Default::default()
+        };
+
+        let text = Reply::Profile(String::from("Andriel#0001"), stats).text("en");
+        assert!(text.starts_with("Andriel#0001\n"));
+        assert!(text.contains("\nRaces: 8 / 10, Podiums: 5, Firsts: 2\n"));
+        assert!(text.ends_with("\nDefilante: 0 / 0"));
+    }
+
+    #[test]
+    fn format_time_played() {
+        assert_eq!(
+            Reply::TimePlayed(90061).text("en"),
+            "Time played: 1d 1h 1min 1s"
+        );
+    }
+
+    #[test]
+    fn format_times() {
+        assert_eq!(format_time(1234), "12.34");
+        assert_eq!(format_time(705), "7.05");
+        assert_eq!(format_time(0), "0.00");
+    }
+
+    #[test]
+    fn format_race_first() {
+        let record = Some((String::from("Bob#0001"), 1100));
+        let reply = Reply::RaceFirst(String::from("Andriel#0001"), 1234, record);
+
+        assert_eq!(
+            reply.text("en"),
+            "Andriel#0001 finished first in 12.34s. Map record: Bob#0001 (11.00s)."
+        );
+    }
+
+    #[test]
+    fn format_tally() {
+        let reply = Reply::DefilanteTally(vec![
+            (String::from("Andriel#0001"), 12),
+            (String::from("Bob#0001"), 7),
+        ]);
+
+        assert_eq!(
+            reply.text("en"),
+            "Points of the round:\n1. Andriel#0001 - 12\n2. Bob#0001 - 7"
+        );
+    }
+}
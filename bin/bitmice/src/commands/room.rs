@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::{Args, Command, Outcome, Reply, GUEST};
+use crate::{client, Client, Server};
+
+const MAX_NAME_LENGTH: usize = 64;
+
+pub const COMMAND: Command = Command {
+    name: "room",
+    aliases: &["salon", "sala"],
+    priv_level: GUEST,
+    usage: "/room [name]",
+    help: "goes to a room, or to a recommended one without a name",
+};
+
+pub async fn handle(client: Arc<Mutex<Client>>, server: Arc<Mutex<Server>>, args: Args) -> Outcome {
+    let name = args.rest(0);
+
+    // special rooms are only entered through their own packets
+    if name.starts_with('\x03') || name.len() > MAX_NAME_LENGTH {
+        return Err(Reply::InvalidRoom.into());
+    }
+
+    client::join_room(client, server, &name).await?;
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::{Args, Command, Outcome, Reply, GUEST};
+use crate::{music, Client, Server};
+
+pub const COMMAND: Command = Command {
+    name: "skip",
+    aliases: &[],
+    priv_level: GUEST,
+    usage: "/skip",
+    help: "votes to skip the video playing on a music room",
+};
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    _args: Args,
+) -> Outcome {
+    let c = client.lock().await;
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    if !music::is_music_room(&c.last_room) {
+        return Err(Reply::MusicRoomOnly.into());
+    }
+    let client_id = c.id;
+    drop(c);
+
+    let mut r = room.lock().await;
+    if r.playlist.current().is_none() {
+        return Err(Reply::NothingPlaying.into());
+    }
+    let players = r.players().len();
+    let (votes, skips) = r.playlist.vote_skip(client_id, players);
+    drop(r);
+
+    if skips {
+        let max_duration = server.lock().await.config.music.max_duration;
+        music::skip(&room, max_duration).await?;
+    } else {
+        super::reply(&client, Reply::SkipVoted(votes, players)).await?;
+    }
+
+    Ok(())
+}
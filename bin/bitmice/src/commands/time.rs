@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{sync::Arc, time::UNIX_EPOCH};
+
+use tokio::sync::Mutex;
+
+use super::{Args, Command, Outcome, Reply, PLAYER};
+use crate::{Client, Server};
+
+pub const COMMAND: Command = Command {
+    name: "time",
+    aliases: &["temps"],
+    priv_level: PLAYER,
+    usage: "/time",
+    help: "shows how long you have played",
+};
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    _args: Args,
+) -> Outcome {
+    let c = client.lock().await;
+    let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+    let time_played = c.time_played + now.saturating_sub(c.login_time);
+    drop(c);
+
+    super::reply(&client, Reply::TimePlayed(time_played)).await?;
+
+    Ok(())
+}
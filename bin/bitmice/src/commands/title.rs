@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::{Args, Command, Failure, Outcome, Reply, PLAYER};
+use crate::{Client, Server};
+
+pub const COMMAND: Command = Command {
+    name: "title",
+    aliases: &["titre", "titulo"],
+    priv_level: PLAYER,
+    usage: "/title [number]",
+    help: "shows or changes your title",
+};
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    args: Args,
+) -> Outcome {
+    if args.is_empty() {
+        let title_number = client.lock().await.title_number;
+        super::reply(&client, Reply::Title(title_number)).await?;
+
+        return Ok(());
+    }
+
+    // there's no list of unlocked titles yet, any of them can be chosen
+    let title_number = match args.get(0).and_then(|t| t.parse::<u16>().ok()) {
+        Some(t) if args.len() == 1 => t,
+        _ => return Err(Failure::Usage),
+    };
+    client.lock().await.title_number = title_number;
+    super::reply(&client, Reply::TitleChanged(title_number)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::{Args, Command, Outcome, Reply, GUEST};
+use crate::{Client, Server};
+
+pub const COMMAND: Command = Command {
+    name: "who",
+    aliases: &[],
+    priv_level: GUEST,
+    usage: "/who",
+    help: "lists the players in the room",
+};
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    _args: Args,
+) -> Outcome {
+    let c = client.lock().await;
+    let room_name = c.last_room.clone();
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    drop(c);
+
+    let players = room.lock().await.players();
+    let mut names = Vec::new();
+    for player in players {
+        names.push(player.lock().await.full_name());
+    }
+
+    super::reply(&client, Reply::Who(room_name, names)).await?;
+
+    Ok(())
+}
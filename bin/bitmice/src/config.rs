@@ -0,0 +1,639 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{collections::HashMap, fmt, path::Path, time::Duration};
+
+use bitmice_events::Event;
+use bitmice_lua::Limits;
+use bitmice_utils::{crypt::KEYS_COUNT, framer::DEFAULT_MAX_PACKET_SIZE};
+use serde::{de::DeserializeOwned, Deserialize};
+
+pub const CONFIG_PATH: &str = "./assets/config.toml";
+
+const ROOM_TYPES: &[&str] = &[
+    "all",
+    "bootcamp",
+    "defilante",
+    "music",
+    "racing",
+    "survivor",
+    "vanilla",
+    "village",
+];
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    pub bulle: BulleConfig,
+    pub server: ServerConfig,
+    /// Supported client versions, the first one is used until the client
+    /// tells its version.
+    #[serde(rename = "protocol")]
+    pub protocols: Vec<ProtocolConfig>,
+    #[serde(default)]
+    pub database: DatabaseConfig,
+    #[serde(default)]
+    pub welcome: WelcomeConfig,
+    #[serde(default)]
+    pub timeouts: TimeoutsConfig,
+    #[serde(default)]
+    pub chat: ChatConfig,
+    #[serde(default)]
+    pub music: MusicConfig,
+    #[serde(default)]
+    pub minigames: MinigamesConfig,
+    #[serde(default)]
+    pub events: HashMap<String, EventConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BulleConfig {
+    pub ports: Vec<u16>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServerConfig {
+    pub name: String,
+    pub ports: Vec<u16>,
+    #[serde(default)]
+    pub last_player_id: u32,
+    #[serde(default = "default_max_packet_size")]
+    pub max_packet_size: usize,
+}
+
+fn default_max_packet_size() -> usize {
+    DEFAULT_MAX_PACKET_SIZE
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProtocolConfig {
+    pub version: u16,
+    pub ckey: String,
+    pub auth_key: u32,
+    pub login_keys: Vec<u32>,
+    pub packet_keys: Vec<i32>,
+    #[serde(default)]
+    pub tokens: Vec<TokenMapping>,
+}
+
+/// Tokens used by the client in place of the ones the server knows.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenMapping {
+    pub direction: Direction,
+    pub client: (u8, u8),
+    pub server: (u8, u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Recv,
+    Send,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DatabaseConfig {
+    pub path: String,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            path: String::from("./bitmice.db"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WelcomeConfig {
+    pub banner_id: i8,
+    pub login_image: String,
+}
+
+impl Default for WelcomeConfig {
+    fn default() -> Self {
+        Self {
+            banner_id: 2,
+            login_image: String::from("x_noel2014.jpg"),
+        }
+    }
+}
+
+/// All values are in seconds.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimeoutsConfig {
+    pub idle: u64,
+    pub policy: u64,
+}
+
+impl Default for TimeoutsConfig {
+    fn default() -> Self {
+        Self {
+            idle: 900, // 15min
+            policy: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ChatConfig {
+    /// Longer messages are cut.
+    pub max_length: usize,
+    /// Milliseconds between two messages of a player, faster ones are dropped.
+    pub min_interval: u64,
+    /// Messages allowed in `flood_window` seconds before the player is muted.
+    pub flood_messages: usize,
+    pub flood_window: u64,
+    /// Seconds a flooding player stays muted.
+    pub mute_time: u64,
+    /// Words replaced by `*`, the case is ignored.
+    pub banned_words: Vec<String>,
+}
+
+impl Default for ChatConfig {
+    fn default() -> Self {
+        Self {
+            max_length: 255,
+            min_interval: 500,
+            flood_messages: 6,
+            flood_window: 5,
+            mute_time: 60,
+            banned_words: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MusicConfig {
+    /// Videos waiting on the playlist of a music room.
+    pub max_queue: usize,
+    /// Seconds a video plays before the next one, their length isn't known.
+    pub max_duration: u64,
+}
+
+impl Default for MusicConfig {
+    fn default() -> Self {
+        Self {
+            max_queue: 20,
+            max_duration: 600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MinigamesConfig {
+    /// Megabytes a script can allocate.
+    pub max_memory: usize,
+    /// Milliseconds a single event of a script can run for.
+    pub max_call_time: u64,
+}
+
+impl MinigamesConfig {
+    pub fn limits(&self) -> Limits {
+        Limits {
+            memory: self.max_memory * 1024 * 1024,
+            call_time: Duration::from_millis(self.max_call_time),
+        }
+    }
+}
+
+impl Default for MinigamesConfig {
+    fn default() -> Self {
+        Self {
+            max_memory: 16,
+            max_call_time: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventConfig {
+    pub enabled: bool,
+    /// Seconds since the unix epoch.
+    pub start_time: u64,
+    pub end_time: u64,
+    pub room_types: Vec<String>,
+    #[serde(default)]
+    pub has_specific_map: bool,
+}
+
+impl EventConfig {
+    pub fn event(&self, name: &str) -> Event {
+        Event {
+            name: name.to_string(),
+            enabled: self.enabled,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            room_types: self.room_types.clone(),
+            has_specific_map: self.has_specific_map,
+        }
+    }
+}
+
+/// Files read at startup, checked once they're parsed.
+pub trait Validate {
+    fn validate(&self) -> Result<(), ConfigError>;
+}
+
+/// Reads a toml file, see `parse_toml`.
+pub fn load_toml<T: DeserializeOwned + Validate>(path: impl AsRef<Path>) -> Result<T, ConfigError> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path).map_err(|e| ConfigError::Io {
+        path: path.display().to_string(),
+        error: e,
+    })?;
+
+    parse_toml(&content)
+}
+
+pub fn parse_toml<T: DeserializeOwned + Validate>(content: &str) -> Result<T, ConfigError> {
+    let value: T = toml::from_str(content).map_err(ConfigError::Parse)?;
+    value.validate()?;
+
+    Ok(value)
+}
+
+impl Validate for Config {
+    fn validate(&self) -> Result<(), ConfigError> {
+        validate_ports("bulle.ports", &self.bulle.ports)?;
+        validate_ports("server.ports", &self.server.ports)?;
+        if let Some(port) = self
+            .server
+            .ports
+            .iter()
+            .find(|p| self.bulle.ports.contains(p))
+        {
+            return Err(ConfigError::invalid(
+                "server.ports",
+                format!("port {} is already used by bulle.ports", port),
+            ));
+        }
+
+        if self.server.name.trim().is_empty() {
+            return Err(ConfigError::invalid("server.name", "must not be empty"));
+        }
+        if self.server.max_packet_size < 3 {
+            return Err(ConfigError::invalid(
+                "server.max_packet_size",
+                "must be at least 3 bytes",
+            ));
+        }
+
+        if self.protocols.is_empty() {
+            return Err(ConfigError::invalid(
+                "protocol",
+                "at least one protocol is required",
+            ));
+        }
+        for (i, protocol) in self.protocols.iter().enumerate() {
+            protocol.validate(&format!("protocol[{}]", i))?;
+
+            if self.protocols[..i]
+                .iter()
+                .any(|p| p.version == protocol.version && p.ckey == protocol.ckey)
+            {
+                return Err(ConfigError::invalid(
+                    format!("protocol[{}]", i),
+                    format!(
+                        "version {} with ckey \"{}\" is listed more than once",
+                        protocol.version, protocol.ckey
+                    ),
+                ));
+            }
+        }
+
+        if self.database.path.trim().is_empty() {
+            return Err(ConfigError::invalid("database.path", "must not be empty"));
+        }
+
+        if self.welcome.login_image.is_empty() {
+            return Err(ConfigError::invalid(
+                "welcome.login_image",
+                "must not be empty",
+            ));
+        }
+
+        if self.timeouts.idle == 0 {
+            return Err(ConfigError::invalid(
+                "timeouts.idle",
+                "must be greater than 0",
+            ));
+        }
+
+        if self.chat.max_length == 0 {
+            return Err(ConfigError::invalid(
+                "chat.max_length",
+                "must be greater than 0",
+            ));
+        }
+        if self.chat.flood_messages == 0 || self.chat.flood_window == 0 {
+            return Err(ConfigError::invalid(
+                "chat.flood_messages",
+                "flood_messages and flood_window must be greater than 0",
+            ));
+        }
+        if self.chat.banned_words.iter().any(|w| w.trim().is_empty()) {
+            return Err(ConfigError::invalid(
+                "chat.banned_words",
+                "must not contain empty words",
+            ));
+        }
+
+        if self.music.max_queue == 0 || self.music.max_duration == 0 {
+            return Err(ConfigError::invalid(
+                "music.max_queue",
+                "max_queue and max_duration must be greater than 0",
+            ));
+        }
+
+        if self.minigames.max_memory == 0 || self.minigames.max_call_time == 0 {
+            return Err(ConfigError::invalid(
+                "minigames.max_memory",
+                "max_memory and max_call_time must be greater than 0",
+            ));
+        }
+
+        for (name, event) in self.events.iter() {
+            if event.start_time >= event.end_time {
+                return Err(ConfigError::invalid(
+                    format!("events.{}.end_time", name),
+                    "must be after start_time",
+                ));
+            }
+
+            if event.room_types.is_empty() {
+                return Err(ConfigError::invalid(
+                    format!("events.{}.room_types", name),
+                    "must not be empty",
+                ));
+            }
+            if let Some(room_type) = event
+                .room_types
+                .iter()
+                .find(|t| !ROOM_TYPES.contains(&t.as_str()))
+            {
+                return Err(ConfigError::invalid(
+                    format!("events.{}.room_types", name),
+                    format!(
+                        "unknown room type \"{}\", expected one of {:?}",
+                        room_type, ROOM_TYPES
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ProtocolConfig {
+    fn validate(&self, field: &str) -> Result<(), ConfigError> {
+        if self.ckey.is_empty() {
+            return Err(ConfigError::invalid(
+                format!("{}.ckey", field),
+                "must not be empty",
+            ));
+        }
+        if self.login_keys.is_empty() {
+            return Err(ConfigError::invalid(
+                format!("{}.login_keys", field),
+                "must not be empty",
+            ));
+        }
+        if self.packet_keys.len() != KEYS_COUNT {
+            return Err(ConfigError::invalid(
+                format!("{}.packet_keys", field),
+                format!(
+                    "expected {} keys, found {}",
+                    KEYS_COUNT,
+                    self.packet_keys.len()
+                ),
+            ));
+        }
+
+        for (i, mapping) in self.tokens.iter().enumerate() {
+            if self.tokens[..i].iter().any(|m| {
+                m.direction == mapping.direction
+                    && (m.client == mapping.client || m.server == mapping.server)
+            }) {
+                return Err(ConfigError::invalid(
+                    format!("{}.tokens[{}]", field, i),
+                    format!(
+                        "tokens {:?} -> {:?} are already mapped",
+                        mapping.client, mapping.server
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_ports(field: &str, ports: &[u16]) -> Result<(), ConfigError> {
+    if ports.is_empty() {
+        return Err(ConfigError::invalid(field, "at least one port is required"));
+    }
+
+    for (i, port) in ports.iter().enumerate() {
+        if *port == 0 {
+            return Err(ConfigError::invalid(field, "port 0 is not allowed"));
+        }
+        if ports[..i].contains(port) {
+            return Err(ConfigError::invalid(
+                field,
+                format!("port {} is listed more than once", port),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io { path: String, error: std::io::Error },
+    Parse(toml::de::Error),
+    Invalid { field: String, reason: String },
+}
+
+impl ConfigError {
+    pub(crate) fn invalid(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::Invalid {
+            field: field.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "failed to read config [{}]: {}", path, error),
+            Self::Parse(error) => write!(f, "failed to parse config: {}", error),
+            Self::Invalid { field, reason } => {
+                write!(f, "invalid value for `{}`: {}", field, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Field rejected when parsing `content`, for the tests of the files.
+#[cfg(test)]
+pub(crate) fn invalid_field<T>(content: &str) -> String
+where
+    T: DeserializeOwned + Validate + fmt::Debug,
+{
+    match parse_toml::<T>(content) {
+        Err(ConfigError::Invalid { field, .. }) => field,
+        other => panic!("expected an invalid value, got {:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{invalid_field, parse_toml, Config, ConfigError};
+    use crate::{minigame::Registry, village::Village};
+
+    const CONFIG: &str = r#"
+        [bulle]
+        ports = [1801]
+
+        [server]
+        name = "BitMice"
+        ports = [11801, 12801]
+
+        [[protocol]]
+        version = 818
+        ckey = "WKvjvHsJiT"
+        auth_key = 567865443
+        login_keys = [5798205]
+        packet_keys = [
+            13, 16, 42, 55, 40, 23, 19, 43, 11, 55, 87, 74, 116, 105, 114, 77, 117, 77, 97, 93,
+        ]
+    "#;
+
+    #[test]
+    fn parse_with_defaults() {
+        let config = parse_toml::<Config>(CONFIG).unwrap();
+
+        assert_eq!(config.server.ports, vec![11801, 12801]);
+        assert_eq!(config.server.last_player_id, 0);
+        assert_eq!(config.welcome.banner_id, 2);
+        assert_eq!(config.timeouts.idle, 900);
+        assert_eq!(config.chat.max_length, 255);
+        assert!(config.chat.banned_words.is_empty());
+        assert_eq!(config.music.max_queue, 20);
+        assert_eq!(config.minigames.limits().memory, 16 * 1024 * 1024);
+        assert!(config.events.is_empty());
+    }
+
+    #[test]
+    fn parse_shipped_files() {
+        let config: Config = parse_toml(include_str!("../../../assets/config.toml")).unwrap();
+        assert_eq!(config.protocols[0].version, 818);
+        assert_eq!(config.protocols[1].version, 616);
+
+        let village: Village = parse_toml(include_str!("../../../assets/village.toml")).unwrap();
+        assert!(!village.npcs.is_empty());
+
+        let registry: Registry =
+            parse_toml(include_str!("../../../assets/minigames.toml")).unwrap();
+        assert!(registry.get("example").is_some());
+    }
+
+    #[test]
+    fn reject_bad_ports() {
+        let content = CONFIG.replace("[11801, 12801]", "[11801, 11801]");
+        assert_eq!(invalid_field::<Config>(&content), "server.ports");
+
+        let content = CONFIG.replace("[11801, 12801]", "[0]");
+        assert_eq!(invalid_field::<Config>(&content), "server.ports");
+
+        let content = CONFIG.replace("[11801, 12801]", "[1801]");
+        assert_eq!(invalid_field::<Config>(&content), "server.ports");
+
+        let content = CONFIG.replace("[1801]", "[]");
+        assert_eq!(invalid_field::<Config>(&content), "bulle.ports");
+    }
+
+    #[test]
+    fn reject_bad_keys() {
+        let content = CONFIG.replace("13, 16, 42, 55,", "");
+        assert_eq!(invalid_field::<Config>(&content), "protocol[0].packet_keys");
+
+        let content = CONFIG.replace("[5798205]", "[]");
+        assert_eq!(invalid_field::<Config>(&content), "protocol[0].login_keys");
+
+        // every version has its keys
+        let content = CONFIG.replace("login_keys = [5798205]", "");
+        assert!(matches!(
+            parse_toml::<Config>(&content),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn parse_several_protocols() {
+        let content = format!(
+            "{}{}",
+            CONFIG,
+            r#"
+            [[protocol]]
+            version = 616
+            ckey = "yAdByj"
+            auth_key = 1234
+            login_keys = [42]
+            packet_keys = [
+                1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
+            ]
+
+            [[protocol.tokens]]
+            direction = "recv"
+            client = [5, 26]
+            server = [5, 38]
+            "#
+        );
+        let config = parse_toml::<Config>(&content).unwrap();
+
+        assert_eq!(config.protocols.len(), 2);
+        assert_eq!(config.protocols[1].login_keys, vec![42]);
+        assert_eq!(config.protocols[1].tokens[0].server, (5, 38));
+
+        // same version twice
+        let content = format!(
+            "{}{}",
+            CONFIG,
+            &CONFIG[CONFIG.find("[[protocol]]").unwrap()..]
+        );
+        assert_eq!(invalid_field::<Config>(&content), "protocol[1]");
+    }
+
+    #[test]
+    fn reject_bad_chat() {
+        let content = format!("{}\n[chat]\nflood_messages = 0\n", CONFIG);
+        assert_eq!(invalid_field::<Config>(&content), "chat.flood_messages");
+
+        let content = format!("{}\n[chat]\nbanned_words = [\"ok\", \" \"]\n", CONFIG);
+        assert_eq!(invalid_field::<Config>(&content), "chat.banned_words");
+    }
+
+    #[test]
+    fn reject_bad_event() {
+        let content = format!(
+            "{}\n[events.fishing]\nenabled = true\nstart_time = 10\nend_time = 20\nroom_types = [\"fishing\"]\n",
+            CONFIG
+        );
+        assert_eq!(
+            invalid_field::<Config>(&content),
+            "events.fishing.room_types"
+        );
+
+        let content = content.replace("end_time = 20", "end_time = 5");
+        assert_eq!(invalid_field::<Config>(&content), "events.fishing.end_time");
+    }
+}
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::Mutex;
+
+use crate::{
+    commands::{self, Reply},
+    room::{self, RoomType},
+    Result, Room,
+};
+
+/// Perm of the defilante maps, they scroll from the left to the right.
+pub const DEFILANTE_PERMA: i8 = 18;
+
+/// Seconds the players wait before the map starts to scroll.
+const COUNTDOWN: u64 = 3;
+
+/// Milliseconds between two points of a player, the faster ones are forged.
+pub const MIN_POINT_INTERVAL: u128 = 250;
+
+/// Whether the countdown of the round is over, no points can be collected
+/// before it.
+pub fn has_started(room: &Room, now: u128) -> bool {
+    now.saturating_sub(room.start_time) >= COUNTDOWN as u128 * 1000
+}
+
+/// Unfreezes the players of a defilante room once the countdown is over,
+/// unless the round has already changed.
+pub async fn start_round(room: &Arc<Mutex<Room>>) {
+    let r = room.lock().await;
+    if r.room_type != RoomType::Defilante {
+        return;
+    }
+    let round_code = r.last_round_code;
+    drop(r);
+
+    let room = Arc::clone(room);
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(COUNTDOWN)).await;
+
+        let r = room.lock().await;
+        if r.last_round_code == round_code {
+            if let Err(e) = r.start_map(true).await {
+                log::warn!("couldn't start the defilante round: {}", e);
+            }
+        }
+    });
+}
+
+/// Adds the points collected on the round of a defilante room to the score of
+/// the players and tells them the tally, must be called before the players
+/// are reset for the next round.
+pub async fn end_round(room: &Arc<Mutex<Room>>) -> Result {
+    let r = room.lock().await;
+    if r.room_type != RoomType::Defilante {
+        return Ok(());
+    }
+    let players = r.players();
+    drop(r);
+    let counts_stats = players.len() >= room::MIN_PLAYERS_FOR_STATS;
+
+    let mut tally = Vec::new();
+    for player in &players {
+        let mut p = player.lock().await;
+        let points = p.defilante_points;
+
+        p.score = p.score.saturating_add(points);
+        if counts_stats {
+            p.stats.defilante_rounds += 1;
+            p.stats.defilante_points += points as u32;
+        }
+        if points > 0 {
+            tally.push((p.full_name(), points));
+        }
+    }
+    if tally.is_empty() {
+        return Ok(());
+    }
+    tally.sort_by_key(|(_, points)| std::cmp::Reverse(*points));
+
+    let reply = Reply::DefilanteTally(tally);
+    for player in players {
+        commands::reply(&player, reply.clone()).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::has_started;
+    use crate::Room;
+
+    #[test]
+    fn countdown() {
+        let mut room = Room::new(String::from("defilante"), String::from("en"));
+        room.start_time = 10_000;
+
+        assert!(!has_started(&room, 10_000));
+        assert!(!has_started(&room, 12_999));
+        assert!(has_started(&room, 13_000));
+    }
+}
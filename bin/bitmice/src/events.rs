@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{
+    collections::HashMap,
+    sync::RwLock,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use bitmice_events::{Change, Event, Hooks, Reward, Scheduler};
+use once_cell::sync::Lazy;
+use rand::seq::SliceRandom;
+
+use crate::{
+    config::EventConfig,
+    music,
+    packets::send,
+    room::{Room, RoomType},
+    Client, Result,
+};
+
+const EVENT_MAPS_FOLDER: &str = "./assets/maps/events/";
+
+/// Time between two checks of the dates of the events.
+const UPDATE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Read by the rooms when they pick a map, which can't wait on a lock.
+static SCHEDULER: Lazy<RwLock<Scheduler>> = Lazy::new(Default::default);
+
+/// Maps of the events with `has_specific_map`, read once on start.
+static MAPS: Lazy<RwLock<HashMap<String, Vec<String>>>> = Lazy::new(Default::default);
+
+/// Schedules the events of the config, they are checked until the server
+/// stops.
+pub fn start(events: &HashMap<String, EventConfig>) {
+    let mut scheduler = Scheduler::new(events.iter().map(|(n, e)| e.event(n)).collect());
+    scheduler.set_hooks("fishing", Fishing);
+
+    let mut maps = HashMap::new();
+    for event in scheduler.events() {
+        if !event.has_specific_map {
+            continue;
+        }
+
+        let xmls = event_maps(&event.name);
+        if event.enabled && xmls.is_empty() {
+            log::warn!(
+                "the event [{}] has no maps in [{}{}/], its rooms keep their rotation",
+                event.name,
+                EVENT_MAPS_FOLDER,
+                event.name
+            );
+        }
+        maps.insert(event.name.clone(), xmls);
+    }
+    *SCHEDULER.write().unwrap() = scheduler;
+    *MAPS.write().unwrap() = maps;
+
+    update();
+    tokio::spawn(async {
+        loop {
+            tokio::time::sleep(UPDATE_INTERVAL).await;
+            update();
+        }
+    });
+}
+
+fn update() {
+    let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+
+    for change in SCHEDULER.write().unwrap().update(now) {
+        match change {
+            Change::Started(name) => log::info!("the event [{}] started", name),
+            Change::Ended(name) => log::info!("the event [{}] ended", name),
+        }
+    }
+}
+
+/// Name of the event running on the room and the xml of one of its maps,
+/// which replace the rotation of the room.
+pub fn map_xml(room: &Room) -> Option<(String, String)> {
+    let name = {
+        let scheduler = SCHEDULER.read().unwrap();
+        let event = scheduler.room_event(room_type(room))?;
+        if !event.has_specific_map {
+            return None;
+        }
+
+        event.name.clone()
+    };
+    let xml = MAPS
+        .read()
+        .unwrap()
+        .get(&name)?
+        .choose(&mut rand::thread_rng())?
+        .clone();
+
+    Some((name, xml))
+}
+
+/// Rewards of the events running on the room for entering the hole on
+/// `place`.
+pub fn rewards(room: &Room, place: u8) -> Vec<Reward> {
+    SCHEDULER.read().unwrap().rewards(room_type(room), place)
+}
+
+pub async fn give_rewards(client: &mut Client, rewards: Vec<Reward>, counts_stats: bool) -> Result {
+    for reward in rewards {
+        match reward {
+            Reward::Cheese(amount) => {
+                if counts_stats {
+                    client.stats.cheese += amount as u32;
+                }
+                client
+                    .send_packet(send::GiveCurrency {
+                        kind: send::currency::CHEESE,
+                        amount,
+                    })
+                    .await?;
+            }
+            Reward::Score(points) => client.score = client.score.saturating_add(points),
+        }
+    }
+
+    Ok(())
+}
+
+/// Type of the room as written on the config.
+fn room_type(room: &Room) -> &'static str {
+    match room.room_type {
+        RoomType::Bootcamp => "bootcamp",
+        RoomType::Defilante => "defilante",
+        RoomType::Racing => "racing",
+        RoomType::Survivor => "survivor",
+        RoomType::Vanilla if music::is_music_room(&room.name) => "music",
+        RoomType::Vanilla => "vanilla",
+        RoomType::Village => "village",
+    }
+}
+
+/// Maps of the folder of an event, the empty files are slots not filled yet.
+fn event_maps(name: &str) -> Vec<String> {
+    let entries = match std::fs::read_dir(format!("{}{}/", EVENT_MAPS_FOLDER, name)) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "xml"))
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .filter(|xml| !xml.trim().is_empty())
+        .collect()
+}
+
+/// The firsts to enter the hole on a room of the fishing event bring an
+/// extra cheese.
+struct Fishing;
+
+impl Hooks for Fishing {
+    fn rewards(&self, _event: &Event, place: u8) -> Vec<Reward> {
+        match place {
+            1..=3 => vec![Reward::Cheese(1)],
+            _ => Vec::new(),
+        }
+    }
+}
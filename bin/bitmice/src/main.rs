@@ -1,15 +1,33 @@
 // SPDX-License-Identifier: BSD-3-Clause
 // Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
 
+mod chat;
 mod client;
+mod commands;
+mod config;
+mod defilante;
+mod events;
+mod minigame;
+mod music;
+mod packets;
+mod protocol;
+mod racing;
 mod room;
 mod server;
+mod shaman;
+mod storage;
+mod survivor;
 mod tokens;
+mod tribe;
+mod tribulle;
+mod village;
 
+use bitmice_database::SqliteStorage;
 use std::{sync::Arc, time::Duration};
 use tokio::{net::TcpListener, sync::Mutex};
 
 use client::Client;
+use config::Config;
 use room::Room;
 use server::Server;
 
@@ -19,28 +37,46 @@ pub type Result = std::result::Result<(), Box<dyn std::error::Error + Send + Syn
 async fn main() {
     env_logger::init();
 
-    let ports = &[11801, 12801, 13801, 14801];
-    let server = Server::new(
-        567865443,    // auth key
-        "WKvjvHsJiT", //ckey
-        818,          // version
-        &[
-            // login keys
-            5798205, 2147483648, 16384, 128, 5798205, 2147483648, 16384, 806984, 5798205,
-            2147483648, 16384,
-        ],
-        &[
-            // packet keys
-            13, 16, 42, 55, 40, 23, 19, 43, 11, 55, 87, 74, 116, 105, 114, 77, 117, 77, 97, 93,
-        ],
-    );
-    /* let server = Server::new(
-        0,
-        "yAdByj", //ckey
-        616,      // version
-        &[],
-        &[],
-    ); */
+    let config_path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| config::CONFIG_PATH.to_string());
+    let config = match config::load_toml::<Config>(&config_path) {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    log::info!("loaded config from [{}]", config_path);
+
+    let storage = match SqliteStorage::open(&config.database.path) {
+        Ok(s) => Arc::new(s),
+        Err(e) => {
+            log::error!("failed to open database [{}]: {}", config.database.path, e);
+            std::process::exit(1);
+        }
+    };
+
+    let village: village::Village = match config::load_toml(village::VILLAGE_PATH) {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let minigames: minigame::Registry = match config::load_toml(minigame::MINIGAMES_PATH) {
+        Ok(m) => m,
+        Err(e) => {
+            log::error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    events::start(&config.events);
+
+    let ports = config.server.ports.clone();
+    let server = Server::new(config, storage, village, minigames);
     let server = Arc::new(Mutex::new(server));
 
     for port in ports.clone() {
@@ -57,12 +93,9 @@ async fn main() {
                     let reader = Arc::new(Mutex::new(reader));
                     let writer = Arc::new(Mutex::new(writer));
 
-                    let client = Client::new(
-                        address,
-                        Arc::clone(&reader),
-                        Arc::clone(&writer),
-                        Arc::clone(&s),
-                    );
+                    let protocol = s.lock().await.protocols.default_protocol();
+                    let client =
+                        Client::new(address, Arc::clone(&writer), Arc::clone(&s), protocol);
                     server::handle_client(client, reader, writer).await;
                 }
             }
@@ -0,0 +1,558 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{collections::VecDeque, sync::Arc};
+
+use bitmice_lua::{Action, Arg, Limits, Module, Player, ScriptError};
+use bitmice_utils::packet::Packet;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use crate::{
+    client,
+    commands::Reply,
+    config::{ConfigError, Validate},
+    packets::send,
+    room, Client, Result, Room, Server,
+};
+
+pub const MINIGAMES_PATH: &str = "./assets/minigames.toml";
+pub const MINIGAMES_FOLDER: &str = "./assets/minigames/";
+
+/// Starts the name of the rooms bound to a minigame, `#name` or `#name0room`.
+pub const ROOM_PREFIX: char = '#';
+const ROOM_SEPARATOR: char = '0';
+
+/// Prefixes of the targets of an image, their kind is the position plus one.
+const IMAGE_TARGETS: &str = "#$%?_!&:";
+const PLAYER_IMAGE: u8 = 2;
+const PLAYER_FRONT_IMAGE: u8 = 3;
+
+/// Milliseconds between two answers of a player to text areas and popups,
+/// each of them runs the script.
+pub const MIN_UI_INTERVAL: u128 = 500;
+
+/// Minigames that can be played, their scripts are in the minigames folder.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Registry {
+    #[serde(default, rename = "minigame")]
+    pub minigames: Vec<Minigame>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Minigame {
+    /// Name of the script, without the `.lua`.
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Players a room of the minigame can hold.
+    #[serde(default = "default_max_players")]
+    pub max_players: usize,
+}
+
+fn default_max_players() -> usize {
+    50
+}
+
+impl Validate for Registry {
+    fn validate(&self) -> std::result::Result<(), ConfigError> {
+        for (i, minigame) in self.minigames.iter().enumerate() {
+            let field = format!("minigame[{}]", i);
+            if !is_name(&minigame.name) {
+                return Err(ConfigError::invalid(
+                    field,
+                    "the name must be made of lowercase letters, digits and `_`",
+                ));
+            }
+            if minigame.name.contains(ROOM_SEPARATOR) {
+                return Err(ConfigError::invalid(
+                    field,
+                    format!("the name can't have a `{}`", ROOM_SEPARATOR),
+                ));
+            }
+            if self.minigames[..i].iter().any(|m| m.name == minigame.name) {
+                return Err(ConfigError::invalid(field, "the name is used twice"));
+            }
+            if minigame.max_players == 0 {
+                return Err(ConfigError::invalid(
+                    field,
+                    "max_players must be greater than 0",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Registry {
+    pub fn get(&self, name: &str) -> Option<&Minigame> {
+        self.minigames
+            .iter()
+            .find(|m| m.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Name of the minigame a room is bound to, rooms are named `#name` or
+/// `#name0room`.
+pub fn room_minigame(room: &str) -> Option<&str> {
+    let name = room.trim_start_matches('*').strip_prefix(ROOM_PREFIX)?;
+    let name = name.split(ROOM_SEPARATOR).next().unwrap_or_default();
+
+    (!name.is_empty()).then_some(name)
+}
+
+/// Names can't leave the minigames folder.
+fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Why a player can't join the room `name`, if it's bound to a minigame that
+/// doesn't exist or is full.
+pub async fn check_room(
+    client: &Arc<Mutex<Client>>,
+    server: &Arc<Mutex<Server>>,
+    name: &str,
+) -> Option<Reply> {
+    let minigame = room_minigame(name)?;
+    let lang = client.lock().await.lang.clone();
+
+    let s = server.lock().await;
+    let max_players = match s.minigames.get(minigame) {
+        Some(m) => m.max_players,
+        None => return Some(Reply::ModuleNotFound(minigame.to_string())),
+    };
+    let room = s.get_room(name.to_string(), lang).await?;
+    drop(s);
+
+    let players = room.lock().await.players().len();
+    (players >= max_players).then(|| Reply::RoomFull(name.to_string()))
+}
+
+/// Loads the minigame a new room is bound to.
+pub async fn start(room: &Arc<Mutex<Room>>, server: &Arc<Mutex<Server>>) -> Result {
+    let name = room.lock().await.name.clone();
+    let minigame = match room_minigame(&name) {
+        Some(m) => m,
+        None => return Ok(()),
+    };
+
+    let s = server.lock().await;
+    let minigame = match s.minigames.get(minigame) {
+        Some(m) => m.name.clone(),
+        None => return Ok(()),
+    };
+    let limits = s.config.minigames.limits();
+    drop(s);
+
+    load(room, &minigame, limits).await
+}
+
+/// Name of the module running on the room, waits for the event it may be
+/// running.
+pub async fn running(room: &Arc<Mutex<Room>>) -> Option<String> {
+    let module = room.lock().await.module.clone()?;
+
+    tokio::task::spawn_blocking(move || module.lock().unwrap().name().to_string())
+        .await
+        .ok()
+}
+
+/// Runs the script `name` of the minigames folder on the room, in place of
+/// the module it was running.
+pub async fn load(room: &Arc<Mutex<Room>>, name: &str, limits: Limits) -> Result {
+    let source = std::fs::read_to_string(format!("{}{}.lua", MINIGAMES_FOLDER, name))?;
+
+    let players = players(room).await;
+    let module_name = name.to_string();
+    let (module, actions) = tokio::task::spawn_blocking(move || {
+        let module = Module::new(&module_name, limits)?;
+        module.set_players(&players)?;
+        let actions = module.exec(&source)?;
+
+        Ok::<_, ScriptError>((module, actions))
+    })
+    .await??;
+
+    let mut r = room.lock().await;
+    log::info!("[{}] loaded the module [{}]", r.name, name);
+    r.module = Some(Arc::new(std::sync::Mutex::new(module)));
+    drop(r);
+
+    apply_all(room, actions).await
+}
+
+/// Calls `event` on the module of the room, if it runs one, and applies what
+/// the script asked for.
+pub async fn emit(room: &Arc<Mutex<Room>>, event: &str, args: Vec<Arg>) -> Result {
+    if room.lock().await.module.is_none() {
+        return Ok(());
+    }
+
+    let actions = call(room, event, args).await;
+    apply_all(room, actions).await
+}
+
+/// Applies the actions, and the ones asked by the events they trigger.
+async fn apply_all(room: &Arc<Mutex<Room>>, actions: Vec<Action>) -> Result {
+    let mut actions = VecDeque::from(actions);
+    while let Some(action) = actions.pop_front() {
+        apply(room, action, &mut actions).await?;
+    }
+
+    Ok(())
+}
+
+async fn call(room: &Arc<Mutex<Room>>, event: &str, args: Vec<Arg>) -> Vec<Action> {
+    let players = players(room).await;
+    let module = match room.lock().await.module.clone() {
+        Some(m) => m,
+        None => return Vec::new(),
+    };
+
+    // the script runs for up to its time limit, away from the room and the
+    // workers
+    let task_module = Arc::clone(&module);
+    let task_event = event.to_string();
+    let task = tokio::task::spawn_blocking(move || {
+        let module = task_module.lock().unwrap();
+        let result = module
+            .set_players(&players)
+            .and_then(|()| module.emit(&task_event, args));
+
+        (module.name().to_string(), result)
+    });
+    let (name, result) = match task.await {
+        Ok(r) => r,
+        Err(e) => {
+            log::error!("the module of a room panicked on {}: {}", event, e);
+            return Vec::new();
+        }
+    };
+
+    match result {
+        Ok(actions) => actions,
+        Err(e) => {
+            let mut r = room.lock().await;
+            log::warn!(
+                "[{}] the module [{}] failed on {}: {}",
+                r.name,
+                name,
+                event,
+                e
+            );
+            // a script over its limits would keep failing, unless it was
+            // replaced meanwhile
+            let is_same = r.module.as_ref().is_some_and(|m| Arc::ptr_eq(m, &module));
+            if e.is_limit() && is_same {
+                r.module = None;
+            }
+
+            Vec::new()
+        }
+    }
+}
+
+/// Applies an action, queuing the actions asked by the event it triggers.
+async fn apply(room: &Arc<Mutex<Room>>, action: Action, queue: &mut VecDeque<Action>) -> Result {
+    match action {
+        Action::NewGame(map) => {
+            let mut r = room.lock().await;
+            if !room::is_valid_map(&map) {
+                log::warn!("[{}] the module asked for an invalid map: {}", r.name, map);
+                return Ok(());
+            }
+
+            // changed on the next tick of the room
+            r.next_map = map;
+            r.round_time = 0;
+            r.can_change_map = true;
+        }
+        Action::KillPlayer(name) => {
+            if let Some(player) = find_player(room, &name).await {
+                let is_dead = player.lock().await.is_dead;
+                if !is_dead {
+                    client::die(player).await?;
+                    queue.extend(call(room, "eventPlayerDied", vec![name.into()]).await);
+                }
+            }
+        }
+        Action::SetPlayerScore { name, score, add } => {
+            if let Some(player) = find_player(room, &name).await {
+                let mut p = player.lock().await;
+                let score = if add { p.score as i32 + score } else { score };
+                p.score = score.clamp(0, u16::MAX as i32) as u16;
+                let packet = send::PlayerScore {
+                    player_id: p.id,
+                    score: p.score,
+                };
+                drop(p);
+
+                room.lock().await.send_packet(packet).await?;
+            }
+        }
+        Action::MovePlayer {
+            name,
+            x,
+            y,
+            offset,
+            speed_x,
+            speed_y,
+            speed_offset,
+        } => {
+            if let Some(player) = find_player(room, &name).await {
+                player
+                    .lock()
+                    .await
+                    .send_packet(send::MovePlayer {
+                        position_x: x,
+                        position_y: y,
+                        position_offset: offset,
+                        speed_x,
+                        speed_y,
+                        speed_offset,
+                    })
+                    .await?;
+            }
+        }
+        Action::AddTextArea {
+            id,
+            text,
+            player,
+            x,
+            y,
+            width,
+            height,
+            background_color,
+            border_color,
+            background_alpha,
+            fixed,
+        } => {
+            let packet = send::AddTextArea {
+                id,
+                text,
+                x,
+                y,
+                width,
+                height,
+                background_color,
+                border_color,
+                background_alpha,
+                is_fixed: fixed,
+            };
+            send_to(room, player, packet).await?;
+        }
+        Action::UpdateTextArea { id, text, player } => {
+            send_to(room, player, send::UpdateTextArea { id, text }).await?;
+        }
+        Action::RemoveTextArea { id, player } => {
+            send_to(room, player, send::RemoveTextArea { id }).await?;
+        }
+        Action::AddPopup {
+            id,
+            kind,
+            text,
+            player,
+            x,
+            y,
+            width,
+            fixed,
+        } => {
+            let packet = send::AddPopup {
+                id,
+                kind,
+                text,
+                x,
+                y,
+                width,
+                is_fixed: fixed,
+            };
+            send_to(room, player, packet).await?;
+        }
+        Action::AddImage {
+            id,
+            image,
+            target,
+            x,
+            y,
+            player,
+        } => {
+            let (target_kind, target) = match image_target(room, &target).await {
+                Some(t) => t,
+                None => {
+                    log::warn!(
+                        "[{}] the module asked for an invalid image target: {}",
+                        room.lock().await.name,
+                        target
+                    );
+                    return Ok(());
+                }
+            };
+            let packet = send::AddImage {
+                image_id: id,
+                name: image,
+                target_kind,
+                target,
+                x,
+                y,
+            };
+            send_to(room, player, packet).await?;
+        }
+        Action::Print(text) => log::info!("[{}] {}", room.lock().await.name, text),
+    }
+
+    Ok(())
+}
+
+/// Players of the room as seen by the scripts.
+async fn players(room: &Arc<Mutex<Room>>) -> Vec<Player> {
+    let list = room.lock().await.players();
+
+    let mut players = Vec::new();
+    for player in list {
+        let p = player.lock().await;
+        players.push(Player {
+            name: p.full_name(),
+            id: p.id,
+            score: p.score as i32,
+            is_dead: p.is_dead,
+            is_shaman: p.is_shaman,
+            has_cheese: p.has_cheese,
+        });
+    }
+
+    players
+}
+
+/// Sends a packet to `player`, or to everyone on the room.
+async fn send_to<P: Packet>(room: &Arc<Mutex<Room>>, player: Option<String>, packet: P) -> Result {
+    match player {
+        Some(name) => {
+            if let Some(player) = find_player(room, &name).await {
+                player.lock().await.send_packet(packet).await?;
+            }
+        }
+        None => room.lock().await.send_packet(packet).await?,
+    }
+
+    Ok(())
+}
+
+/// Kind and id of the target of an image, the players are given by name.
+async fn image_target(room: &Arc<Mutex<Room>>, target: &str) -> Option<(u8, i32)> {
+    let (kind, target) = parse_image_target(target)?;
+    let id = match kind {
+        PLAYER_IMAGE | PLAYER_FRONT_IMAGE => {
+            find_player(room, target).await?.lock().await.id as i32
+        }
+        _ => target.parse().ok()?,
+    };
+
+    Some((kind, id))
+}
+
+/// Splits the prefix of an image target, `#object`, `$player`, `%player` or
+/// a layer and its depth.
+fn parse_image_target(target: &str) -> Option<(u8, &str)> {
+    let prefix = target.chars().next()?;
+    let kind = IMAGE_TARGETS.find(prefix)? as u8 + 1;
+
+    Some((kind, &target[prefix.len_utf8()..]))
+}
+
+async fn find_player(room: &Arc<Mutex<Room>>, name: &str) -> Option<Arc<Mutex<Client>>> {
+    let players = room.lock().await.players();
+    for player in players {
+        if player.lock().await.has_name(name) {
+            return Some(player);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use bitmice_lua::{Action, Limits, Module};
+
+    use super::{is_name, parse_image_target, room_minigame, Registry};
+    use crate::config::{invalid_field, parse_toml};
+
+    const MINIGAMES: &str = r#"
+        [[minigame]]
+        name = "example"
+        max_players = 10
+
+        [[minigame]]
+        name = "racing_2"
+    "#;
+
+    #[test]
+    fn image_targets() {
+        assert_eq!(parse_image_target("#12"), Some((1, "12")));
+        assert_eq!(parse_image_target("$Souris#0000"), Some((2, "Souris#0000")));
+        assert_eq!(parse_image_target(":1"), Some((8, "1")));
+        assert_eq!(parse_image_target("12"), None);
+        assert_eq!(parse_image_target(""), None);
+    }
+
+    #[test]
+    fn script_names() {
+        assert!(is_name("example"));
+        assert!(is_name("the_game2"));
+        assert!(!is_name("Example"));
+        assert!(!is_name("../minigames/example"));
+        assert!(!is_name(""));
+    }
+
+    #[test]
+    fn room_names() {
+        assert_eq!(room_minigame("#example"), Some("example"));
+        assert_eq!(room_minigame("#example0fun"), Some("example"));
+        assert_eq!(room_minigame("*#example0"), Some("example"));
+        assert_eq!(room_minigame("#"), None);
+        assert_eq!(room_minigame("#0fun"), None);
+        assert_eq!(room_minigame("vanilla1"), None);
+    }
+
+    #[test]
+    fn parse_registry() {
+        let registry = parse_toml::<Registry>(MINIGAMES).unwrap();
+
+        assert_eq!(registry.get("Example").map(|m| m.max_players), Some(10));
+        assert_eq!(registry.get("racing_2").map(|m| m.max_players), Some(50));
+        assert!(registry.get("missing").is_none());
+
+        let content = MINIGAMES.replace("racing_2", "example");
+        assert_eq!(invalid_field::<Registry>(&content), "minigame[1]");
+
+        let content = MINIGAMES.replace("racing_2", "racing0");
+        assert_eq!(invalid_field::<Registry>(&content), "minigame[1]");
+
+        let content = MINIGAMES.replace("max_players = 10", "max_players = 0");
+        assert_eq!(invalid_field::<Registry>(&content), "minigame[0]");
+    }
+
+    #[test]
+    fn run_example() {
+        let module = Module::new("example", Limits::default()).unwrap();
+        let actions = module
+            .exec(include_str!("../../../assets/minigames/example.lua"))
+            .unwrap();
+        assert_eq!(actions, vec![Action::NewGame(String::from("-1"))]);
+
+        let actions = module
+            .emit(
+                "eventChatCommand",
+                vec!["Souris#0000".into(), "jump".into()],
+            )
+            .unwrap();
+        assert!(matches!(
+            &actions[..],
+            [Action::MovePlayer { offset: true, .. }]
+        ));
+    }
+}
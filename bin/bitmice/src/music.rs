@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Arc,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use tokio::sync::Mutex;
+
+use crate::{packets::send, Result, Room};
+
+/// Characters of a YouTube video id.
+const VIDEO_ID_LENGTH: usize = 11;
+
+/// Whether a room plays the videos queued by its players.
+pub fn is_music_room(name: &str) -> bool {
+    name.contains("music")
+}
+
+/// Takes the id of a YouTube video, either given alone or on a link to it.
+pub fn parse_video_id(text: &str) -> Option<String> {
+    let text = text.trim();
+    let id = if let Some((_, rest)) = text.split_once("v=") {
+        rest.split('&').next().unwrap_or_default()
+    } else if let Some((_, rest)) = text.split_once("youtu.be/") {
+        rest.split('?').next().unwrap_or_default()
+    } else {
+        text
+    };
+
+    let is_valid = id.len() == VIDEO_ID_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    is_valid.then(|| id.to_string())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Video {
+    pub id: String,
+    /// Name of the player that queued it.
+    pub by: String,
+    pub by_id: u32,
+}
+
+/// Why a video wasn't queued.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueError {
+    /// The player already has a video waiting to be played.
+    AlreadyQueued,
+    Full,
+}
+
+/// Videos of a music room, the first one is the one playing.
+#[derive(Debug, Default)]
+pub struct Playlist {
+    videos: VecDeque<Video>,
+    /// Milliseconds since the epoch when the first video started.
+    started_at: u128,
+    /// Counts the videos played, to tell if the one playing changed.
+    played: u32,
+    /// Players that voted to skip the video playing.
+    skip_votes: HashSet<u32>,
+}
+
+impl Playlist {
+    pub fn current(&self) -> Option<&Video> {
+        self.videos.front()
+    }
+
+    pub fn videos(&self) -> impl Iterator<Item = &Video> {
+        self.videos.iter()
+    }
+
+    /// Seconds since the video playing started.
+    pub fn elapsed(&self, now: u128) -> u64 {
+        (now.saturating_sub(self.started_at) / 1000) as u64
+    }
+
+    /// Queues a video, returns whether it's the only one, so it started and
+    /// must be played.
+    pub fn push(
+        &mut self,
+        video: Video,
+        max_queue: usize,
+        now: u128,
+    ) -> std::result::Result<bool, QueueError> {
+        if self.videos.iter().skip(1).any(|v| v.by_id == video.by_id) {
+            return Err(QueueError::AlreadyQueued);
+        }
+        if self.videos.len() >= max_queue {
+            return Err(QueueError::Full);
+        }
+
+        self.videos.push_back(video);
+        if self.videos.len() > 1 {
+            return Ok(false);
+        }
+        self.start(now);
+
+        Ok(true)
+    }
+
+    /// Goes to the next video, returns it if there's one.
+    pub fn advance(&mut self, now: u128) -> Option<&Video> {
+        self.videos.pop_front();
+        self.start(now);
+
+        self.videos.front()
+    }
+
+    fn start(&mut self, now: u128) {
+        self.started_at = now;
+        self.played += 1;
+        self.skip_votes.clear();
+    }
+
+    /// Adds the vote of a player to skip the video playing, returns the votes
+    /// and whether they're enough, more than half of the `players`. The one
+    /// who queued the video skips it alone.
+    pub fn vote_skip(&mut self, player_id: u32, players: usize) -> (usize, bool) {
+        let is_owner = self.current().is_some_and(|v| v.by_id == player_id);
+        self.skip_votes.insert(player_id);
+        let votes = self.skip_votes.len();
+
+        (votes, is_owner || votes * 2 > players)
+    }
+}
+
+fn now() -> u128 {
+    UNIX_EPOCH.elapsed().unwrap().as_millis()
+}
+
+/// Packet of the video playing, at the time it's at.
+pub fn video_packet(playlist: &Playlist) -> Option<send::MusicVideo> {
+    let video = playlist.current()?;
+
+    Some(send::MusicVideo {
+        video_id: video.id.clone(),
+        title: video.id.clone(),
+        time: playlist.elapsed(now()).min(u16::MAX as u64) as u16,
+        by: video.by.clone(),
+    })
+}
+
+/// Skips the video playing on the room.
+pub async fn skip(room: &Arc<Mutex<Room>>, max_duration: u64) -> Result {
+    room.lock().await.playlist.advance(now());
+
+    play(room, max_duration).await
+}
+
+/// Sends the video playing to everyone on the room, it's skipped once it
+/// plays for `max_duration` seconds, the length of the videos isn't known.
+pub async fn play(room: &Arc<Mutex<Room>>, max_duration: u64) -> Result {
+    let r = room.lock().await;
+    let packet = match video_packet(&r.playlist) {
+        Some(p) => p,
+        None => return Ok(()),
+    };
+    let played = r.playlist.played;
+    r.send_packet(packet).await?;
+    drop(r);
+
+    let room = Arc::clone(room);
+    tokio::spawn(async move {
+        let mut played = played;
+        loop {
+            tokio::time::sleep(Duration::from_secs(max_duration)).await;
+
+            // skipped or queued again since
+            let mut r = room.lock().await;
+            if r.playlist.played != played {
+                return;
+            }
+            r.playlist.advance(now());
+            played = r.playlist.played;
+
+            let packet = match video_packet(&r.playlist) {
+                Some(p) => p,
+                None => return,
+            };
+            if let Err(e) = r.send_packet(packet).await {
+                log::warn!("couldn't play the next video: {}", e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_video_id, Playlist, QueueError, Video};
+
+    fn video(id: &str, by_id: u32) -> Video {
+        Video {
+            id: id.to_string(),
+            by: format!("Player{}#0001", by_id),
+            by_id,
+        }
+    }
+
+    #[test]
+    fn parse_ids() {
+        let id = Some(String::from("dQw4w9WgXcQ"));
+        assert_eq!(parse_video_id("dQw4w9WgXcQ"), id);
+        assert_eq!(
+            parse_video_id("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=10"),
+            id
+        );
+        assert_eq!(parse_video_id("https://youtu.be/dQw4w9WgXcQ?t=10"), id);
+        assert_eq!(parse_video_id("dQw4w9"), None);
+        assert_eq!(parse_video_id("dQw4w9WgX<Q"), None);
+    }
+
+    #[test]
+    fn queue_videos() {
+        let mut playlist = Playlist::default();
+
+        assert_eq!(playlist.push(video("aaaaaaaaaaa", 1), 3, 0), Ok(true));
+        // the video playing doesn't count as queued
+        assert_eq!(playlist.push(video("bbbbbbbbbbb", 1), 3, 0), Ok(false));
+        assert_eq!(
+            playlist.push(video("ccccccccccc", 1), 3, 0),
+            Err(QueueError::AlreadyQueued)
+        );
+        assert_eq!(playlist.push(video("ccccccccccc", 2), 3, 0), Ok(false));
+        assert_eq!(
+            playlist.push(video("ddddddddddd", 3), 3, 0),
+            Err(QueueError::Full)
+        );
+
+        let next = playlist.advance(1000).map(|v| v.id.clone());
+        assert_eq!(next.as_deref(), Some("bbbbbbbbbbb"));
+        assert_eq!(playlist.elapsed(3500), 2);
+    }
+
+    #[test]
+    fn vote_to_skip() {
+        let mut playlist = Playlist::default();
+        playlist.push(video("aaaaaaaaaaa", 1), 3, 0).unwrap();
+        playlist.push(video("bbbbbbbbbbb", 5), 3, 0).unwrap();
+
+        assert_eq!(playlist.vote_skip(2, 4), (1, false));
+        assert_eq!(playlist.vote_skip(2, 4), (1, false));
+        assert_eq!(playlist.vote_skip(3, 4), (2, false));
+        assert_eq!(playlist.vote_skip(4, 4), (3, true));
+
+        // the votes are cleared and the owner skips alone
+        playlist.advance(0);
+        assert_eq!(playlist.vote_skip(5, 4), (1, true));
+    }
+}
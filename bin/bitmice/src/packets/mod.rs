@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+pub mod recv;
+pub mod send;
+pub mod tribulle;
+
+use bitmice_utils::packet::Field;
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct Angle {
+    pub angle: i16,
+    pub speed: i16,
+    pub loc_1: bool,
+}
+
+#[cfg(test)]
+pub(crate) fn round_trip<P>(packet: P)
+where
+    P: bitmice_utils::packet::Packet + PartialEq + std::fmt::Debug,
+{
+    let mut data = packet.encode();
+    assert_eq!(P::decode(&mut data).unwrap(), packet);
+    assert!(data.is_empty(), "{} bytes left", data.len());
+}
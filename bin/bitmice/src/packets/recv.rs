@@ -0,0 +1,537 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use bitmice_utils::packet::{Packet, Rest};
+
+use super::Angle;
+
+// old
+
+/// Packet of the old protocol, its tokens are the first two bytes of the data
+/// and the values are separated by `\x01`.
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (1, 1))]
+pub struct Old {
+    pub data: String,
+}
+
+// sync
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (4, 4))]
+pub struct MouseMovement {
+    pub round_code: i32,
+    pub is_moving_right: bool,
+    pub is_moving_left: bool,
+    pub position_x: u32,
+    pub position_y: u32,
+    pub speed_x: u16,
+    pub speed_y: u16,
+    pub is_jumping: bool,
+    pub jump_img: i8,
+    pub portal: i8,
+    pub angle: Option<Angle>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (4, 5))]
+pub struct Death {
+    pub round_code: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (4, 9))]
+pub struct Crouch {
+    pub crouch: i8,
+}
+
+// room
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (5, 18))]
+pub struct EnterHole {
+    pub hole_type: u8,
+    pub round_code: i32,
+    pub monde: i32,
+    pub distance: i16,
+    pub hole_x: i16,
+    pub hole_y: i16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (5, 19))]
+pub struct GetCheese {
+    pub round_code: i32,
+    pub cheese_x: i16,
+    pub cheese_y: i16,
+    pub distance: i16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (5, 20))]
+pub struct PlaceObject {
+    pub round_code: i8,
+    pub object_id: i32,
+    pub code: i16,
+    pub position_x: i16,
+    pub position_y: i16,
+    pub angle: i16,
+    pub speed_x: i8,
+    pub speed_y: i8,
+    pub is_ghost: bool,
+    pub is_origin: bool,
+}
+
+/// A point collected on a defilante map.
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (5, 25))]
+pub struct DefilantePoints;
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (5, 38))]
+pub struct EnterRoom {
+    pub community: String,
+    pub room_name: String,
+    pub auto_select: bool,
+}
+
+/// A video queued on a music room, the id or a link to it.
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (5, 70))]
+pub struct SendMusic {
+    pub video_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (5, 73))]
+pub struct MusicPlaylist;
+
+// chat
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (6, 6))]
+pub struct RoomMessage {
+    pub message: String,
+}
+
+// player
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (8, 2))]
+pub struct Langue;
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (8, 30))]
+pub struct Ping;
+
+/// The player clicked on an NPC.
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (8, 44))]
+pub struct TalkToNpc {
+    pub name: String,
+}
+
+// tribe
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (16, 1))]
+pub struct EnterTribeHouse;
+
+// login
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (26, 7))]
+pub struct CreateAccount {
+    pub name: String,
+    pub password: String,
+    pub email: String,
+    pub captcha: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (26, 8))]
+pub struct Login {
+    pub identity: String,
+    pub password: String,
+    pub url: String,
+    pub start_room: String,
+    pub auth_key: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (26, 20))]
+pub struct Captcha;
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (26, 26))]
+pub struct Dummy;
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (26, 40))]
+pub struct Undefined;
+
+// informations
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (28, 1))]
+pub struct CorrectVersion {
+    pub version: u16,
+    pub lang: String,
+    pub ckey: String,
+    pub stand: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (28, 4))]
+pub struct GameLog {
+    pub c: u8,
+    pub cc: u8,
+    pub old_c: u8,
+    pub old_cc: u8,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (28, 6))]
+pub struct PlayerPing {
+    pub ping_count: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (28, 10))]
+pub struct SetShamanMode {
+    pub mode: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (28, 17))]
+pub struct ComputerInfo {
+    pub lang: String,
+    pub os: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (28, 48))]
+pub struct Command {
+    pub command: String,
+}
+
+// tribulle
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (60, 1))]
+pub struct OldTribulle {
+    pub code: u16,
+    pub data: Rest<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (60, 3))]
+pub struct Tribulle {
+    pub code: u16,
+    pub data: Rest<u8>,
+}
+
+// lua
+
+/// A link of a text area was clicked, `callback` is the text after `event:`.
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (29, 21))]
+pub struct TextAreaCallback {
+    pub id: i32,
+    pub callback: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (29, 20))]
+pub struct PopupAnswer {
+    pub id: i32,
+    pub answer: String,
+}
+
+// language
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (176, 1))]
+pub struct SetLanguage {
+    pub langue: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = (176, 2))]
+pub struct LanguageList;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packets::round_trip;
+    use bitmice_utils::ByteArray;
+
+    #[test]
+    fn old() {
+        round_trip(Old {
+            data: String::from("\x05\x07\x01anchor"),
+        });
+    }
+
+    #[test]
+    fn mouse_movement() {
+        let packet = MouseMovement {
+            round_code: 3,
+            is_moving_right: true,
+            is_moving_left: false,
+            position_x: 2700,
+            position_y: 1350,
+            speed_x: 10,
+            speed_y: 20,
+            is_jumping: true,
+            jump_img: 1,
+            portal: 0,
+            angle: None,
+        };
+        round_trip(packet.clone());
+        round_trip(MouseMovement {
+            angle: Some(Angle {
+                angle: 90,
+                speed: -5,
+                loc_1: true,
+            }),
+            ..packet
+        });
+    }
+
+    #[test]
+    fn death() {
+        round_trip(Death { round_code: 12 });
+    }
+
+    #[test]
+    fn crouch() {
+        round_trip(Crouch { crouch: -1 });
+    }
+
+    #[test]
+    fn enter_hole() {
+        round_trip(EnterHole {
+            hole_type: 0,
+            round_code: 1,
+            monde: 0,
+            distance: 20,
+            hole_x: 700,
+            hole_y: 350,
+        });
+    }
+
+    #[test]
+    fn get_cheese() {
+        round_trip(GetCheese {
+            round_code: 1,
+            cheese_x: 400,
+            cheese_y: 200,
+            distance: 15,
+        });
+    }
+
+    #[test]
+    fn place_object() {
+        round_trip(PlaceObject {
+            round_code: 1,
+            object_id: 4,
+            code: 2,
+            position_x: 400,
+            position_y: 200,
+            angle: 90,
+            speed_x: 0,
+            speed_y: 0,
+            is_ghost: false,
+            is_origin: true,
+        });
+    }
+
+    #[test]
+    fn defilante_points() {
+        round_trip(DefilantePoints);
+    }
+
+    #[test]
+    fn enter_room() {
+        round_trip(EnterRoom {
+            community: String::from("en"),
+            room_name: String::from("vanilla1"),
+            auto_select: false,
+        });
+    }
+
+    #[test]
+    fn send_music() {
+        round_trip(SendMusic {
+            video_id: String::from("dQw4w9WgXcQ"),
+        });
+    }
+
+    #[test]
+    fn music_playlist() {
+        round_trip(MusicPlaylist);
+    }
+
+    #[test]
+    fn langue() {
+        round_trip(Langue);
+    }
+
+    #[test]
+    fn ping() {
+        round_trip(Ping);
+    }
+
+    #[test]
+    fn talk_to_npc() {
+        round_trip(TalkToNpc {
+            name: String::from("Papaille"),
+        });
+    }
+
+    #[test]
+    fn create_account() {
+        round_trip(CreateAccount {
+            name: String::from("Andriel"),
+            password: String::from("hash"),
+            email: String::from("andrielfr@proton.me"),
+            captcha: String::from("ABC"),
+            url: String::from("app:/Transformice.swf"),
+        });
+    }
+
+    #[test]
+    fn login() {
+        round_trip(Login {
+            identity: String::from("Andriel#0001"),
+            password: String::from("hash"),
+            url: String::from("app:/Transformice.swf"),
+            start_room: String::from("1"),
+            auth_key: 567865443,
+        });
+    }
+
+    #[test]
+    fn captcha() {
+        round_trip(Captcha);
+    }
+
+    #[test]
+    fn dummy() {
+        round_trip(Dummy);
+    }
+
+    #[test]
+    fn undefined() {
+        // the data of this packet is ignored
+        let mut data = ByteArray::new().write_u8(1);
+        assert_eq!(Undefined::decode(&mut data).unwrap(), Undefined);
+    }
+
+    #[test]
+    fn correct_version() {
+        round_trip(CorrectVersion {
+            version: 818,
+            lang: String::from("en"),
+            ckey: String::from("WKvjvHsJiT"),
+            stand: String::from("StandAlone"),
+        });
+    }
+
+    #[test]
+    fn game_log() {
+        round_trip(GameLog {
+            c: 1,
+            cc: 1,
+            old_c: 8,
+            old_cc: 5,
+            error: String::from("TypeError"),
+        });
+    }
+
+    #[test]
+    fn player_ping() {
+        round_trip(PlayerPing { ping_count: 4 });
+    }
+
+    #[test]
+    fn set_shaman_mode() {
+        round_trip(SetShamanMode { mode: 1 });
+    }
+
+    #[test]
+    fn computer_info() {
+        round_trip(ComputerInfo {
+            lang: String::from("en"),
+            os: String::from("Linux"),
+        });
+    }
+
+    #[test]
+    fn command() {
+        round_trip(Command {
+            command: String::from("np @123"),
+        });
+    }
+
+    #[test]
+    fn room_message() {
+        round_trip(RoomMessage {
+            message: String::from("hello"),
+        });
+    }
+
+    #[test]
+    fn enter_tribe_house() {
+        round_trip(EnterTribeHouse);
+    }
+
+    #[test]
+    fn tribulle() {
+        round_trip(OldTribulle {
+            code: 28,
+            data: Rest(vec![0, 0, 0, 1]),
+        });
+        round_trip(Tribulle {
+            code: 52,
+            data: Rest(vec![0, 0, 0, 2, 0, 1, b'a', 0, 1, b'b']),
+        });
+    }
+
+    #[test]
+    fn text_area_callback() {
+        round_trip(TextAreaCallback {
+            id: 1,
+            callback: String::from("play"),
+        });
+    }
+
+    #[test]
+    fn popup_answer() {
+        round_trip(PopupAnswer {
+            id: 2,
+            answer: String::from("yes"),
+        });
+    }
+
+    #[test]
+    fn set_language() {
+        round_trip(SetLanguage {
+            langue: String::from("português brasileiro"),
+        });
+    }
+
+    #[test]
+    fn language_list() {
+        round_trip(LanguageList);
+    }
+
+    #[test]
+    fn reject_short_data() {
+        let mut data = ByteArray::new().write_i32(1).write_bool(true);
+        assert!(MouseMovement::decode(&mut data).is_err());
+    }
+}
@@ -0,0 +1,836 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use bitmice_utils::packet::{Blob, ByteList, Field, Packet, Rest};
+
+use super::Angle;
+use crate::tokens::send as tokens;
+
+// sync
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::PLAYER_MOVEMENT)]
+pub struct PlayerMovement {
+    pub player_id: u32,
+    pub round_code: i32,
+    pub is_moving_right: bool,
+    pub is_moving_left: bool,
+    pub position_x: u32,
+    pub position_y: u32,
+    pub speed_x: u16,
+    pub speed_y: u16,
+    pub is_jumping: bool,
+    pub jump_img: i8,
+    pub portal: i8,
+    pub angle: Option<Angle>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::CROUCH)]
+pub struct Crouch {
+    pub player_id: u32,
+    pub crouch: i8,
+    pub unknown: u8,
+}
+
+// chat
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::ROOM_MESSAGE)]
+pub struct RoomMessage {
+    pub name: String,
+    pub community: u8,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::MESSAGE)]
+pub struct Message {
+    pub message: String,
+}
+
+// room
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::NEW_MAP)]
+pub struct NewMap {
+    pub map_code: i32,
+    pub players: i16,
+    pub round_code: i8,
+    pub xml: Blob, // compressed with zlib
+    pub author: String,
+    pub perma: i8,
+    pub is_inverted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::MAP_START_TIMER)]
+pub struct MapStartTimer {
+    pub start: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::SPAWN_OBJECT)]
+pub struct SpawnObject {
+    pub object_id: i32,
+    pub code: i16,
+    pub position_x: i16,
+    pub position_y: i16,
+    pub angle: i16,
+    pub speed_x: i8,
+    pub speed_y: i8,
+    pub is_ghost: bool,
+    pub is_origin: bool,
+    pub unknown: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::ENTER_ROOM)]
+pub struct EnterRoom {
+    pub is_standard: bool,
+    pub name: String,
+    pub community: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::ROUND_TIME)]
+pub struct RoundTime {
+    pub time: i16,
+}
+
+/// Video played on a music room, from `time` seconds.
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::MUSIC_VIDEO)]
+pub struct MusicVideo {
+    pub video_id: String,
+    pub title: String,
+    pub time: u16,
+    pub by: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct MusicEntry {
+    pub title: String,
+    pub by: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::MUSIC_PLAYLIST)]
+pub struct MusicPlaylist {
+    pub videos: Vec<MusicEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::TUTORIAL)]
+pub struct Tutorial {
+    pub step: i8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::ROOM_SERVER)]
+pub struct RoomServer {
+    pub server: i8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::ROOM_TYPE)]
+pub struct RoomType {
+    pub game_type: i8,
+}
+
+// login
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::BANNER_LOGIN)]
+pub struct BannerLogin {
+    pub unknown: i8,
+    pub banner_id: i8,
+    pub unknown_2: i8,
+    pub unknown_3: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::PLAYER_IDENTIFICATION)]
+pub struct PlayerIdentification {
+    pub player_id: u32,
+    pub name: String,
+    pub time_played: u32,
+    pub lang_id: i8,
+    pub player_code: u32,
+    pub unknown: bool,
+    pub perms: ByteList<i8>,
+    pub is_admin: bool,
+    pub unknown_2: u16,
+    pub unknown_3: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::CORRECT_VERSION)]
+pub struct CorrectVersion {
+    pub players: i32,
+    pub lang: String,
+    pub lang_2: String,
+    pub auth_key: i32,
+    pub unknown: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::LOGIN_RESULT)]
+pub struct LoginResult {
+    pub code: i8,
+    pub name: String,
+    pub unknown: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::CAPTCHA)]
+pub struct Captcha {
+    pub image: Blob, // compressed with zlib
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::LOGIN_SOURIS)]
+pub struct LoginSouris {
+    pub kind: i8,
+    pub value: u8,
+}
+
+// informations
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::PING)]
+pub struct Ping {
+    pub ping_count: u8,
+    pub last_ping: bool,
+}
+
+/// Kinds of [`GiveCurrency`].
+pub mod currency {
+    pub const CHEESE: u8 = 0;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::GIVE_CURRENCY)]
+pub struct GiveCurrency {
+    pub kind: u8,
+    pub amount: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::IMAGE_LOGIN)]
+pub struct ImageLogin {
+    pub image: String,
+}
+
+// tribulle
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::TRIBULLE)]
+pub struct Tribulle {
+    pub code: u16,
+    pub data: Rest<u8>,
+}
+
+// players
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct PlayerData {
+    pub name: String,
+    pub player_id: u32,
+    pub is_shaman: bool,
+    pub is_dead: bool,
+    pub score: u16,
+    pub has_cheese: bool,
+    pub title_number: u16,
+    pub title_stars: u8,
+    pub gender: u8,
+    pub unknown: String,
+    pub look: String,
+    pub unknown_2: bool,
+    pub color: u32,
+    pub shaman_color: u32,
+    pub unknown_3: u32,
+    pub nick_color: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::PLAYER_LIST)]
+pub struct PlayerList {
+    pub players: Vec<PlayerData>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::PLAYER_RESPAWN)]
+pub struct PlayerRespawn {
+    pub player: PlayerData,
+    pub is_reborn: bool,
+    pub display: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::PLAYER_GET_CHEESE)]
+pub struct PlayerGetCheese {
+    pub player_id: u32,
+    pub has_cheese: bool,
+}
+
+/// Moves the player, by offsets of its current position and speed when the
+/// offset flags are set.
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::MOVE_PLAYER)]
+pub struct MovePlayer {
+    pub position_x: i16,
+    pub position_y: i16,
+    pub position_offset: bool,
+    pub speed_x: i16,
+    pub speed_y: i16,
+    pub speed_offset: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::PLAYER_WIN)]
+pub struct PlayerWin {
+    pub kind: u8,
+    pub player_id: u32,
+    pub score: u16,
+    pub place: u8,
+    pub time: u16, // in centiseconds
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::PLAYER_SCORE)]
+pub struct PlayerScore {
+    pub player_id: u32,
+    pub score: u16,
+}
+
+/// Shamans of the round, a second one only exists on dual shaman maps.
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::SHAMAN_INFO)]
+pub struct ShamanInfo {
+    pub shaman_id: u32,
+    pub second_shaman_id: u32,
+    pub shaman_mode: u8,
+    pub second_shaman_mode: u8,
+    pub shaman_level: u16,
+    pub second_shaman_level: u16,
+    pub shaman_badge: u16,
+    pub second_shaman_badge: u16,
+}
+
+/// A mouse controlled by the server.
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::NPC)]
+pub struct Npc {
+    pub id: i32,
+    pub name: String,
+    pub title: u16,
+    pub is_feminine: bool,
+    pub look: String,
+    pub position_x: i16,
+    pub position_y: i16,
+    pub emote: i16,
+    pub facing_right: bool,
+    pub faces_player: bool,
+    pub interface: u8,
+    pub message: String,
+}
+
+// lua
+
+/// A text area drawn by a minigame, its links call back with the
+/// `event:` of their `href`.
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::ADD_TEXT_AREA)]
+pub struct AddTextArea {
+    pub id: i32,
+    pub text: String,
+    pub x: i16,
+    pub y: i16,
+    pub width: i16,
+    pub height: i16,
+    pub background_color: u32,
+    pub border_color: u32,
+    pub background_alpha: u8, // from 0 to 100
+    pub is_fixed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::UPDATE_TEXT_AREA)]
+pub struct UpdateTextArea {
+    pub id: i32,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::REMOVE_TEXT_AREA)]
+pub struct RemoveTextArea {
+    pub id: i32,
+}
+
+/// A popup drawn by a minigame, `kind` 0 is a message, 1 a yes or no question
+/// and 2 asks for a text.
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::ADD_POPUP)]
+pub struct AddPopup {
+    pub id: i32,
+    pub kind: u8,
+    pub text: String,
+    pub x: i16,
+    pub y: i16,
+    pub width: i16,
+    pub is_fixed: bool,
+}
+
+/// An image shown by a minigame, on an object, a player or a layer of the map.
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::ADD_IMAGE)]
+pub struct AddImage {
+    pub image_id: i32,
+    pub name: String,
+    pub target_kind: u8,
+    pub target: i32,
+    pub x: i16,
+    pub y: i16,
+}
+
+// language
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::SET_LANGUAGE)]
+pub struct SetLanguage {
+    pub lang: String,
+    pub code: String,
+    pub unknown: i16,
+    pub unknown_2: bool,
+    pub unknown_3: bool,
+    pub unknown_4: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct LanguageInfo {
+    pub lang: String,
+    pub name: String,
+    pub code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Packet)]
+#[packet(tokens = tokens::LANGUAGE_LIST)]
+pub struct LanguageList {
+    pub languages: Vec<LanguageInfo>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packets::round_trip;
+
+    fn player_data() -> PlayerData {
+        PlayerData {
+            name: String::from("Andriel#0001"),
+            player_id: 1,
+            is_shaman: false,
+            is_dead: true,
+            score: 10,
+            has_cheese: false,
+            title_number: 5,
+            title_stars: 3,
+            gender: 2,
+            unknown: String::new(),
+            look: String::from("1;0,0,0,0,0,0,0,0,0,0,0"),
+            unknown_2: false,
+            color: 0x95d9d6,
+            shaman_color: 0x95fe3f,
+            unknown_3: 0,
+            nick_color: 0x953,
+        }
+    }
+
+    #[test]
+    fn player_movement() {
+        round_trip(PlayerMovement {
+            player_id: 1,
+            round_code: 3,
+            is_moving_right: false,
+            is_moving_left: true,
+            position_x: 100,
+            position_y: 200,
+            speed_x: 1,
+            speed_y: 2,
+            is_jumping: false,
+            jump_img: 0,
+            portal: 1,
+            angle: Some(Angle {
+                angle: 45,
+                speed: 2,
+                loc_1: false,
+            }),
+        });
+    }
+
+    #[test]
+    fn crouch() {
+        round_trip(Crouch {
+            player_id: 1,
+            crouch: 1,
+            unknown: 0,
+        });
+    }
+
+    #[test]
+    fn new_map() {
+        round_trip(NewMap {
+            map_code: 900,
+            players: 2,
+            round_code: 4,
+            xml: Blob(vec![120, 156, 3, 0, 0, 0, 0, 1]),
+            author: String::from("-"),
+            perma: 100,
+            is_inverted: false,
+        });
+    }
+
+    #[test]
+    fn map_start_timer() {
+        round_trip(MapStartTimer { start: true });
+    }
+
+    #[test]
+    fn spawn_object() {
+        round_trip(SpawnObject {
+            object_id: 4,
+            code: 2,
+            position_x: 400,
+            position_y: 200,
+            angle: 90,
+            speed_x: 0,
+            speed_y: 0,
+            is_ghost: false,
+            is_origin: true,
+            unknown: 0,
+        });
+    }
+
+    #[test]
+    fn enter_room() {
+        round_trip(EnterRoom {
+            is_standard: true,
+            name: String::from("en-vanilla1"),
+            community: String::from("en"),
+        });
+    }
+
+    #[test]
+    fn round_time() {
+        round_trip(RoundTime { time: 120 });
+    }
+
+    #[test]
+    fn music_video() {
+        round_trip(MusicVideo {
+            video_id: String::from("dQw4w9WgXcQ"),
+            title: String::from("dQw4w9WgXcQ"),
+            time: 42,
+            by: String::from("Andriel#0001"),
+        });
+    }
+
+    #[test]
+    fn music_playlist() {
+        round_trip(MusicPlaylist {
+            videos: vec![MusicEntry {
+                title: String::from("dQw4w9WgXcQ"),
+                by: String::from("Andriel#0001"),
+            }],
+        });
+    }
+
+    #[test]
+    fn tutorial() {
+        round_trip(Tutorial { step: 1 });
+    }
+
+    #[test]
+    fn room_message() {
+        round_trip(RoomMessage {
+            name: String::from("Mouse#0000"),
+            community: 1,
+            message: String::from("hello"),
+        });
+    }
+
+    #[test]
+    fn message() {
+        round_trip(Message {
+            message: String::from("You are muted for 60 seconds."),
+        });
+    }
+
+    #[test]
+    fn room_server() {
+        round_trip(RoomServer { server: 0 });
+    }
+
+    #[test]
+    fn room_type() {
+        round_trip(RoomType { game_type: 4 });
+    }
+
+    #[test]
+    fn banner_login() {
+        round_trip(BannerLogin {
+            unknown: 1,
+            banner_id: 2,
+            unknown_2: 1,
+            unknown_3: false,
+        });
+    }
+
+    #[test]
+    fn player_identification() {
+        let packet = PlayerIdentification {
+            player_id: 1,
+            name: String::from("Andriel"),
+            time_played: 3600,
+            lang_id: 0,
+            player_code: 1,
+            unknown: true,
+            perms: ByteList(vec![-1, 13, 11]),
+            is_admin: false,
+            unknown_2: 255,
+            unknown_3: 0,
+        };
+        assert_eq!(packet.encode().len(), 4 + 9 + 4 + 1 + 4 + 1 + 4 + 1 + 2 + 2);
+
+        round_trip(packet);
+    }
+
+    #[test]
+    fn correct_version() {
+        round_trip(CorrectVersion {
+            players: 0,
+            lang: String::from("EN"),
+            lang_2: String::from("EN"),
+            auth_key: 1234,
+            unknown: false,
+        });
+    }
+
+    #[test]
+    fn login_result() {
+        round_trip(LoginResult {
+            code: 2,
+            name: String::from("Andriel"),
+            unknown: String::new(),
+        });
+    }
+
+    #[test]
+    fn captcha() {
+        round_trip(Captcha {
+            image: Blob(vec![1, 2, 3, 4]),
+        });
+    }
+
+    #[test]
+    fn login_souris() {
+        round_trip(LoginSouris {
+            kind: 4,
+            value: 200,
+        });
+    }
+
+    #[test]
+    fn ping() {
+        round_trip(Ping {
+            ping_count: 2,
+            last_ping: true,
+        });
+    }
+
+    #[test]
+    fn give_currency() {
+        round_trip(GiveCurrency {
+            kind: currency::CHEESE,
+            amount: 1,
+        });
+    }
+
+    #[test]
+    fn image_login() {
+        round_trip(ImageLogin {
+            image: String::from("x_noel2014.jpg"),
+        });
+    }
+
+    #[test]
+    fn tribulle() {
+        round_trip(Tribulle {
+            code: 34,
+            data: Rest(vec![0, 0]),
+        });
+    }
+
+    #[test]
+    fn player_list() {
+        round_trip(PlayerList {
+            players: vec![player_data(), player_data()],
+        });
+    }
+
+    #[test]
+    fn player_respawn() {
+        round_trip(PlayerRespawn {
+            player: player_data(),
+            is_reborn: false,
+            display: true,
+        });
+    }
+
+    #[test]
+    fn player_get_cheese() {
+        round_trip(PlayerGetCheese {
+            player_id: 1,
+            has_cheese: true,
+        });
+    }
+
+    #[test]
+    fn move_player() {
+        round_trip(MovePlayer {
+            position_x: 400,
+            position_y: -20,
+            position_offset: false,
+            speed_x: 0,
+            speed_y: 0,
+            speed_offset: true,
+        });
+    }
+
+    #[test]
+    fn player_win() {
+        round_trip(PlayerWin {
+            kind: 0,
+            player_id: 1,
+            score: 16,
+            place: 1,
+            time: 1234,
+        });
+    }
+
+    #[test]
+    fn player_score() {
+        round_trip(PlayerScore {
+            player_id: 1,
+            score: 42,
+        });
+    }
+
+    #[test]
+    fn shaman_info() {
+        round_trip(ShamanInfo {
+            shaman_id: 1,
+            second_shaman_id: 0,
+            shaman_mode: 0,
+            second_shaman_mode: 0,
+            shaman_level: 1,
+            second_shaman_level: 0,
+            shaman_badge: 0,
+            second_shaman_badge: 0,
+        });
+    }
+
+    #[test]
+    fn npc() {
+        round_trip(Npc {
+            id: -1,
+            name: String::from("Papaille"),
+            title: 0,
+            is_feminine: false,
+            look: String::from("1;0,0,0,0,0,0,0,0,0,0,0"),
+            position_x: 400,
+            position_y: 350,
+            emote: 0,
+            facing_right: true,
+            faces_player: true,
+            interface: 0,
+            message: String::new(),
+        });
+    }
+
+    #[test]
+    fn set_language() {
+        round_trip(SetLanguage {
+            lang: String::from("br"),
+            code: String::from("pt"),
+            unknown: 0,
+            unknown_2: false,
+            unknown_3: true,
+            unknown_4: String::new(),
+        });
+    }
+
+    #[test]
+    fn add_text_area() {
+        round_trip(AddTextArea {
+            id: 1,
+            text: String::from("<a href='event:play'>Play</a>"),
+            x: 10,
+            y: 20,
+            width: 100,
+            height: 0,
+            background_color: 0x324650,
+            border_color: 0,
+            background_alpha: 100,
+            is_fixed: true,
+        });
+        round_trip(UpdateTextArea {
+            id: 1,
+            text: String::from("Playing"),
+        });
+        round_trip(RemoveTextArea { id: 1 });
+    }
+
+    #[test]
+    fn add_popup() {
+        round_trip(AddPopup {
+            id: 2,
+            kind: 1,
+            text: String::from("Ready?"),
+            x: 50,
+            y: 50,
+            width: 0,
+            is_fixed: false,
+        });
+    }
+
+    #[test]
+    fn add_image() {
+        round_trip(AddImage {
+            image_id: 1,
+            name: String::from("1507b11c813.png"),
+            target_kind: 2,
+            target: 42,
+            x: -10,
+            y: 0,
+        });
+    }
+
+    #[test]
+    fn language_list() {
+        round_trip(LanguageList {
+            languages: vec![
+                LanguageInfo {
+                    lang: String::from("en"),
+                    name: String::from("English"),
+                    code: String::from("gb"),
+                },
+                LanguageInfo {
+                    lang: String::from("br"),
+                    name: String::from("Português brasileiro"),
+                    code: String::from("br"),
+                },
+            ],
+        });
+    }
+}
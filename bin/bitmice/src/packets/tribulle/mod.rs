@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+//! Packets of the tribulle (friends, ignore lists and whispers), they are
+//! wrapped by the (60, 3) packets and identified by an `u16` code.
+
+use bitmice_utils::{packet::Field, ByteArray};
+
+/// A tribulle packet, it's encoded after its code.
+pub trait Tribulle: Field {
+    const CODE: u16;
+
+    fn encode(&self) -> ByteArray {
+        self.write(ByteArray::new())
+    }
+}
+
+macro_rules! tribulle {
+    ($($packet:ty = $code:expr,)*) => {
+        $(
+            impl $crate::packets::tribulle::Tribulle for $packet {
+                const CODE: u16 = $code;
+            }
+        )*
+    };
+}
+
+pub mod recv;
+pub mod send;
@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use bitmice_utils::packet::Field;
+
+// friends
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct AddFriend {
+    pub tribulle_id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct RemoveFriend {
+    pub tribulle_id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct OpenFriendList {
+    pub tribulle_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct CloseFriendList {
+    pub tribulle_id: i32,
+}
+
+// ignores
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct AddIgnore {
+    pub tribulle_id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct RemoveIgnore {
+    pub tribulle_id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct OpenIgnoreList {
+    pub tribulle_id: i32,
+}
+
+// whispers
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct Whisper {
+    pub tribulle_id: i32,
+    pub name: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct SetWhisperState {
+    pub tribulle_id: i32,
+    pub state: u8,
+    pub message: String,
+}
+
+// tribes
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct TribeMessage {
+    pub tribulle_id: i32,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct InviteToTribe {
+    pub tribulle_id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct AnswerTribeInvite {
+    pub tribulle_id: i32,
+    pub inviter: String,
+    pub accept: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct LeaveTribe {
+    pub tribulle_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct CreateTribe {
+    pub tribulle_id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct SetTribeMessage {
+    pub tribulle_id: i32,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct SetTribeHouseMap {
+    pub tribulle_id: i32,
+    pub xml: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct KickTribeMember {
+    pub tribulle_id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct OpenTribe {
+    pub tribulle_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct CloseTribe {
+    pub tribulle_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct SetTribeRank {
+    pub tribulle_id: i32,
+    pub name: String,
+    pub rank: u8,
+}
+
+tribulle! {
+    AddFriend = 18,
+    RemoveFriend = 20,
+    OpenFriendList = 28,
+    CloseFriendList = 30,
+
+    AddIgnore = 42,
+    RemoveIgnore = 44,
+    OpenIgnoreList = 46,
+
+    Whisper = 52,
+    SetWhisperState = 60,
+
+    TribeMessage = 50,
+    InviteToTribe = 78,
+    AnswerTribeInvite = 80,
+    LeaveTribe = 82,
+    CreateTribe = 84,
+    SetTribeMessage = 98,
+    SetTribeHouseMap = 102,
+    KickTribeMember = 104,
+    OpenTribe = 108,
+    CloseTribe = 110,
+    SetTribeRank = 112,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packets::tribulle::Tribulle;
+
+    fn round_trip<T: Tribulle + PartialEq + std::fmt::Debug>(packet: T) {
+        let mut data = packet.encode();
+        assert_eq!(T::read(&mut data).unwrap(), packet);
+        assert!(data.is_empty(), "{} bytes left", data.len());
+    }
+
+    #[test]
+    fn friends() {
+        round_trip(AddFriend {
+            tribulle_id: 1,
+            name: String::from("Andriel#0001"),
+        });
+        round_trip(RemoveFriend {
+            tribulle_id: 2,
+            name: String::from("Andriel"),
+        });
+        round_trip(OpenFriendList { tribulle_id: 3 });
+        round_trip(CloseFriendList { tribulle_id: 4 });
+    }
+
+    #[test]
+    fn ignores() {
+        round_trip(AddIgnore {
+            tribulle_id: 1,
+            name: String::from("Andriel#0001"),
+        });
+        round_trip(RemoveIgnore {
+            tribulle_id: 2,
+            name: String::from("Andriel#0001"),
+        });
+        round_trip(OpenIgnoreList { tribulle_id: 3 });
+    }
+
+    #[test]
+    fn whispers() {
+        round_trip(Whisper {
+            tribulle_id: 1,
+            name: String::from("Andriel#0001"),
+            message: String::from("hi"),
+        });
+        round_trip(SetWhisperState {
+            tribulle_id: 2,
+            state: 3,
+            message: String::from("busy"),
+        });
+    }
+
+    #[test]
+    fn tribes() {
+        round_trip(CreateTribe {
+            tribulle_id: 1,
+            name: String::from("Mice"),
+        });
+        round_trip(AnswerTribeInvite {
+            tribulle_id: 2,
+            inviter: String::from("Andriel#0001"),
+            accept: true,
+        });
+        round_trip(SetTribeHouseMap {
+            tribulle_id: 3,
+            xml: String::from("<C><P /><Z><S /><D /><O /></Z></C>"),
+        });
+        round_trip(SetTribeRank {
+            tribulle_id: 4,
+            name: String::from("Bob#0001"),
+            rank: 3,
+        });
+    }
+}
@@ -0,0 +1,366 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use bitmice_utils::packet::Field;
+
+/// Results of the tribulle requests.
+pub mod result {
+    pub const SUCCESS: u8 = 1;
+    pub const ALREADY_ADDED: u8 = 4;
+    pub const LIST_FULL: u8 = 7;
+    pub const YOURSELF: u8 = 11;
+    pub const NOT_FOUND: u8 = 12;
+    pub const INVALID_NAME: u8 = 8;
+    pub const NAME_TAKEN: u8 = 9;
+    pub const NOT_ALLOWED: u8 = 18;
+}
+
+/// A friend as seen by the owner of the list, the room is only shown when
+/// the friendship is mutual.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Field)]
+pub struct FriendInfo {
+    pub id: u32,
+    pub name: String,
+    pub gender: u8,
+    pub avatar: u32,
+    pub is_mutual: bool,
+    pub is_connected: bool,
+    pub community: u8,
+    pub room: String,
+    pub last_connection: u32,
+}
+
+// friends
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct AddFriendResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct RemoveFriendResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct FriendConnected {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct FriendDisconnected {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct FriendList {
+    pub friends: Vec<FriendInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct FriendUpdated {
+    pub friend: FriendInfo,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct FriendAdded {
+    pub friend: FriendInfo,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct FriendRemoved {
+    pub id: u32,
+}
+
+// ignores
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct AddIgnoreResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct RemoveIgnoreResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct IgnoreList {
+    pub names: Vec<String>,
+}
+
+// whispers
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct WhisperResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct SetWhisperStateResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct WhisperReceived {
+    pub sender: String,
+    pub community: u32,
+    pub receiver: String,
+    pub message: String,
+}
+
+// tribes
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct TribeMemberInfo {
+    pub id: u32,
+    pub name: String,
+    pub gender: u8,
+    pub rank: u8,
+    pub is_connected: bool,
+    pub room: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct TribeMessageResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct TribeMessageReceived {
+    pub name: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct InviteToTribeResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct AnswerTribeInviteResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct LeaveTribeResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct CreateTribeResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct TribeInvitation {
+    pub inviter: String,
+    pub tribe: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct TribeMemberJoined {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct TribeMemberLeft {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct TribeMemberKicked {
+    pub name: String,
+    pub by: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct SetTribeMessageResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct SetTribeHouseMapResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct KickTribeMemberResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct TribeInfo {
+    pub id: u32,
+    pub name: String,
+    pub message: String,
+    pub ranks: Vec<String>,
+    pub members: Vec<TribeMemberInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct SetTribeRankResult {
+    pub tribulle_id: i32,
+    pub result: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct TribeMemberRankChanged {
+    pub name: String,
+    pub rank: u8,
+    pub by: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct TribeMessageChanged {
+    pub name: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Field)]
+pub struct TribeHouseMapChanged {
+    pub name: String,
+}
+
+tribulle! {
+    AddFriendResult = 19,
+    RemoveFriendResult = 21,
+    FriendConnected = 32,
+    FriendDisconnected = 33,
+    FriendList = 34,
+    FriendUpdated = 35,
+    FriendAdded = 36,
+    FriendRemoved = 37,
+
+    AddIgnoreResult = 43,
+    RemoveIgnoreResult = 45,
+    IgnoreList = 47,
+
+    WhisperResult = 53,
+    SetWhisperStateResult = 61,
+    WhisperReceived = 66,
+
+    TribeMessageResult = 51,
+    TribeMessageReceived = 65,
+    InviteToTribeResult = 79,
+    AnswerTribeInviteResult = 81,
+    LeaveTribeResult = 83,
+    CreateTribeResult = 85,
+    TribeInvitation = 86,
+    TribeMemberJoined = 91,
+    TribeMemberLeft = 92,
+    TribeMemberKicked = 93,
+    SetTribeMessageResult = 99,
+    SetTribeHouseMapResult = 103,
+    KickTribeMemberResult = 105,
+    TribeInfo = 109,
+    SetTribeRankResult = 113,
+    TribeMemberRankChanged = 124,
+    TribeMessageChanged = 125,
+    TribeHouseMapChanged = 127,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packets::tribulle::Tribulle;
+
+    fn round_trip<T: Tribulle + PartialEq + std::fmt::Debug>(packet: T) {
+        let mut data = packet.encode();
+        assert_eq!(T::read(&mut data).unwrap(), packet);
+        assert!(data.is_empty(), "{} bytes left", data.len());
+    }
+
+    #[test]
+    fn friend_list() {
+        round_trip(FriendList {
+            friends: vec![
+                FriendInfo {
+                    id: 1,
+                    name: String::from("Andriel#0001"),
+                    gender: 2,
+                    is_mutual: true,
+                    is_connected: true,
+                    community: 1,
+                    room: String::from("en-1"),
+                    ..Default::default()
+                },
+                FriendInfo {
+                    id: 2,
+                    name: String::from("Bob#0001"),
+                    ..Default::default()
+                },
+            ],
+        });
+    }
+
+    #[test]
+    fn friend_notifications() {
+        round_trip(FriendConnected {
+            name: String::from("Andriel#0001"),
+        });
+        round_trip(FriendDisconnected {
+            name: String::from("Andriel#0001"),
+        });
+        round_trip(FriendRemoved { id: 1 });
+    }
+
+    #[test]
+    fn whisper_received() {
+        round_trip(WhisperReceived {
+            sender: String::from("Andriel#0001"),
+            community: 1,
+            receiver: String::from("Bob#0001"),
+            message: String::from("hi"),
+        });
+    }
+
+    #[test]
+    fn tribe_info() {
+        round_trip(TribeInfo {
+            id: 1,
+            name: String::from("Mice"),
+            message: String::from("welcome"),
+            ranks: vec![String::from("Initiate"), String::from("Leader")],
+            members: vec![TribeMemberInfo {
+                id: 1,
+                name: String::from("Andriel#0001"),
+                gender: 2,
+                rank: 1,
+                is_connected: true,
+                room: String::from("\x03[Tribe] Mice"),
+            }],
+        });
+    }
+
+    #[test]
+    fn tribe_notifications() {
+        round_trip(TribeInvitation {
+            inviter: String::from("Andriel#0001"),
+            tribe: String::from("Mice"),
+        });
+        round_trip(TribeMemberRankChanged {
+            name: String::from("Bob#0001"),
+            rank: 3,
+            by: String::from("Andriel#0001"),
+        });
+        round_trip(TribeMessageReceived {
+            name: String::from("Andriel#0001"),
+            message: String::from("hi"),
+        });
+    }
+}
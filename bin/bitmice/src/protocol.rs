@@ -0,0 +1,294 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{collections::HashMap, sync::Arc};
+
+use bitmice_utils::{
+    crypt::{compute_keys, decode_chunks, xor_cipher},
+    ByteArray,
+};
+
+use crate::config::{Direction, ProtocolConfig};
+
+/// Tokens of the packets encrypted by the client.
+const ENCRYPTED_TOKENS: [(u8, u8); 6] = [
+    (26, 7),  // create account
+    (26, 8),  // login
+    (28, 48), // send commands
+    (60, 1),  // old tribulle
+    (60, 3),  // tribulle
+    (176, 7), // verify code
+];
+
+/// Keys and tokens of a client version.
+#[derive(Debug)]
+pub struct Protocol {
+    pub version: u16,
+    pub ckey: String,
+    pub auth_key: u32,
+    pub login_keys: Vec<u32>,
+    pub packet_keys: Vec<i32>,
+
+    recv_tokens: HashMap<(u8, u8), (u8, u8)>,
+    send_tokens: HashMap<(u8, u8), (u8, u8)>,
+}
+
+impl Protocol {
+    pub fn new(config: &ProtocolConfig) -> Self {
+        let tokens = |direction| {
+            config
+                .tokens
+                .iter()
+                .filter(|m| m.direction == direction)
+                .map(|m| match direction {
+                    Direction::Recv => (m.client, m.server),
+                    Direction::Send => (m.server, m.client),
+                })
+                .collect::<HashMap<_, _>>()
+        };
+
+        Self {
+            version: config.version,
+            ckey: config.ckey.clone(),
+            auth_key: config.auth_key,
+            login_keys: config.login_keys.clone(),
+            packet_keys: config.packet_keys.clone(),
+
+            recv_tokens: tokens(Direction::Recv),
+            send_tokens: tokens(Direction::Send),
+        }
+    }
+
+    /// Tokens known by the server for tokens sent by the client.
+    pub fn recv_tokens(&self, tokens: (u8, u8)) -> (u8, u8) {
+        *self.recv_tokens.get(&tokens).unwrap_or(&tokens)
+    }
+
+    /// Tokens known by the client for tokens sent by the server.
+    pub fn send_tokens(&self, tokens: (u8, u8)) -> (u8, u8) {
+        *self.send_tokens.get(&tokens).unwrap_or(&tokens)
+    }
+
+    pub fn is_valid_auth_key(&self, mut auth_key: u32) -> bool {
+        for key in self.login_keys.iter() {
+            auth_key ^= key;
+        }
+
+        auth_key == self.auth_key
+    }
+
+    /// Decrypts the data of a packet, giving back the packet id to be used by
+    /// the handler. `None` means that the data is malformed.
+    pub fn decrypt(
+        &self,
+        tokens: (u8, u8),
+        packet_id: u8,
+        mut data: ByteArray,
+    ) -> Option<(u8, ByteArray)> {
+        if !ENCRYPTED_TOKENS.contains(&tokens) {
+            return Some((packet_id, data));
+        }
+
+        // login
+        if tokens == (26, 8) {
+            // identification
+            if data.len() < 10 {
+                return None;
+            }
+
+            let keys = compute_keys(&self.packet_keys, b"identification");
+
+            let count = data.try_read_i16().ok()?.max(0) as usize;
+            let mut chunks = (0..count)
+                .map(|_| data.try_read_i32())
+                .collect::<Result<Vec<i32>, _>>()
+                .ok()?;
+            decode_chunks(&mut chunks, &keys);
+
+            let data = chunks
+                .into_iter()
+                .fold(ByteArray::new(), |b, chunk| b.write_i32(chunk));
+            Some((packet_id, data))
+        } else {
+            let packet_id = packet_id.wrapping_add(1);
+
+            let keys = compute_keys(&self.packet_keys, b"msg");
+            let data = ByteArray::with(xor_cipher(data.as_bytes(), packet_id, &keys));
+            Some((packet_id, data))
+        }
+    }
+}
+
+/// Every supported protocol, keyed by version and ckey.
+#[derive(Debug)]
+pub struct Protocols {
+    protocols: Vec<Arc<Protocol>>,
+}
+
+impl Protocols {
+    /// `configs` must not be empty, this is checked when the config is
+    /// loaded.
+    pub fn new(configs: &[ProtocolConfig]) -> Self {
+        Self {
+            protocols: configs.iter().map(|c| Arc::new(Protocol::new(c))).collect(),
+        }
+    }
+
+    /// Protocol used until the client tells its version.
+    pub fn default_protocol(&self) -> Arc<Protocol> {
+        Arc::clone(&self.protocols[0])
+    }
+
+    pub fn get(&self, version: u16, ckey: &str) -> Option<Arc<Protocol>> {
+        self.protocols
+            .iter()
+            .find(|p| p.version == version && p.ckey == ckey)
+            .cloned()
+    }
+
+    pub fn versions(&self) -> Vec<u16> {
+        self.protocols.iter().map(|p| p.version).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Protocols;
+    use crate::config::{parse_toml, Config};
+    use bitmice_utils::{
+        crypt::{compute_keys, encode_chunks, xor_cipher},
+        ByteArray,
+    };
+
+    const CONFIG: &str = r#"
+        [bulle]
+        ports = [1801]
+
+        [server]
+        name = "BitMice"
+        ports = [11801]
+
+        [[protocol]]
+        version = 818
+        ckey = "WKvjvHsJiT"
+        auth_key = 567865443
+        login_keys = [5798205, 2147483648]
+        packet_keys = [
+            13, 16, 42, 55, 40, 23, 19, 43, 11, 55, 87, 74, 116, 105, 114, 77, 117, 77, 97, 93,
+        ]
+
+        [[protocol]]
+        version = 616
+        ckey = "yAdByj"
+        auth_key = 1234
+        login_keys = [42]
+        packet_keys = [
+            1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
+        ]
+
+        [[protocol.tokens]]
+        direction = "recv"
+        client = [5, 26]
+        server = [5, 38]
+
+        [[protocol.tokens]]
+        direction = "send"
+        client = [5, 20]
+        server = [5, 21]
+    "#;
+
+    fn protocols() -> Protocols {
+        Protocols::new(&parse_toml::<Config>(CONFIG).unwrap().protocols)
+    }
+
+    #[test]
+    fn select_by_version_and_ckey() {
+        let protocols = protocols();
+
+        assert_eq!(protocols.default_protocol().version, 818);
+        assert_eq!(protocols.get(616, "yAdByj").unwrap().version, 616);
+        assert!(protocols.get(616, "WKvjvHsJiT").is_none());
+        assert!(protocols.get(700, "yAdByj").is_none());
+        assert_eq!(protocols.versions(), vec![818, 616]);
+    }
+
+    #[test]
+    fn map_tokens() {
+        let protocols = protocols();
+        let current = protocols.get(818, "WKvjvHsJiT").unwrap();
+        let old = protocols.get(616, "yAdByj").unwrap();
+
+        assert_eq!(current.recv_tokens((5, 26)), (5, 26));
+        assert_eq!(old.recv_tokens((5, 26)), (5, 38));
+        assert_eq!(old.recv_tokens((4, 4)), (4, 4));
+
+        assert_eq!(current.send_tokens((5, 21)), (5, 21));
+        assert_eq!(old.send_tokens((5, 21)), (5, 20));
+    }
+
+    #[test]
+    fn check_auth_key() {
+        let protocols = protocols();
+        let current = protocols.default_protocol();
+
+        assert!(current.is_valid_auth_key(567865443 ^ 5798205 ^ 2147483648));
+        assert!(!current.is_valid_auth_key(567865443));
+
+        let old = protocols.get(616, "yAdByj").unwrap();
+        assert!(old.is_valid_auth_key(1234 ^ 42));
+        assert!(!old.is_valid_auth_key(567865443 ^ 5798205 ^ 2147483648));
+    }
+
+    #[test]
+    fn decrypt_packets() {
+        let protocols = protocols();
+        let current = protocols.default_protocol();
+        let old = protocols.get(616, "yAdByj").unwrap();
+
+        let data = ByteArray::new().write_utf("room");
+        let keys = compute_keys(&current.packet_keys, b"msg");
+        let encrypted = ByteArray::with(xor_cipher(data.as_bytes(), 6, &keys));
+
+        let (packet_id, decrypted) = current.decrypt((28, 48), 5, encrypted.clone()).unwrap();
+        assert_eq!(packet_id, 6);
+        assert_eq!(decrypted, data);
+
+        // not encrypted
+        let (packet_id, same) = current.decrypt((5, 38), 5, encrypted.clone()).unwrap();
+        assert_eq!((packet_id, same), (5, encrypted.clone()));
+
+        // each version uses its own keys
+        let (_, decrypted) = old.decrypt((28, 48), 5, encrypted.clone()).unwrap();
+        assert_ne!(decrypted, data);
+        let keys = compute_keys(&old.packet_keys, b"msg");
+        let encrypted = ByteArray::with(xor_cipher(data.as_bytes(), 6, &keys));
+        let (_, decrypted) = old.decrypt((28, 48), 5, encrypted).unwrap();
+        assert_eq!(decrypted, data);
+    }
+
+    #[test]
+    fn decrypt_login() {
+        let protocols = protocols();
+        let current = protocols.default_protocol();
+
+        let original = [0x0005416E, 0x64726965, 0x6C000000, 0x00001234];
+        let mut chunks = original;
+        encode_chunks(
+            &mut chunks,
+            &compute_keys(&current.packet_keys, b"identification"),
+        );
+        let data = chunks
+            .iter()
+            .fold(ByteArray::new().write_i16(4), |b, c| b.write_i32(*c));
+
+        let (_, decrypted) = current.decrypt((26, 8), 1, data).unwrap();
+        let expected = original
+            .iter()
+            .fold(ByteArray::new(), |b, c| b.write_i32(*c));
+        assert_eq!(decrypted, expected);
+
+        // more chunks than data
+        let data = ByteArray::new().write_i16(10).write_i32(1).write_i32(2);
+        assert!(current.decrypt((26, 8), 1, data).is_none());
+    }
+}
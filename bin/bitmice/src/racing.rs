@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::{
+    commands::{self, Reply},
+    room::{self, RoomType},
+    storage, Client, Result, Room, Server,
+};
+
+async fn announce(room: &Arc<Mutex<Room>>, reply: Reply) -> Result {
+    let players = room.lock().await.players();
+
+    for player in players {
+        commands::reply(&player, reply.clone()).await?;
+    }
+
+    Ok(())
+}
+
+/// Counts the round for everyone on a racing room that just changed its map.
+pub async fn start_round(room: &Arc<Mutex<Room>>) {
+    let r = room.lock().await;
+    let players = r.players();
+    if r.room_type != RoomType::Racing || players.len() < room::MIN_PLAYERS_FOR_STATS {
+        return;
+    }
+    drop(r);
+
+    for player in players {
+        player.lock().await.stats.racing_rounds += 1;
+    }
+}
+
+/// Handles a mouse that brought the cheese to the hole on a racing room: the
+/// first one starts the countdown of the end of the round, and the times are
+/// checked against the record of the map.
+pub async fn finish(
+    client: &Arc<Mutex<Client>>,
+    server: &Arc<Mutex<Server>>,
+    room: &Arc<Mutex<Room>>,
+    place: u8,
+    time: u32,
+) -> Result {
+    let mut r = room.lock().await;
+    if place == 1 {
+        // `room::trigger` cuts the round time once the map can be changed
+        r.can_change_map = true;
+    }
+    let map_code = r.map_code;
+    let counts_stats = r.players().len() >= room::MIN_PLAYERS_FOR_STATS;
+    drop(r);
+
+    let mut c = client.lock().await;
+    if counts_stats {
+        c.stats.racing_finishes += 1;
+        if place <= 3 {
+            c.stats.racing_podiums += 1;
+        }
+        if place == 1 {
+            c.stats.racing_firsts += 1;
+        }
+    }
+    let account_id = c.account_id;
+    let name = c.full_name();
+    drop(c);
+
+    // guests can't hold records, and maps without a code can't be told apart
+    if account_id == 0 || map_code <= 0 {
+        if place == 1 {
+            announce(room, Reply::RaceFirst(name, time, None)).await?;
+        }
+
+        return Ok(());
+    }
+
+    let (record, is_record) = storage::run(server, move |s| {
+        Ok((
+            s.map_record(map_code)?,
+            s.save_map_record(map_code, account_id, time)?,
+        ))
+    })
+    .await?;
+    if is_record {
+        announce(room, Reply::RaceRecord(name, time)).await?;
+    } else if place == 1 {
+        let record = record.map(|r| (r.name, r.time));
+        announce(room, Reply::RaceFirst(name, time, record)).await?;
+    }
+
+    Ok(())
+}
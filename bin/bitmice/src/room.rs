@@ -8,13 +8,30 @@ use std::{
     vec,
 };
 
-use bitmice_utils::ByteArray;
+use bitmice_lua::Module;
+use bitmice_utils::{packet::Packet, ByteArray};
 use rand::{seq::SliceRandom, Rng};
 use tokio::sync::Mutex;
 
-use crate::{tokens, Client, Result};
+use crate::{
+    defilante, events, minigame,
+    music::Playlist,
+    packets::send,
+    shaman::{self, Shaman},
+    survivor, tokens, tribe, village, Client, Result,
+};
 
 const VANILLA_MAPS_FOLDER: &str = "./assets/maps/vanilla/";
+const BOOTCAMP_MAPS_FOLDER: &str = "./assets/maps/bootcamp/";
+const SURVIVOR_MAPS_FOLDER: &str = "./assets/maps/survivor/";
+const VAMPIRE_SURVIVOR_MAPS_FOLDER: &str = "./assets/maps/vampire/";
+const DEFILANTE_MAPS_FOLDER: &str = "./assets/maps/defilante/";
+
+/// Perm of the bootcamp maps.
+pub const BOOTCAMP_PERMA: i8 = 3;
+
+/// Players needed on a room for the cheese and firsts to be counted.
+pub const MIN_PLAYERS_FOR_STATS: usize = 2;
 
 #[derive(Debug)]
 pub struct Room {
@@ -24,14 +41,23 @@ pub struct Room {
     pub map_xml: String,
     pub next_map: String,
     sync_name: String,
+    /// Anchors put by the shaman on the round.
+    pub anchors: Vec<String>,
 
     pub map_code: i32,
     pub map_perma: i8,
     pub round_time: i16,
     pub start_time: u128,
-    pub time_change_map: u16,
     pub last_round_code: i8,
     sync_code: i32,
+    pub shamans: Vec<Shaman>,
+    /// Mice that entered the hole on the round.
+    pub finished: u8,
+    /// Videos of a music room.
+    pub playlist: Playlist,
+    /// Minigame script running on the room, the lua state can't be shared
+    /// between threads. It's taken out of the room while an event runs.
+    pub module: Option<Arc<std::sync::Mutex<Module>>>,
 
     pub can_change_map: bool,
     pub is_new: bool,
@@ -52,14 +78,18 @@ impl Room {
             map_xml: String::new(),
             next_map: String::from("-1"),
             sync_name: String::new(),
+            anchors: Vec::new(),
 
             map_code: -1,
             map_perma: 0,
             round_time: -1,
             start_time: 0,
-            time_change_map: 0,
             last_round_code: -1,
             sync_code: -1,
+            shamans: Vec::new(),
+            finished: 0,
+            playlist: Playlist::default(),
+            module: None,
 
             can_change_map: true,
             is_new: true,
@@ -73,7 +103,7 @@ impl Room {
     }
 
     pub fn players(&self) -> Vec<Arc<Mutex<Client>>> {
-        self.clients.iter().map(|c| Arc::clone(c)).collect()
+        self.clients.iter().map(Arc::clone).collect()
     }
 
     pub async fn alive(&self) -> i16 {
@@ -93,13 +123,26 @@ impl Room {
     fn select_map(&mut self) {
         if &self.next_map == "-1" {
             match self.map_type {
-                MapType::Vanilla => {
-                    let (map_code, xml) = self.get_vanilla_map_xml();
+                // back to the vanilla rotation after a map chosen by `/np` or
+                // by a minigame
+                MapType::Vanilla | MapType::Custom | MapType::Perm | MapType::Xml => {
+                    let (folder, perma) = *map_pools(self.room_type)
+                        .choose(&mut rand::thread_rng())
+                        .unwrap();
+                    // the maps of a running event replace the rotation
+                    let (map_code, map_name, xml) = match events::map_xml(self) {
+                        Some((event, xml)) => (0, event, xml),
+                        None => {
+                            let (map_code, xml) = self.get_map_xml(folder);
+                            (map_code, String::from("BitMice"), xml)
+                        }
+                    };
 
                     self.map_code = map_code;
-                    self.map_name = String::from("BitMice");
+                    self.map_type = MapType::Vanilla;
+                    self.map_name = map_name;
                     self.map_xml = xml;
-                    self.map_perma = 22;
+                    self.map_perma = perma;
                     self.is_inverted_map = false;
                 }
                 _ => {
@@ -118,25 +161,27 @@ impl Room {
         self.next_map = String::from("-1");
         self.map_code = -1;
 
-        if let Ok(next_code) = next_map.parse::<i32>() {
+        if !is_valid_map(&next_map) {
+            log::warn!("[{}] invalid next map: {}", self.name, next_map);
+        } else if let Ok(next_code) = next_map.parse::<i32>() {
             self.map_code = next_code;
-        } else if next_map.starts_with("@") {
+        } else if let Some(map_code) = next_map.strip_prefix('@') {
             // custom
-            let map_code = next_map[1..].parse::<i32>().unwrap();
+            let map_code = map_code.parse::<i32>().unwrap_or_default();
 
             if let Some(info) = get_map_info(map_code) {
                 self.map_code = map_code;
                 self.map_name = info[0].to_string();
                 self.map_xml = info[1].to_string();
-                self.map_perma = info[2].parse::<i8>().unwrap();
+                self.map_perma = info[2].parse::<i8>().unwrap_or_default();
                 self.map_type = MapType::Custom;
                 self.is_inverted_map = false;
             } else {
                 self.map_code = 0;
             }
-        } else if next_map.starts_with("#") {
+        } else if let Some(map_perma) = next_map.strip_prefix('#') {
             // perm
-            let map_perma = next_map[1..].parse::<i8>().unwrap();
+            let map_perma = map_perma.parse::<i8>().unwrap_or_default();
 
             self.map_code = -1;
             self.map_perma = map_perma;
@@ -151,25 +196,20 @@ impl Room {
             self.map_perma = 22;
             self.map_type = MapType::Xml;
             self.is_inverted_map = false;
-        } else {
-            return;
         }
     }
 
     pub async fn start_map(&self, start: bool) -> Result {
-        self.send_data(
-            tokens::send::MAP_START_TIMER,
-            ByteArray::new().write_bool(start), // use start variable
-        )
-        .await?;
+        self.send_packet(send::MapStartTimer { start }).await?;
 
         Ok(())
     }
 
-    fn get_vanilla_map_xml(&self) -> (i32, String) {
+    /// Picks a random map of the pool in `folder`.
+    fn get_map_xml(&self, folder: &str) -> (i32, String) {
         let mut xml = String::new();
 
-        let file_list = std::fs::read_dir(VANILLA_MAPS_FOLDER)
+        let file_list = std::fs::read_dir(folder)
             .unwrap()
             .map(|m| m.unwrap())
             .collect::<Vec<std::fs::DirEntry>>();
@@ -195,7 +235,7 @@ impl Room {
             })
             .collect::<Vec<i32>>();
         let map_code = code_list.choose(&mut rand::thread_rng()).unwrap();
-        let file = std::fs::File::open(format!("{}{}.xml", VANILLA_MAPS_FOLDER, map_code));
+        let file = std::fs::File::open(format!("{}{}.xml", folder, map_code));
         if let Ok(mut f) = file {
             f.read_to_string(&mut xml).unwrap();
         }
@@ -224,21 +264,23 @@ impl Room {
         self.sync_code
     }
 
+    /// Adds a client to the room, if it isn't new the caller must start the
+    /// play of the client once the room is unlocked.
     pub async fn add_client(&mut self, client: Arc<Mutex<Client>>) -> Result {
         if !self.is_new {
             let mut c = client.lock().await;
 
-            c.is_dead = true;
+            // there are no rounds to wait for on the village
+            c.is_dead = self.room_type != RoomType::Village;
             let client_id = c.id;
-            let b = ByteArray::new()
-                .write_bytes(c.player_data())
-                .write_bool(false)
-                .write_bool(true);
+            let packet = send::PlayerRespawn {
+                player: c.player_data(),
+                is_reborn: false,
+                display: true,
+            };
             drop(c);
 
-            self.send_data_except(client_id, tokens::send::PLAYER_RESPAWN, b)
-                .await?;
-            crate::client::start_play(Arc::clone(&client)).await?;
+            self.send_packet_except(client_id, packet).await?;
         }
 
         self.clients.push(client);
@@ -271,6 +313,15 @@ impl Room {
         }
     }
 
+    pub async fn send_packet<P: Packet>(&self, packet: P) -> Result {
+        self.send_data(P::TOKENS, packet.encode()).await
+    }
+
+    pub async fn send_packet_except<P: Packet>(&self, client_id: u32, packet: P) -> Result {
+        self.send_data_except(client_id, P::TOKENS, packet.encode())
+            .await
+    }
+
     pub async fn send_data(&self, tokens: (u8, u8), data: ByteArray) -> Result {
         for player in self.players() {
             match player.try_lock() {
@@ -315,6 +366,9 @@ impl Room {
 }
 
 pub async fn change_map(room: Arc<Mutex<Room>>) -> Result {
+    survivor::end_round(&room).await;
+    defilante::end_round(&room).await?;
+
     let mut r = room.lock().await;
 
     r.sync_name = String::new();
@@ -333,14 +387,28 @@ pub async fn change_map(room: Arc<Mutex<Room>>) -> Result {
         r.is_specific_map = true;
         r.map_type = MapType::Tutorial;
         r.round_time = 0;
+    } else if r.name.starts_with(tribe::HOUSE_PREFIX) {
+        // the map xml is the one saved by the tribe
+        r.map_code = 0;
+        r.map_name = r.name[tribe::HOUSE_PREFIX.len()..].to_string();
+        r.map_perma = 22;
+        r.map_type = MapType::Tribe;
+        r.round_time = 0;
+    } else if r.name.starts_with(village::ROOM_PREFIX) {
+        // a single map without rounds
+        r.room_type = RoomType::Village;
+        r.map_code = 0;
+        r.map_name = String::from("Village");
+        r.map_xml = village::map_xml();
+        r.map_perma = 22;
+        r.map_type = MapType::Village;
+        r.round_time = 0;
     } else if r.name.starts_with("\x03[Totem] ") {
         r.map_code = 444;
         r.is_specific_map = true;
         r.map_type = MapType::Totem;
         r.round_time = 0;
     } else {
-        r.select_map();
-
         if r.name.starts_with("bootcamp") {
             r.room_type = RoomType::Bootcamp;
             r.round_time = 360;
@@ -352,23 +420,50 @@ pub async fn change_map(room: Arc<Mutex<Room>>) -> Result {
         } else if r.name.starts_with("survivor") {
             r.room_type = RoomType::Survivor;
         }
+
+        // the map pool depends on the type of the room
+        r.select_map();
     }
     r.start_time = UNIX_EPOCH.elapsed().unwrap().as_millis();
 
+    r.anchors.clear();
+    r.finished = 0;
+
     let players = r.players();
     drop(r);
-    for player in players {
-        let mut p = player.lock().await;
+    for player in &players {
+        player.lock().await.reset_player();
+    }
 
-        p.reset_player();
-        drop(p);
+    shaman::select(&room).await;
+    crate::racing::start_round(&room).await;
+    for player in players {
         crate::client::start_play(player).await?;
     }
+    defilante::start_round(&room).await;
 
     let mut r = room.lock().await;
     r.can_change_map = false;
+    drop(r);
 
-    Ok(())
+    minigame::emit(&room, "eventNewGame", Vec::new()).await
+}
+
+/// Folders of the maps played by each type of room, with the perm of the
+/// maps. A pool is picked at random for each round.
+fn map_pools(room_type: RoomType) -> &'static [(&'static str, i8)] {
+    match room_type {
+        RoomType::Bootcamp => &[(BOOTCAMP_MAPS_FOLDER, BOOTCAMP_PERMA)],
+        RoomType::Defilante => &[(DEFILANTE_MAPS_FOLDER, defilante::DEFILANTE_PERMA)],
+        RoomType::Survivor => &[
+            (SURVIVOR_MAPS_FOLDER, survivor::SURVIVOR_PERMA),
+            (
+                VAMPIRE_SURVIVOR_MAPS_FOLDER,
+                survivor::VAMPIRE_SURVIVOR_PERMA,
+            ),
+        ],
+        _ => &[(VANILLA_MAPS_FOLDER, 22)],
+    }
 }
 
 pub async fn trigger(room: Arc<Mutex<Room>>) -> Result {
@@ -378,33 +473,50 @@ pub async fn trigger(room: Arc<Mutex<Room>>) -> Result {
 
             r.round_time -= 1;
 
-            let alive_count = r.alive().await;
+            let map_type = r.map_type;
+            let room_type = r.room_type;
+
+            let alive_count = match room_type {
+                RoomType::Survivor => survivor::alive_mice(&r).await,
+                _ => r.alive().await,
+            };
             let round_time = r.round_time;
+            let elapsed = UNIX_EPOCH
+                .elapsed()
+                .unwrap()
+                .as_millis()
+                .saturating_sub(r.start_time);
 
             let can_change_map = r.can_change_map;
             let is_new = r.is_new;
-
-            let map_type = r.map_type;
-            let room_type = r.room_type;
             drop(r);
 
             if room_type == RoomType::Racing && can_change_map && round_time > 20 {
                 let mut r = room.lock().await;
                 r.round_time = 21;
 
-                for player in r.players() {
-                    let mut p = player.lock().await;
-
-                    p.send_data(
-                        tokens::send::ROUND_TIME,
-                        ByteArray::new().write_i16(r.round_time),
-                    )
-                    .await
-                    .unwrap();
+                if let Err(e) = r.send_packet(send::RoundTime { time: 21 }).await {
+                    log::error!("failed to send the round time of [{}]: {}", r.name, e);
                 }
             }
 
-            if is_new || alive_count <= 0 || can_change_map && round_time <= 0 {
+            let args = vec![
+                (elapsed as i64).into(),
+                (round_time.max(0) as i64 * 1000).into(),
+            ];
+            if let Err(e) = minigame::emit(&room, "eventLoop", args).await {
+                log::error!(
+                    "failed to run the module of [{}]: {}",
+                    room.lock().await.name,
+                    e
+                );
+            }
+
+            // survivor and defilante rounds also end when the time runs out
+            let time_is_over = (can_change_map
+                || matches!(room_type, RoomType::Survivor | RoomType::Defilante))
+                && round_time <= 0;
+            if is_new || alive_count <= 0 || time_is_over {
                 if is_new {
                     let mut r = room.lock().await;
                     r.is_new = false;
@@ -416,7 +528,18 @@ pub async fn trigger(room: Arc<Mutex<Room>>) -> Result {
                 {
                     break;
                 }
-                crate::room::change_map(Arc::clone(&room)).await.unwrap();
+                if let Err(e) = crate::room::change_map(Arc::clone(&room)).await {
+                    log::error!(
+                        "failed to change the map of [{}]: {}",
+                        room.lock().await.name,
+                        e
+                    );
+                }
+
+                // the village map is loaded once
+                if room.lock().await.map_type == MapType::Village {
+                    break;
+                }
             }
 
             tokio::time::sleep(Duration::from_secs(1)).await;
@@ -432,8 +555,10 @@ pub enum MapType {
     Editor,
     Perm,
     Totem,
+    Tribe,
     Tutorial,
     Vanilla,
+    Village,
     Xml,
 }
 
@@ -444,6 +569,29 @@ pub enum RoomType {
     Racing,
     Survivor,
     Vanilla,
+    Village,
+}
+
+/// Score given to a mouse for entering the hole on `place`.
+pub fn place_points(place: u8) -> u16 {
+    match place {
+        1 => 16,
+        2 => 14,
+        3 => 12,
+        _ => 10,
+    }
+}
+
+/// Whether `map` can be the next map of a room: `@code` for a custom map,
+/// `#perm` for a category, a vanilla code, `-1` for the rotation, or an xml.
+pub fn is_valid_map(map: &str) -> bool {
+    if let Some(code) = map.strip_prefix('@') {
+        code.parse::<i32>().is_ok_and(|c| c >= 0)
+    } else if let Some(perma) = map.strip_prefix('#') {
+        perma.parse::<i8>().is_ok_and(|p| p >= 0)
+    } else {
+        map.parse::<i32>().is_ok_and(|c| c >= -1) || map.starts_with('<')
+    }
 }
 
 fn get_map_info<'a>(_map_code: i32) -> Option<Vec<&'a str>> {
@@ -453,3 +601,33 @@ fn get_map_info<'a>(_map_code: i32) -> Option<Vec<&'a str>> {
         "22",
     ])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{is_valid_map, place_points};
+
+    #[test]
+    fn points_by_place() {
+        assert_eq!(place_points(1), 16);
+        assert_eq!(place_points(2), 14);
+        assert_eq!(place_points(3), 12);
+        assert_eq!(place_points(4), 10);
+        assert_eq!(place_points(255), 10);
+    }
+
+    #[test]
+    fn valid_maps() {
+        for map in [
+            "-1",
+            "7",
+            "@123",
+            "#17",
+            "<C><P /><Z><S /><D /><O /></Z></C>",
+        ] {
+            assert!(is_valid_map(map), "{}", map);
+        }
+        for map in ["", "@", "@abc", "@-1", "#-5", "#1000", "-2", "map"] {
+            assert!(!is_valid_map(map), "{}", map);
+        }
+    }
+}
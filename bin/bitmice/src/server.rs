@@ -1,51 +1,64 @@
 // SPDX-License-Identifier: BSD-3-Clause
 // Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
 
-use bitmice_utils::{
-    bytes_to_string,
-    crypt::{compute_keys, decode_chunks},
-    str_to_bytes, ByteArray,
-};
+use bitmice_database::Storage;
+use bitmice_utils::{bytes_to_string, framer::Framer, str_to_bytes, ByteArray};
 use once_cell::sync::Lazy;
-use std::{sync::Arc, time::Duration, usize};
+use std::{sync::Arc, time::Duration};
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
     net::tcp::{OwnedReadHalf, OwnedWriteHalf},
     sync::{mpsc, Mutex},
 };
 
-use crate::{room::MapType, tokens, Client, Result, Room};
+use crate::{
+    config::Config,
+    minigame::{self, Registry},
+    protocol::Protocols,
+    room::MapType,
+    storage, tokens, tribulle,
+    village::Village,
+    Client, Room,
+};
 
 pub static CLIENTS: Lazy<Mutex<Vec<Arc<Mutex<Client>>>>> = Lazy::new(|| Mutex::new(Vec::new()));
 pub static ROOMS: Lazy<Mutex<Vec<Arc<Mutex<Room>>>>> = Lazy::new(|| Mutex::new(Vec::new()));
 
-#[derive(Debug)]
 pub struct Server {
-    pub ckey: String,
+    pub config: Config,
+    pub storage: Arc<dyn Storage>,
+    pub protocols: Protocols,
+    pub village: Arc<Village>,
+    pub minigames: Arc<Registry>,
 
-    pub auth_key: u32,
     pub last_player_id: u32,
-    pub login_keys: Vec<u32>,
-    pub packet_keys: Vec<i32>,
-    pub version: u16,
+}
+
+impl std::fmt::Debug for Server {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Server")
+            .field("protocols", &self.protocols.versions())
+            .field("last_player_id", &self.last_player_id)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Server {
     pub fn new(
-        auth_key: u32,
-        ckey: &str,
-        version: u16,
-        login_keys: &[u32],
-        packet_keys: &[i32],
+        config: Config,
+        storage: Arc<dyn Storage>,
+        village: Village,
+        minigames: Registry,
     ) -> Self {
         Self {
-            ckey: ckey.to_string(),
+            protocols: Protocols::new(&config.protocols),
+            village: Arc::new(village),
+            minigames: Arc::new(minigames),
+
+            last_player_id: config.server.last_player_id,
 
-            auth_key,
-            last_player_id: 0,
-            login_keys: login_keys.to_vec(),
-            packet_keys: packet_keys.to_vec(),
-            version,
+            config,
+            storage,
         }
     }
 
@@ -65,18 +78,10 @@ impl Server {
         ps
     }
 
-    pub async fn get_player(&self, mut name: String) -> Option<Arc<Mutex<Client>>> {
-        let clients = CLIENTS.lock().await;
-
-        for client in clients.iter() {
-            let c = client.lock().await;
-
-            if c.is_guest {
-                name = format!("*{}", name);
-            }
-
-            if *c.name == name || *c.full_name() == name {
-                return Some(Arc::clone(client));
+    pub async fn get_player(&self, name: String) -> Option<Arc<Mutex<Client>>> {
+        for client in self.players().await {
+            if client.lock().await.has_name(&name) {
+                return Some(client);
             }
         }
 
@@ -94,33 +99,11 @@ impl Server {
         }
     }
 
-    pub async fn rooms(&self) -> Vec<Arc<Mutex<Room>>> {
-        let rooms = ROOMS.lock().await;
-
-        let mut rs = Vec::new();
-        for room in rooms.iter() {
-            rs.push(Arc::clone(room));
-        }
-
-        rs
-    }
-
     pub async fn add_room(&self, room: Arc<Mutex<Room>>) {
         let mut rooms = ROOMS.lock().await;
         rooms.push(room);
     }
 
-    pub async fn remove_room(&self, room: Arc<Mutex<Room>>) {
-        let mut rooms = ROOMS.lock().await;
-
-        if let Some(index) = rooms
-            .iter()
-            .position(|v| std::ptr::eq(v.as_ref(), room.as_ref()))
-        {
-            rooms.swap_remove(index);
-        }
-    }
-
     pub async fn get_room(&self, name: String, lang: String) -> Option<Arc<Mutex<Room>>> {
         let rooms = ROOMS.lock().await;
 
@@ -165,37 +148,6 @@ impl Server {
 
         last_room.unwrap()
     }
-
-    pub async fn send_data(&self, tokens: (u8, u8), data: ByteArray) -> Result {
-        for player in self.players().await {
-            match player.try_lock() {
-                Ok(mut player) => player.send_data(tokens, data.clone()).await?,
-                Err(_) => continue,
-            }
-        }
-
-        Ok(())
-    }
-
-    pub async fn send_data_except(
-        &self,
-        client_id: u32,
-        tokens: (u8, u8),
-        data: ByteArray,
-    ) -> Result {
-        for player in self.players().await {
-            match player.try_lock() {
-                Ok(mut player) => {
-                    if player.id != client_id {
-                        player.send_data(tokens, data.clone()).await?
-                    }
-                }
-                Err(_) => continue,
-            }
-        }
-
-        Ok(())
-    }
 }
 
 pub async fn handle_client(
@@ -225,7 +177,13 @@ pub async fn handle_client(
                     None => break,
                 };
 
-                if let Err(_) = writer.lock().await.write_all(data.as_bytes()).await {
+                if writer
+                    .lock()
+                    .await
+                    .write_all(data.as_bytes())
+                    .await
+                    .is_err()
+                {
                     log::error!("failed to write data");
                     drop(data_rx);
                     player_disconnect(player_w).await;
@@ -237,19 +195,21 @@ pub async fn handle_client(
         // reader
         let reader = reader.clone();
         tokio::spawn(async move {
-            loop {
-                let mut buffer = vec![0u8; 4096];
+            let server = Arc::clone(&player.lock().await.server);
+            let max_packet_size = server.lock().await.config.server.max_packet_size;
+            drop(server);
 
-                match reader.lock().await.read(&mut buffer).await {
-                    Ok(s) => {
-                        if s == 0 {
-                            drop(data_tx);
-                            player_disconnect(Arc::clone(&player)).await;
-                            break;
-                        }
+            let mut framer = Framer::new(max_packet_size);
+            let mut buffer = vec![0u8; 4096];
 
-                        buffer.truncate(s);
+            loop {
+                let size = match reader.lock().await.read(&mut buffer).await {
+                    Ok(0) => {
+                        drop(data_tx);
+                        player_disconnect(Arc::clone(&player)).await;
+                        break;
                     }
+                    Ok(s) => s,
                     Err(_) => {
                         log::error!("failed to read data");
                         drop(data_tx);
@@ -257,174 +217,141 @@ pub async fn handle_client(
                         break;
                     }
                 };
+                let bytes = &buffer[..size];
 
-                let mut data = ByteArray::with(buffer);
-                if data.is_empty() {
-                    continue;
-                }
+                log::trace!("received data = [{}]", bytes_to_string(bytes));
+                if framer.buffered() == 0
+                    && bytes_to_string(bytes).contains("<policy-file-request/>")
+                {
+                    let policy = str_to_bytes("<cross-domain-policy><allow-access-from domain=\"*\" to-ports=\"*\"/></cross-domain-policy>");
+                    let _ = data_tx.send(ByteArray::with(policy)).await;
 
-                // parser
-                let player = Arc::clone(&player);
-                let data_tx = data_tx.clone();
-                tokio::spawn(async move {
-                    let mut client = player.lock().await;
+                    let server = Arc::clone(&player.lock().await.server);
+                    let policy_timeout = server.lock().await.config.timeouts.policy;
+                    tokio::time::sleep(Duration::from_secs(policy_timeout)).await;
 
-                    if client.is_closed {
-                        return;
-                    }
+                    drop(data_tx);
+                    player_disconnect(Arc::clone(&player)).await;
+                    break;
+                }
 
-                    log::trace!("received data = [{}]", bytes_to_string(&data.as_bytes()));
-                    if bytes_to_string(&data.as_bytes()).contains("<policy-file-request/>") {
-                        let policy = str_to_bytes("<cross-domain-policy><allow-access-from domain=\"*\" to-ports=\"*\"/></cross-domain-policy>");
-                        let _ = data_tx.send(ByteArray::with(policy)).await.unwrap();
-                        tokio::time::sleep(Duration::from_secs(2)).await;
-                        client.close().await.unwrap();
-                    }
-                    drop(client);
+                // packets are parsed in the order they were received
+                framer.push(bytes);
+                loop {
+                    match framer.next_packet() {
+                        Ok(Some(packet)) => parse_packet(Arc::clone(&player), packet).await,
+                        Ok(None) => break,
+                        Err(e) => {
+                            let client = player.lock().await;
+                            log::error!("[{}] sent an invalid packet: {}", client.full_name(), e);
+                            drop(client);
 
-                    let mut length = 0;
-                    let mut o = 0;
-                    let mut i = 0;
-                    loop {
-                        if data.is_empty() {
+                            player_disconnect(Arc::clone(&player)).await;
                             return;
                         }
-
-                        let byte = data.read_u8() & 255;
-                        length |= (byte & 127) << 7 * i;
-                        o = o << 7;
-                        i += 1;
-
-                        if !((byte & 128) == 128 && i < 5) {
-                            length += 1;
-                            break;
-                        }
                     }
+                }
+            }
+        });
+    });
+}
 
-                    if ((o >> 1) & length) != 0 {
-                        length |= o;
-                    }
+async fn parse_packet(player: Arc<Mutex<Client>>, mut data: ByteArray) {
+    let mut client = player.lock().await;
 
-                    let mut length = length as usize;
-                    if length == 0 {
-                        return;
-                    } else if length > data.len() {
-                        length = data.len();
-                    }
+    if client.is_closed {
+        return;
+    }
 
-                    data = data.read(length);
-                    let mut client = player.lock().await;
-                    let mut packet_id = data.read_u8();
-                    client.packet_id = (client.packet_id + 1) % 100;
-
-                    let tokens = (data.read_u8(), data.read_u8());
-
-                    if tokens.0 > 0 && tokens.1 > 0 {
-                        if !vec![(26, 26), (4, 4)].contains(&tokens) {
-                            log::debug!(
-                                "received packet id = [{}], tokens = {:?} from [{}]",
-                                client.packet_id,
-                                tokens,
-                                client.full_name(),
-                            );
-                        }
+    let (packet_id, c, cc) = match (data.try_read_u8(), data.try_read_u8(), data.try_read_u8()) {
+        (Ok(packet_id), Ok(c), Ok(cc)) => (packet_id, c, cc),
+        _ => return,
+    };
+    client.packet_id = (client.packet_id + 1) % 100;
+
+    let protocol = Arc::clone(&client.protocol);
+    let tokens = protocol.recv_tokens((c, cc));
+
+    if tokens.0 > 0 && tokens.1 > 0 {
+        if ![(26, 26), (4, 4)].contains(&tokens) {
+            log::debug!(
+                "received packet id = [{}], tokens = {:?} from [{}]",
+                client.packet_id,
+                tokens,
+                client.full_name(),
+            );
+        }
 
-                        if tokens != (26, 26) {
-                            client.update_last_response();
-                        }
+        if tokens != (26, 26) {
+            client.update_last_response();
+        }
 
-                        let server = Arc::clone(&client.server);
-                        drop(client);
-                        let client = Arc::clone(&player);
-
-                        // decrypt packets
-                        if vec![
-                            (26, 7),  // create account
-                            (26, 8),  // login
-                            (28, 48), // send commands
-                            (60, 1),  // old tribulle
-                            (60, 3),  // tribulle
-                            (176, 7), // verify code
-                        ]
-                        .contains(&tokens)
-                        {
-                            let s = server.lock().await;
-
-                            // login
-                            if vec![(26, 8)].contains(&tokens) {
-                                // identification
-
-                                if data.len() < 10 {
-                                    return;
-                                }
-
-                                let keys = compute_keys(
-                                    s.packet_keys.clone(),
-                                    "identification".as_bytes(),
-                                );
-
-                                let mut chunks = Vec::new();
-                                (0..data.read_i16()).for_each(|_| chunks.push(data.read_i32()));
-                                let length = chunks.len() as u32;
-                                decode_chunks(&mut chunks, length, keys);
-
-                                let mut b = ByteArray::new();
-                                for chunk in chunks {
-                                    b = b.write_i32(chunk);
-                                }
-
-                                data = b;
-                            } else {
-                                // decrypt
-                                packet_id += 1;
-
-                                let keys = compute_keys(s.packet_keys.clone(), "msg".as_bytes());
-                                let mut bytes = Vec::new();
-                                for (i, byte) in data.as_bytes().iter().enumerate() {
-                                    bytes.push(
-                                        (*byte as i128
-                                            ^ keys[((packet_id + i as u8) % 20) as usize])
-                                            as u8
-                                            & 0xFF,
-                                    );
-                                }
-
-                                data = ByteArray::with(bytes);
-                            }
-                        }
+        let server = Arc::clone(&client.server);
+        drop(client);
+        let client = Arc::clone(&player);
 
-                        tokens::recv::parse_tokens(client, server, tokens, data, packet_id)
-                            .await
-                            .unwrap();
-                    }
-                });
-            }
-        });
-    });
+        let (packet_id, data) = match protocol.decrypt(tokens, packet_id, data) {
+            Some(d) => d,
+            None => return,
+        };
+
+        if let Err(e) = tokens::recv::parse_tokens(client, server, tokens, data, packet_id).await {
+            log::error!(
+                "failed to handle tokens {:?} from [{}]: {}",
+                tokens,
+                player.lock().await.full_name(),
+                e
+            );
+        }
+    }
 }
 
 async fn player_disconnect(player: Arc<Mutex<Client>>) {
     let client = player.lock().await;
     let client_id = client.id;
+    let name = client.full_name();
+    let account = client.account();
+    let stats = client.stats.clone();
 
-    let room = if client.room.is_some() {
-        Some(Arc::clone(&client.room.as_ref().unwrap()))
-    } else {
-        None
-    };
+    let room = client.room.clone();
     let server = Arc::clone(&client.server);
     drop(client);
 
+    // save player profile
+    if let Some(account) = account {
+        let (account_id, full_name) = (account.id, account.full_name());
+        if let Err(e) = storage::run(&server, move |s| s.save_account(&account)).await {
+            log::error!("failed to save [{}]: {}", full_name, e);
+        }
+        if let Err(e) = storage::run(&server, move |s| s.save_stats(account_id, &stats)).await {
+            log::error!("failed to save the stats of [{}]: {}", full_name, e);
+        }
+    }
+
     // remove client from room
-    if room.is_some() {
-        let r = room.unwrap();
-        let mut r = r.lock().await;
+    if let Some(room) = room {
+        let mut r = room.lock().await;
         r.remove_client(client_id).await;
+        drop(r);
+
+        if let Err(e) = minigame::emit(&room, "eventPlayerLeft", vec![name.into()]).await {
+            log::error!(
+                "failed to run the module of [{}]: {}",
+                room.lock().await.name,
+                e
+            );
+        }
     }
 
     // remove client from server
     let s = server.lock().await;
     s.remove_player(Arc::clone(&player)).await;
+    drop(s);
+
+    // tell friends
+    if let Err(e) = tribulle::disconnect(Arc::clone(&player), server).await {
+        log::error!("failed to notify friends: {}", e);
+    }
 
     // close tcp connection
     let mut client = player.lock().await;
@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{cmp::Reverse, sync::Arc};
+
+use bitmice_database::Stats;
+use rand::seq::SliceRandom;
+use tokio::sync::Mutex;
+
+use crate::{
+    room::{MapType, RoomType},
+    Room,
+};
+
+/// Perms of the maps that change the shamans of the round.
+pub const NO_SHAMAN_PERMA: i8 = 7;
+pub const DUAL_SHAMAN_PERMA: i8 = 8;
+pub const HARD_MODE_PERMA: i8 = 32;
+pub const DIVINE_MODE_PERMA: i8 = 34;
+
+/// Codes of the objects restricted by the harder modes.
+const ARROW: i16 = 0;
+const SPIRIT: i16 = 24;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ShamanMode {
+    #[default]
+    Normal,
+    Hard,
+    Divine,
+}
+
+impl ShamanMode {
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Normal),
+            1 => Some(Self::Hard),
+            2 => Some(Self::Divine),
+            _ => None,
+        }
+    }
+
+    /// Mode forced by the perm of a map, the preference of the shaman is used
+    /// on the other maps.
+    pub fn forced_by(perma: i8) -> Option<Self> {
+        match perma {
+            HARD_MODE_PERMA => Some(Self::Hard),
+            DIVINE_MODE_PERMA => Some(Self::Divine),
+            _ => None,
+        }
+    }
+
+    /// Objects a shaman on this mode can't summon.
+    pub fn forbidden_objects(self) -> &'static [i16] {
+        match self {
+            Self::Normal => &[],
+            Self::Hard => &[ARROW],
+            Self::Divine => &[ARROW, SPIRIT],
+        }
+    }
+
+    /// Shaman experience earned by a save.
+    pub fn skill_multiplier(self) -> u32 {
+        match self {
+            Self::Normal => 1,
+            Self::Hard => 2,
+            Self::Divine => 3,
+        }
+    }
+
+    fn count_save(self, stats: &mut Stats) {
+        match self {
+            Self::Normal => stats.saves += 1,
+            Self::Hard => stats.hard_saves += 1,
+            Self::Divine => stats.divine_saves += 1,
+        }
+        stats.shaman_experience += self.skill_multiplier();
+    }
+}
+
+/// A shaman of the round, on dual shaman maps the first one is the blue and
+/// the second one is the pink.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shaman {
+    pub id: u32,
+    pub mode: ShamanMode,
+}
+
+/// Whether the rounds of a room are played with a shaman.
+pub fn has_shaman(room: &Room) -> bool {
+    matches!(room.room_type, RoomType::Vanilla | RoomType::Survivor)
+        && room.map_perma != NO_SHAMAN_PERMA
+        && matches!(
+            room.map_type,
+            MapType::Vanilla | MapType::Custom | MapType::Perm
+        )
+}
+
+/// Picks the shamans of a new round, the mice with the highest scores, which
+/// start over from zero. Ties are broken randomly.
+pub async fn select(room: &Arc<Mutex<Room>>) {
+    let mut r = room.lock().await;
+    r.shamans.clear();
+    if !has_shaman(&r) {
+        return;
+    }
+    let mut players = r.players();
+    let count = if r.map_perma == DUAL_SHAMAN_PERMA {
+        2
+    } else {
+        1
+    };
+    let forced_mode = ShamanMode::forced_by(r.map_perma);
+    drop(r);
+
+    players.shuffle(&mut rand::thread_rng());
+    let mut scores = Vec::new();
+    for player in players {
+        let score = player.lock().await.score;
+        scores.push((player, score));
+    }
+    // the sort is stable, the ties keep their random order
+    scores.sort_by_key(|(_, score)| Reverse(*score));
+
+    let mut shamans = Vec::new();
+    for (player, _) in scores.into_iter().take(count) {
+        let mut p = player.lock().await;
+        p.is_shaman = true;
+        p.score = 0;
+        shamans.push(Shaman {
+            id: p.id,
+            mode: forced_mode.unwrap_or(p.shaman_mode),
+        });
+    }
+
+    room.lock().await.shamans = shamans;
+}
+
+/// Counts a save for the shamans that helped a mouse to enter the hole, they
+/// must be alive and have summoned something on the round. On dual shaman
+/// maps the blue hole saves for the blue shaman and the pink for the pink.
+pub async fn count_save(room: &Arc<Mutex<Room>>, client_id: u32, hole_type: u8) {
+    let r = room.lock().await;
+    let mut shamans = r.shamans.clone();
+    let players = r.players();
+    drop(r);
+
+    if shamans.iter().any(|s| s.id == client_id) {
+        return;
+    }
+    if shamans.len() > 1 && (hole_type == 1 || hole_type == 2) {
+        shamans = vec![shamans[hole_type as usize - 1]];
+    }
+
+    for player in players {
+        let mut p = player.lock().await;
+        let shaman = match shamans.iter().find(|s| s.id == p.id) {
+            Some(s) => *s,
+            None => continue,
+        };
+
+        if !p.is_dead && p.has_summoned {
+            shaman.mode.count_save(&mut p.stats);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitmice_database::Stats;
+
+    use super::{ShamanMode, DIVINE_MODE_PERMA, HARD_MODE_PERMA};
+
+    #[test]
+    fn modes() {
+        for mode in [ShamanMode::Normal, ShamanMode::Hard, ShamanMode::Divine] {
+            assert_eq!(ShamanMode::from_id(mode.id()), Some(mode));
+        }
+        assert_eq!(ShamanMode::from_id(3), None);
+
+        assert_eq!(
+            ShamanMode::forced_by(HARD_MODE_PERMA),
+            Some(ShamanMode::Hard)
+        );
+        assert_eq!(
+            ShamanMode::forced_by(DIVINE_MODE_PERMA),
+            Some(ShamanMode::Divine)
+        );
+        assert_eq!(ShamanMode::forced_by(0), None);
+
+        assert!(ShamanMode::Normal.forbidden_objects().is_empty());
+        assert!(
+            ShamanMode::Divine.forbidden_objects().len()
+                > ShamanMode::Hard.forbidden_objects().len()
+        );
+    }
+
+    #[test]
+    fn count_saves() {
+        let mut stats = Stats::default();
+
+        ShamanMode::Normal.count_save(&mut stats);
+        ShamanMode::Hard.count_save(&mut stats);
+        ShamanMode::Divine.count_save(&mut stats);
+        assert_eq!(
+            (stats.saves, stats.hard_saves, stats.divine_saves),
+            (1, 1, 1)
+        );
+        assert_eq!(stats.shaman_experience, 6);
+    }
+}
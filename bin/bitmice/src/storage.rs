@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use bitmice_database::Storage;
+use tokio::sync::Mutex;
+
+use crate::Server;
+
+/// Runs `f` on the storage of the server away from the async workers, the
+/// database calls block.
+pub async fn run<T, F>(server: &Arc<Mutex<Server>>, f: F) -> bitmice_database::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&dyn Storage) -> bitmice_database::Result<T> + Send + 'static,
+{
+    let storage = Arc::clone(&server.lock().await.storage);
+
+    match tokio::task::spawn_blocking(move || f(storage.as_ref())).await {
+        Ok(result) => result,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::{
+    room::{self, RoomType},
+    Room,
+};
+
+/// Perms of the survivor maps.
+pub const SURVIVOR_PERMA: i8 = 10;
+pub const VAMPIRE_SURVIVOR_PERMA: i8 = 11;
+
+/// Points earned by the mice alive at the end of the round.
+pub const SURVIVE_POINTS: u16 = 10;
+
+/// Code of the cannon, its skins are `1701`, `1702` and so on.
+const CANNON: i16 = 17;
+
+/// Whether an object is a cannon, the only one a survivor shaman can shoot.
+pub fn is_cannon(code: i16) -> bool {
+    code == CANNON || code / 100 == CANNON
+}
+
+/// Mice alive on the round, not counting the shamans. The shamans are only
+/// counted while they play alone.
+pub async fn alive_mice(room: &Room) -> i16 {
+    let mut mice = 0;
+    let mut alive = 0;
+
+    for player in room.players() {
+        let p = player.lock().await;
+        if p.is_shaman {
+            continue;
+        }
+
+        mice += 1;
+        if !p.is_dead {
+            alive += 1;
+        }
+    }
+
+    if mice == 0 {
+        return room.alive().await;
+    }
+
+    alive
+}
+
+/// Milliseconds a cannon can take to reach a mouse, deaths after that aren't
+/// kills of the shaman.
+const KILL_WINDOW: u128 = 3000;
+
+/// Whether a death at `now` was caused by a cannon summoned at `last_summon`.
+fn is_kill(last_summon: u128, now: u128) -> bool {
+    last_summon != 0 && last_summon <= now && now - last_summon <= KILL_WINDOW
+}
+
+/// Counts a kill for the shamans of a survivor room that shot a cannon
+/// right before the death of a mouse at `now`.
+pub async fn count_kill(room: &Arc<Mutex<Room>>, client_id: u32, now: u128) {
+    let r = room.lock().await;
+    if r.room_type != RoomType::Survivor {
+        return;
+    }
+    let players = r.players();
+    drop(r);
+    if players.len() < room::MIN_PLAYERS_FOR_STATS {
+        return;
+    }
+
+    for player in players {
+        let mut p = player.lock().await;
+        if p.is_shaman && p.id != client_id && is_kill(p.last_summon, now) {
+            p.stats.survivor_kills += 1;
+        }
+    }
+}
+
+/// Rewards the mice that survived the round of a survivor room, must be
+/// called before the players are reset for the next round.
+pub async fn end_round(room: &Arc<Mutex<Room>>) {
+    let r = room.lock().await;
+    if r.room_type != RoomType::Survivor {
+        return;
+    }
+    let players = r.players();
+    drop(r);
+    let counts_stats = players.len() >= room::MIN_PLAYERS_FOR_STATS;
+
+    for player in players {
+        let mut p = player.lock().await;
+        if p.is_shaman {
+            continue;
+        }
+
+        if counts_stats {
+            p.stats.survivor_rounds += 1;
+        }
+        if !p.is_dead {
+            p.score = p.score.saturating_add(SURVIVE_POINTS);
+            if counts_stats {
+                p.stats.survivor_survived += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_cannon, is_kill};
+
+    #[test]
+    fn cannons() {
+        assert!(is_cannon(17));
+        assert!(is_cannon(1701));
+        assert!(!is_cannon(0));
+        assert!(!is_cannon(24));
+        assert!(!is_cannon(170));
+    }
+
+    #[test]
+    fn kills() {
+        assert!(is_kill(10_000, 10_500));
+        assert!(is_kill(10_000, 13_000));
+        assert!(!is_kill(10_000, 13_001));
+        // nothing was shot on the round
+        assert!(!is_kill(0, 1_000));
+    }
+}
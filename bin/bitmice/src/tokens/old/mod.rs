@@ -1,4 +1,5 @@
 // SPDX-License-Identifier: BSD-3-Clause
 // Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
 
+pub mod recv;
 pub mod send;
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use crate::{
+    chat, minigame,
+    packets::{recv, send},
+    Client, Result, Server,
+};
+use bitmice_utils::language_id;
+use tokio::sync::Mutex;
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::RoomMessage,
+    _packet_id: u8,
+) -> Result {
+    let config = server.lock().await.config.chat.clone();
+
+    let message = packet
+        .message
+        .trim()
+        .chars()
+        .take(config.max_length)
+        .collect::<String>();
+    if message.is_empty() {
+        return Ok(());
+    }
+
+    let mut c = client.lock().await;
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+
+    if !c.can_chat(&config).await? {
+        return Ok(());
+    }
+
+    let name = c.full_name();
+    let community = language_id(&c.lang) as u8;
+    drop(c);
+
+    let r = room.lock().await;
+
+    // commands of the minigame running on the room aren't shown
+    if let Some(command) = message.strip_prefix('!') {
+        if r.module.is_some() {
+            drop(r);
+            let args = vec![name.into(), command.to_string().into()];
+
+            return minigame::emit(&room, "eventChatCommand", args).await;
+        }
+    }
+
+    let message = chat::escape(&chat::censor(&message, &config.banned_words));
+    log::debug!("[{}] said: {}", name, message);
+
+    let packet = send::RoomMessage {
+        name,
+        community,
+        message,
+    };
+    r.send_packet(packet).await
+}
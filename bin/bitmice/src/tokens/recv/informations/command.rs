@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use crate::{commands, packets::recv, Client, Result, Server};
+use tokio::sync::Mutex;
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::Command,
+    _packet_id: u8,
+) -> Result {
+    commands::run(client, server, &packet.command).await
+}
@@ -3,23 +3,19 @@
 
 use std::sync::Arc;
 
-use crate::{Client, Result, Server};
-use bitmice_utils::ByteArray;
+use crate::{packets::recv, Client, Result, Server};
 use tokio::sync::Mutex;
 
 pub async fn handle(
     client: Arc<Mutex<Client>>,
     _server: Arc<Mutex<Server>>,
-    mut data: ByteArray,
+    packet: recv::ComputerInfo,
     _packet_id: u8,
 ) -> Result {
-    let lang = data.read_utf();
-    let os = data.read_utf();
-
     let mut client = client.lock().await;
 
-    client.computer_lang = lang;
-    client.computer_os = os;
+    client.computer_lang = packet.lang;
+    client.computer_os = packet.os;
 
     Ok(())
 }
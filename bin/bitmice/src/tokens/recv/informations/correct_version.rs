@@ -3,70 +3,82 @@
 
 use std::sync::Arc;
 
-use crate::{tokens, Client, Result, Server};
-use bitmice_utils::ByteArray;
+use crate::{
+    packets::{recv, send},
+    Client, Result, Server,
+};
 use rand::Rng;
 use tokio::sync::Mutex;
 
 pub async fn handle(
     client: Arc<Mutex<Client>>,
     server: Arc<Mutex<Server>>,
-    mut data: ByteArray,
+    packet: recv::CorrectVersion,
     _packet_id: u8,
 ) -> Result {
-    let version = data.read_u16();
-    let lang = data.read_utf().to_uppercase();
-    let ckey = data.read_utf();
-    let stand = data.read_utf();
+    let recv::CorrectVersion {
+        version,
+        lang,
+        ckey,
+        stand,
+    } = packet;
+    let lang = lang.to_uppercase();
 
     let mut client = client.lock().await;
     let server = server.lock().await;
 
     let ip_address = client.address().to_string();
 
-    log::debug!("new client on [{}] using [{}]", ip_address, stand);
-
-    if version != server.version || ckey != server.ckey {
-        log::debug!(
-            "[{}] disconnected by using incorrect version = [{}] and/or ckey = [{}]",
-            ip_address,
-            version,
-            ckey
-        );
+    // the protocol can't change once the client is validated
+    if client.version_validated {
+        log::debug!("[{}] disconnected by sending its version twice", ip_address);
         client.close().await?;
         return Ok(());
     }
 
+    log::debug!("new client on [{}] using [{}]", ip_address, stand);
+
+    match server.protocols.get(version, &ckey) {
+        Some(protocol) => client.protocol = protocol,
+        None => {
+            log::debug!(
+                "[{}] disconnected by using unsupported version = [{}] and/or ckey = [{}]",
+                ip_address,
+                version,
+                ckey
+            );
+            client.close().await?;
+            return Ok(());
+        }
+    }
+
+    let banner_id = server.config.welcome.banner_id;
+    let login_image = server.config.welcome.login_image.clone();
+    drop(server);
+
     let auth_key = rand::thread_rng().gen_range(0..2147483647);
     client.auth_key = auth_key;
 
     client.version_validated = true;
     client
-        .send_data(
-            tokens::send::CORRECT_VERSION,
-            ByteArray::new()
-                .write_i32(0) // players count
-                .write_utf(&lang) // lang
-                .write_utf(&lang) // lang
-                .write_i32(auth_key) // auth key
-                .write_bool(false),
-        )
+        .send_packet(send::CorrectVersion {
+            players: 0,
+            lang: lang.clone(),
+            lang_2: lang,
+            auth_key,
+            unknown: false,
+        })
         .await?;
     client
-        .send_data(
-            tokens::send::BANNER_LOGIN,
-            ByteArray::new()
-                .write_i8(1)
-                .write_i8(2) // banner id
-                .write_i8(1)
-                .write_bool(false),
-        )
+        .send_packet(send::BannerLogin {
+            unknown: 1,
+            banner_id,
+            unknown_2: 1,
+            unknown_3: false,
+        })
         .await?;
     client
-        .send_data(
-            tokens::send::IMAGE_LOGIN,
-            ByteArray::new().write_utf("x_noel2014.jpg"),
-        )
+        .send_packet(send::ImageLogin { image: login_image })
         .await?;
     /* client
     .send_data(
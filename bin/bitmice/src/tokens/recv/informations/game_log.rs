@@ -3,21 +3,22 @@
 
 use std::sync::Arc;
 
-use crate::{Client, Result, Server};
-use bitmice_utils::ByteArray;
+use crate::{packets::recv, Client, Result, Server};
 use tokio::sync::Mutex;
 
 pub async fn handle(
     client: Arc<Mutex<Client>>,
     _server: Arc<Mutex<Server>>,
-    mut data: ByteArray,
+    packet: recv::GameLog,
     _packet_id: u8,
 ) -> Result {
-    let c = data.read_u8();
-    let cc = data.read_u8();
-    let old_c = data.read_u8();
-    let old_cc = data.read_u8();
-    let error = data.read_utf();
+    let recv::GameLog {
+        c,
+        cc,
+        old_c,
+        old_cc,
+        error,
+    } = packet;
 
     let client = client.lock().await;
 
@@ -1,23 +1,25 @@
 // SPDX-License-Identifier: BSD-3-Clause
 // Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
 
-use std::sync::Arc;
+use std::{sync::Arc, time::UNIX_EPOCH};
 
-use crate::{Client, Result, Server};
-use bitmice_utils::ByteArray;
+use crate::{packets::recv, Client, Result, Server};
 use tokio::sync::Mutex;
 
 pub async fn handle(
     client: Arc<Mutex<Client>>,
     _server: Arc<Mutex<Server>>,
-    mut data: ByteArray,
+    packet: recv::PlayerPing,
     _packet_id: u8,
 ) -> Result {
-    let ping_count = data.read_u8();
-
     let mut client = client.lock().await;
 
-    client.ping.0 = ping_count;
+    client.ping.0 = packet.ping_count;
+    client.latency = UNIX_EPOCH
+        .elapsed()
+        .unwrap()
+        .as_millis()
+        .saturating_sub(client.ping.1);
 
     Ok(())
 }
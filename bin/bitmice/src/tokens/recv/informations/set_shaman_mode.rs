@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use crate::{packets::recv, shaman::ShamanMode, Client, Result, Server};
+use tokio::sync::Mutex;
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    packet: recv::SetShamanMode,
+    _packet_id: u8,
+) -> Result {
+    // the mode of the current round doesn't change, only the next ones
+    if let Some(mode) = ShamanMode::from_id(packet.mode) {
+        client.lock().await.shaman_mode = mode;
+    }
+
+    Ok(())
+}
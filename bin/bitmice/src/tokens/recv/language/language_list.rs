@@ -3,35 +3,38 @@
 
 use std::sync::Arc;
 
-use crate::{tokens, Client, Result, Server};
-use bitmice_utils::{language_info, language_list, ByteArray};
+use crate::{
+    packets::{recv, send},
+    Client, Result, Server,
+};
+use bitmice_utils::{language_info, language_list};
 use tokio::sync::Mutex;
 
 pub async fn handle(
     client: Arc<Mutex<Client>>,
     _server: Arc<Mutex<Server>>,
-    _data: ByteArray,
+    _packet: recv::LanguageList,
     _packet_id: u8,
 ) -> Result {
     let mut client = client.lock().await;
 
     let langs = language_list();
 
-    let mut b = ByteArray::new()
-        .write_i16(langs.len() as i16) // language count
-        .write_utf(&client.lang) // current language code
-        .write_utf(language_info(&client.lang).0) // current language name
-        .write_utf(language_info(&client.lang).1); // current language code
+    let info = |lang: &str| send::LanguageInfo {
+        lang: lang.to_string(),
+        name: language_info(lang).0.to_string(),
+        code: language_info(lang).1.to_string(),
+    };
 
+    // current language first
+    let mut languages = vec![info(&client.lang)];
     for lang in langs {
         if lang != client.lang {
-            let info = language_info(lang);
-
-            b = b.write_utf(lang).write_utf(info.0).write_utf(info.1);
+            languages.push(info(lang));
         }
     }
 
-    client.send_data(tokens::send::LANGUAGE_LIST, b).await?;
+    client.send_packet(send::LanguageList { languages }).await?;
 
     Ok(())
 }
@@ -3,17 +3,20 @@
 
 use std::sync::Arc;
 
-use crate::{tokens, Client, Result, Server};
-use bitmice_utils::{language_code, language_info, ByteArray};
+use crate::{
+    packets::{recv, send},
+    Client, Result, Server,
+};
+use bitmice_utils::{language_code, language_info};
 use tokio::sync::Mutex;
 
 pub async fn handle(
     client: Arc<Mutex<Client>>,
     _server: Arc<Mutex<Server>>,
-    mut data: ByteArray,
+    packet: recv::SetLanguage,
     _packet_id: u8,
 ) -> Result {
-    let langue = data.read_utf().to_lowercase();
+    let langue = packet.langue.to_lowercase();
     let lang = if langue.len() == 2 {
         language_info(&langue).1
     } else {
@@ -23,16 +26,14 @@ pub async fn handle(
     let mut client = client.lock().await;
 
     client
-        .send_data(
-            tokens::send::SET_LANGUAGE,
-            ByteArray::new()
-                .write_utf(&lang)
-                .write_utf(language_info(&lang).1)
-                .write_i16(0)
-                .write_bool(false)
-                .write_bool(true)
-                .write_utf(""),
-        )
+        .send_packet(send::SetLanguage {
+            lang: lang.to_string(),
+            code: language_info(lang).1.to_string(),
+            unknown: 0,
+            unknown_2: false,
+            unknown_3: true,
+            unknown_4: String::new(),
+        })
         .await?;
 
     Ok(())
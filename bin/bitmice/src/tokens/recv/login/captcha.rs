@@ -3,21 +3,27 @@
 
 use std::sync::Arc;
 
-use crate::{tokens, Client, Result, Server};
-use bitmice_utils::{encode_zlib, generate_captcha, generate_captcha_image, ByteArray};
+use crate::{
+    packets::{recv, send},
+    Client, Result, Server,
+};
+use bitmice_utils::{
+    encode_zlib, generate_captcha, generate_captcha_image, packet::Blob, ByteArray,
+};
 use rand::Rng;
 use tokio::sync::Mutex;
 
 pub async fn handle(
     client: Arc<Mutex<Client>>,
     _server: Arc<Mutex<Server>>,
-    _data: ByteArray,
+    _packet: recv::Captcha,
     _packet_id: u8,
 ) -> Result {
     let mut client = client.lock().await;
 
     let code = generate_captcha(rand::thread_rng().gen_range(3..6));
     let (captcha, width, height) = generate_captcha_image(&code);
+    client.captcha_code = code;
     let mut p = ByteArray::new()
         .write_u8(0)
         .write_u16(width as u16)
@@ -34,12 +40,9 @@ pub async fn handle(
     let encoded_captcha = encode_zlib(p.to_string()).unwrap();
 
     client
-        .send_data(
-            tokens::send::CAPTCHA,
-            ByteArray::new()
-                .write_u32(encoded_captcha.len() as u32)
-                .write_bytes(encoded_captcha),
-        )
+        .send_packet(send::Captcha {
+            image: Blob(encoded_captcha),
+        })
         .await?;
 
     Ok(())
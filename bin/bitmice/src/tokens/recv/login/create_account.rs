@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{collections::HashSet, sync::Arc};
+
+use crate::{
+    packets::{recv, send},
+    storage, Client, Result, Server,
+};
+use bitmice_database::{Account, DatabaseError};
+use rand::seq::SliceRandom;
+use tokio::sync::Mutex;
+
+use super::login;
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::CreateAccount,
+    _packet_id: u8,
+) -> Result {
+    let recv::CreateAccount {
+        name,
+        password,
+        email,
+        captcha,
+        ..
+    } = packet;
+
+    let mut c = client.lock().await;
+
+    // already logged
+    if c.id != 0 {
+        return Ok(());
+    }
+
+    // a captcha can only be tried once, even when the name is refused
+    let captcha_code = std::mem::take(&mut c.captcha_code);
+    if captcha_code.is_empty() || !captcha.eq_ignore_ascii_case(&captcha_code) {
+        // incorrect captcha
+        return send_result(&mut c, 7, &name).await;
+    }
+
+    if !is_valid_name(&name) {
+        // invalid name
+        return send_result(&mut c, 5, &name).await;
+    }
+
+    if password.is_empty() {
+        // incorrect password
+        return send_result(&mut c, 2, &name).await;
+    }
+    drop(c);
+
+    // hashing the password takes a while, it runs away from the workers
+    let account_name = name.clone();
+    let account = storage::run(&server, move |storage| {
+        let tags = storage.account_tags(&account_name)?;
+        let tag = match free_tag(&tags) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        let account = Account {
+            email,
+            ..Account::new(&account_name, &tag)
+        };
+        match storage.create_account(account, &password) {
+            Ok(account) => Ok(Some(account)),
+            // the tag was taken by another client meanwhile
+            Err(DatabaseError::AlreadyExists(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    })
+    .await?;
+
+    let mut c = client.lock().await;
+    let account = match account {
+        Some(a) => a,
+        None => {
+            // name already taken
+            return send_result(&mut c, 3, &name).await;
+        }
+    };
+    log::info!("new account [{}] created", account.full_name());
+
+    c.load_account(account);
+    drop(c);
+
+    login::enter_game(client, server, "").await
+}
+
+async fn send_result(client: &mut Client, code: i8, name: &str) -> Result {
+    client
+        .send_packet(send::LoginResult {
+            code,
+            name: name.to_string(),
+            unknown: String::new(),
+        })
+        .await
+}
+
+/// Names must have 3-12 characters, start with a letter and only contain
+/// letters, digits and single underscores that aren't at the end.
+fn is_valid_name(name: &str) -> bool {
+    let length = name.chars().count();
+    if !(3..=12).contains(&length) {
+        return false;
+    }
+
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) || name.ends_with('_') {
+        return false;
+    }
+
+    name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') && !name.contains("__")
+}
+
+fn free_tag(used_tags: &[String]) -> Option<String> {
+    let used_tags = used_tags.iter().collect::<HashSet<&String>>();
+    let free_tags = (1..10000)
+        .map(|t| format!("{:04}", t))
+        .filter(|t| !used_tags.contains(t))
+        .collect::<Vec<String>>();
+
+    free_tags.choose(&mut rand::thread_rng()).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{free_tag, is_valid_name};
+
+    #[test]
+    fn validate_names() {
+        assert!(is_valid_name("Andriel"));
+        assert!(is_valid_name("And_riel2"));
+
+        assert!(!is_valid_name("An"));
+        assert!(!is_valid_name("Andriel_Andriel"));
+        assert!(!is_valid_name("2Andriel"));
+        assert!(!is_valid_name("Andriel_"));
+        assert!(!is_valid_name("And__riel"));
+        assert!(!is_valid_name("*Andriel"));
+        assert!(!is_valid_name("Andriél"));
+    }
+
+    #[test]
+    fn assign_free_tag() {
+        let used_tags = (1..9999).map(|t| format!("{:04}", t)).collect::<Vec<_>>();
+        assert_eq!(free_tag(&used_tags), Some(String::from("9999")));
+
+        let used_tags = (1..10000).map(|t| format!("{:04}", t)).collect::<Vec<_>>();
+        assert_eq!(free_tag(&used_tags), None);
+    }
+}
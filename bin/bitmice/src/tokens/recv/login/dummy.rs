@@ -3,23 +3,22 @@
 
 use std::{sync::Arc, time::UNIX_EPOCH};
 
-use crate::{Client, Result, Server};
-use bitmice_utils::ByteArray;
+use crate::{packets::recv, Client, Result, Server};
 use tokio::sync::Mutex;
 
 pub async fn handle(
     client: Arc<Mutex<Client>>,
-    _server: Arc<Mutex<Server>>,
-    _data: ByteArray,
+    server: Arc<Mutex<Server>>,
+    _packet: recv::Dummy,
     _packet_id: u8,
 ) -> Result {
+    let idle_timeout = server.lock().await.config.timeouts.idle as u128 * 1000;
     let mut client = client.lock().await;
 
     let last_response = client.last_response;
     let now = UNIX_EPOCH.elapsed().unwrap().as_millis();
 
-    if now - last_response >= 900000 {
-        // 15min
+    if now - last_response >= idle_timeout {
         client.close().await?;
     }
 
@@ -3,59 +3,50 @@
 
 use std::{collections::HashMap, sync::Arc, time::UNIX_EPOCH};
 
-use crate::{room, tokens, Client, Result, Server};
-use bitmice_utils::{bytes_to_string, language_id, ByteArray};
+use crate::{
+    packets::{recv, send},
+    room, storage, tokens, tribe, tribulle, Client, Result, Server,
+};
+use bitmice_utils::{language_id, packet::ByteList, ByteArray};
 use tokio::sync::Mutex;
 
 pub async fn handle(
     client: Arc<Mutex<Client>>,
     server: Arc<Mutex<Server>>,
-    mut data: ByteArray,
+    packet: recv::Login,
     _packet_id: u8,
 ) -> Result {
-    log::error!("{:?}", bytes_to_string(data.as_bytes()));
-    let mut identity = data.read_utf();
-    let password = data.read_utf();
-    let _url = data.read_utf();
-    let mut start_room = data.read_utf();
-    let mut auth_key = data.read_u32();
+    let recv::Login {
+        mut identity,
+        password,
+        mut start_room,
+        auth_key,
+        ..
+    } = packet;
 
-    let mut s = server.lock().await;
-
-    for key in &s.login_keys {
-        auth_key ^= key;
-    }
-    if auth_key != s.auth_key {
-        let mut c = client.lock().await;
+    let mut c = client.lock().await;
+    if !c.protocol.is_valid_auth_key(auth_key) {
         c.close().await?;
         return Ok(());
     }
+    drop(c);
+
+    let s = server.lock().await;
+    let is_connected = s.get_player(identity.clone()).await.is_some();
+    drop(s);
 
     if identity.is_empty() || identity.len() < 3 {
         let mut c = client.lock().await;
         c // invalid account
-            .send_data(
-                tokens::send::LOGIN_RESULT,
-                ByteArray::new()
-                    .write_i8(2)
-                    .write_utf(&identity)
-                    .write_utf(&password),
-            )
-            .await?;
-        return Ok(());
-    } else if s.get_player(identity.clone()).await.is_some() {
-        let mut c = client.lock().await;
-        c // already connected
-            .send_data(
-                tokens::send::LOGIN_RESULT,
-                ByteArray::new()
-                    .write_i8(1)
-                    .write_utf(&identity)
-                    .write_utf(&password),
-            )
+            .send_packet(send::LoginResult {
+                code: 2,
+                name: identity,
+                unknown: password,
+            })
             .await?;
-
         return Ok(());
+    } else if is_connected {
+        return already_connected(&client, identity, password).await;
     } else if password.is_empty() {
         if !identity.starts_with("+") {
             identity = format!("*{}", identity);
@@ -63,15 +54,92 @@ pub async fn handle(
 
         start_room = format!("\x03[Tutorial] {}", identity);
 
+        // checked again with the server locked, two logins can't both pass
+        let s = server.lock().await;
+        if s.get_player(identity.clone()).await.is_some() {
+            drop(s);
+            return already_connected(&client, identity, password).await;
+        }
+
         let mut c = client.lock().await;
+        c.name = identity;
         c.priv_level = 0;
         c.time_played = UNIX_EPOCH.elapsed().unwrap().as_millis() as u64;
         c.is_guest = true;
+        drop(s);
+    } else {
+        // hashing the password takes a while, it runs away from the workers
+        let name = identity.clone();
+        let found = storage::run(&server, move |storage| {
+            let (name, tag) = match name.split_once('#') {
+                Some((name, tag)) => (name, Some(tag)),
+                None => (name.as_str(), None),
+            };
+
+            match storage.login(name, tag, &password)? {
+                Some(account) => {
+                    let stats = storage.stats(account.id)?;
+                    Ok(Some((account, stats)))
+                }
+                None => Ok(None),
+            }
+        })
+        .await?;
+
+        match found {
+            Some((account, stats)) => {
+                // the account may have logged in while the password was checked
+                let s = server.lock().await;
+                let full_name = format!("{}#{}", account.name, account.tag);
+                if s.get_player(full_name).await.is_some() {
+                    drop(s);
+                    return already_connected(&client, identity, String::new()).await;
+                }
+
+                let mut c = client.lock().await;
+                c.load_account(account);
+                c.stats = stats;
+            }
+            None => {
+                let mut c = client.lock().await;
+                c // incorrect name or password
+                    .send_packet(send::LoginResult {
+                        code: 2,
+                        name: identity,
+                        unknown: String::new(),
+                    })
+                    .await?;
+                return Ok(());
+            }
+        }
     }
 
+    enter_game(client, server, &start_room).await
+}
+
+async fn already_connected(
+    client: &Arc<Mutex<Client>>,
+    identity: String,
+    password: String,
+) -> Result {
     let mut c = client.lock().await;
-    c.name = identity;
+    c.send_packet(send::LoginResult {
+        code: 1,
+        name: identity,
+        unknown: password,
+    })
+    .await
+}
 
+/// Sends the identification data of an authenticated client and puts it
+/// on its first room.
+pub(super) async fn enter_game(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    start_room: &str,
+) -> Result {
+    let mut s = server.lock().await;
+    let mut c = client.lock().await;
     c.id = s.new_player_id();
     drop(c);
     drop(s);
@@ -81,10 +149,12 @@ pub async fn handle(
 
     // enter room
     let mut c = client.lock().await;
-    c.enter_room(&start_room).await?;
+    c.enter_room(start_room).await?;
     drop(c);
 
     add_to_room(Arc::clone(&client)).await?;
+    tribe::connect(Arc::clone(&client), Arc::clone(&server)).await?;
+    tribulle::connect(Arc::clone(&client), server).await?;
 
     // send anchors
     let mut c = client.lock().await;
@@ -97,7 +167,6 @@ pub async fn handle(
 async fn identification(client: Arc<Mutex<Client>>) -> Result {
     let mut client = client.lock().await;
 
-    let mut p = ByteArray::new();
     let mut perms = Vec::new();
     let priv_authorization = HashMap::from([
         (0, -1),
@@ -127,25 +196,19 @@ async fn identification(client: Arc<Mutex<Client>>) -> Result {
         perms.push(10);
     }
 
-    for perm in perms.iter() {
-        p = p.write_i8(*perm)
-    }
-
-    let data = ByteArray::new()
-        .write_u32(client.id)
-        .write_utf(&client.name)
-        .write_u32(client.time_played as u32)
-        .write_i8(language_id(&client.lang))
-        .write_u32(client.id)
-        .write_bool(true)
-        .write_u8(perms.len() as u8)
-        .write_bytes(p)
-        .write_bool(client.priv_level >= 9)
-        .write_u16(255)
-        .write_u16(0);
-    client
-        .send_data(tokens::send::PLAYER_IDENTIFICATION, data)
-        .await
+    let identification = send::PlayerIdentification {
+        player_id: client.id,
+        name: client.name.clone(),
+        time_played: client.time_played as u32,
+        lang_id: language_id(&client.lang),
+        player_code: client.id,
+        unknown: true,
+        perms: ByteList(perms),
+        is_admin: client.priv_level >= 9,
+        unknown_2: 255,
+        unknown_3: 0,
+    };
+    client.send_packet(identification).await
 }
 
 async fn login(client: Arc<Mutex<Client>>) -> Result {
@@ -168,37 +231,18 @@ async fn login(client: Arc<Mutex<Client>>) -> Result {
 
     // guest login
     if client.is_souris() {
-        client
-            .send_data(
-                tokens::send::LOGIN_SOURIS,
-                ByteArray::new().write_i8(1).write_i8(10),
-            )
-            .await?;
-        client
-            .send_data(
-                tokens::send::LOGIN_SOURIS,
-                ByteArray::new().write_i8(2).write_i8(5),
-            )
-            .await?;
-        client
-            .send_data(
-                tokens::send::LOGIN_SOURIS,
-                ByteArray::new().write_i8(3).write_i8(15),
-            )
-            .await?;
-        client
-            .send_data(
-                tokens::send::LOGIN_SOURIS,
-                ByteArray::new().write_i8(4).write_u8(200),
-            )
-            .await?;
+        for (kind, value) in [(1, 10), (2, 5), (3, 15), (4, 200)] {
+            client
+                .send_packet(send::LoginSouris { kind, value })
+                .await?;
+        }
     }
     Ok(())
 }
 
 async fn add_to_room(client: Arc<Mutex<Client>>) -> Result {
     let c = client.lock().await;
-    let room = Arc::clone(&c.room.as_ref().unwrap());
+    let room = Arc::clone(c.room.as_ref().unwrap());
     let mut r = room.lock().await;
     drop(c);
 
@@ -208,6 +252,8 @@ async fn add_to_room(client: Arc<Mutex<Client>>) -> Result {
 
     if is_new {
         room::trigger(Arc::clone(&room)).await?;
+    } else {
+        crate::client::start_play(client).await?;
     }
 
     Ok(())
@@ -1,33 +1,9 @@
 // SPDX-License-Identifier: BSD-3-Clause
 // Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
 
-mod captcha;
-mod dummy;
-mod login;
-mod undefined;
-
-use std::sync::Arc;
-
-use bitmice_utils::ByteArray;
-use tokio::sync::Mutex;
-
-use crate::{Client, Result, Server};
-
-pub async fn parse_token(
-    client: Arc<Mutex<Client>>,
-    server: Arc<Mutex<Server>>,
-    cc: u8,
-    data: ByteArray,
-    packet_id: u8,
-) -> Result {
-    match cc {
-        8 => login::handle(client, server, data, packet_id).await,
-        20 => captcha::handle(client, server, data, packet_id).await,
-        26 => dummy::handle(client, server, data, packet_id).await,
-        40 => undefined::handle(client, server, data, packet_id).await,
-        _ => {
-            log::debug!("cc = [{}] not identified\ndata = [{:?}]", cc, data);
-            Ok(())
-        }
-    }
-}
+pub(super) mod captcha;
+pub(super) mod create_account;
+pub(super) mod dummy;
+#[allow(clippy::module_inception)]
+pub(super) mod login;
+pub(super) mod undefined;
@@ -0,0 +1,5 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+pub(super) mod popup_answer;
+pub(super) mod text_area_callback;
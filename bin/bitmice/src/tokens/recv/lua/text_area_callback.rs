@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{sync::Arc, time::UNIX_EPOCH};
+
+use crate::{minigame, packets::recv, Client, Result, Server};
+use tokio::sync::Mutex;
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    packet: recv::TextAreaCallback,
+    _packet_id: u8,
+) -> Result {
+    let mut c = client.lock().await;
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+
+    let now = UNIX_EPOCH.elapsed().unwrap().as_millis();
+    if now.saturating_sub(c.last_ui_answer) < minigame::MIN_UI_INTERVAL {
+        return Ok(());
+    }
+    c.last_ui_answer = now;
+
+    let args = vec![
+        (packet.id as i64).into(),
+        c.full_name().into(),
+        packet.callback.into(),
+    ];
+    drop(c);
+
+    minigame::emit(&room, "eventTextAreaCallback", args).await
+}
@@ -1,38 +1,94 @@
 // SPDX-License-Identifier: BSD-3-Clause
 // Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
 
+mod chat;
 mod informations;
 mod language;
 mod login;
+mod lua;
+mod old;
 mod player;
 mod room;
 mod sync;
+mod tribe;
+mod tribulle;
 
 use std::sync::Arc;
 
-use crate::{Client, Result, Server};
-use bitmice_utils::ByteArray;
+use crate::{packets::recv, Client, Result, Server};
+use bitmice_utils::{packet::Packet, ByteArray};
 use tokio::sync::Mutex;
 
+/// Decodes the packet bound to `tokens` and hands it to its handler.
+macro_rules! route {
+    ($client:ident, $server:ident, $tokens:ident, $data:ident, $packet_id:ident {
+        $($packet:ty => $handler:path,)*
+    }) => {
+        match $tokens {
+            $(
+                <$packet as Packet>::TOKENS => {
+                    let packet = <$packet as Packet>::decode(&mut $data)?;
+                    $handler($client, $server, packet, $packet_id).await
+                }
+            )*
+            _ => {
+                log::debug!("tokens {:?} not identified\ndata = [{:?}]", $tokens, $data);
+                Ok(())
+            }
+        }
+    };
+}
+
 pub async fn parse_tokens(
     client: Arc<Mutex<Client>>,
     server: Arc<Mutex<Server>>,
     tokens: (u8, u8),
-    data: ByteArray,
+    mut data: ByteArray,
     packet_id: u8,
 ) -> Result {
-    let (c, cc) = tokens;
-
-    match c {
-        4 => sync::parse_token(client, server, cc, data, packet_id).await,
-        5 => room::parse_token(client, server, cc, data, packet_id).await,
-        8 => player::parse_token(client, server, cc, data, packet_id).await,
-        26 => login::parse_token(client, server, cc, data, packet_id).await,
-        28 => informations::parse_token(client, server, cc, data, packet_id).await,
-        176 => language::parse_token(client, server, cc, data, packet_id).await,
-        _ => {
-            log::debug!("tokens {:?} not identified\ndata = [{:?}]", tokens, data);
-            Ok(())
-        }
-    }
+    route!(client, server, tokens, data, packet_id {
+        recv::Old => old::handle,
+
+        recv::MouseMovement => sync::mouse_movement::handle,
+        recv::Death => sync::death::handle,
+        recv::Crouch => sync::crouch::handle,
+
+        recv::EnterHole => room::enter_hole::handle,
+        recv::GetCheese => room::get_cheese::handle,
+        recv::PlaceObject => room::place_object::handle,
+        recv::DefilantePoints => room::defilante_points::handle,
+        recv::EnterRoom => room::enter_room::handle,
+        recv::SendMusic => room::send_music::handle,
+        recv::MusicPlaylist => room::music_playlist::handle,
+
+        recv::RoomMessage => chat::room_message::handle,
+
+        recv::Langue => player::langue::handle,
+        recv::Ping => player::ping::handle,
+        recv::TalkToNpc => player::talk_to_npc::handle,
+
+        recv::EnterTribeHouse => tribe::enter_tribe_house::handle,
+
+        recv::CreateAccount => login::create_account::handle,
+        recv::Login => login::login::handle,
+        recv::Captcha => login::captcha::handle,
+        recv::Dummy => login::dummy::handle,
+        recv::Undefined => login::undefined::handle,
+
+        recv::CorrectVersion => informations::correct_version::handle,
+        recv::GameLog => informations::game_log::handle,
+        recv::PlayerPing => informations::player_ping::handle,
+        recv::SetShamanMode => informations::set_shaman_mode::handle,
+        recv::ComputerInfo => informations::computer_info::handle,
+        recv::Command => informations::command::handle,
+
+        recv::TextAreaCallback => lua::text_area_callback::handle,
+        recv::PopupAnswer => lua::popup_answer::handle,
+
+        recv::OldTribulle => tribulle::handle_old,
+        recv::Tribulle => tribulle::handle,
+
+        recv::SetLanguage => language::set_language::handle,
+        recv::LanguageList => language::language_list::handle,
+    })
 }
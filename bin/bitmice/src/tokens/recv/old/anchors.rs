@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use crate::{tokens, Client, Result, Server};
+use bitmice_utils::ByteArray;
+use tokio::sync::Mutex;
+
+/// Anchors kept on a room for a round, they are sent to every joining player.
+const MAX_ANCHORS: usize = 50;
+const MAX_ANCHOR_LENGTH: usize = 64;
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    values: Vec<String>,
+) -> Result {
+    let c = client.lock().await;
+    if !c.is_shaman || c.is_dead || values.is_empty() {
+        return Ok(());
+    }
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    drop(c);
+
+    let mut r = room.lock().await;
+    let free = MAX_ANCHORS.saturating_sub(r.anchors.len());
+    let values = values
+        .into_iter()
+        .filter(|v| !v.is_empty() && v.len() <= MAX_ANCHOR_LENGTH)
+        .take(free)
+        .collect::<Vec<String>>();
+    if values.is_empty() {
+        return Ok(());
+    }
+
+    r.anchors.extend(values.iter().cloned());
+    r.send_old_data(
+        tokens::old::send::ANCHORS,
+        ByteArray::with(values.join("\x01").into_bytes()),
+    )
+    .await
+}
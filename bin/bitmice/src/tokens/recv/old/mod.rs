@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+mod anchors;
+
+use std::sync::Arc;
+
+use crate::{packets::recv, tokens::old::recv as old, Client, Result, Server};
+use tokio::sync::Mutex;
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::Old,
+    _packet_id: u8,
+) -> Result {
+    let data = packet.data.as_bytes();
+    if data.len() < 2 {
+        return Ok(());
+    }
+
+    let tokens = (data[0], data[1]);
+    let values = String::from_utf8_lossy(&data[2..])
+        .split('\x01')
+        .filter(|v| !v.is_empty())
+        .map(String::from)
+        .collect::<Vec<String>>();
+
+    match tokens {
+        old::ANCHORS => anchors::handle(client, server, values).await,
+        _ => {
+            log::debug!(
+                "old tokens {:?} not identified\nvalues = {:?}",
+                tokens,
+                values
+            );
+            Ok(())
+        }
+    }
+}
@@ -3,14 +3,13 @@
 
 use std::sync::Arc;
 
-use crate::{Client, Result, Server};
-use bitmice_utils::ByteArray;
+use crate::{packets::recv, Client, Result, Server};
 use tokio::sync::Mutex;
 
 pub async fn handle(
     _client: Arc<Mutex<Client>>,
     _server: Arc<Mutex<Server>>,
-    _data: ByteArray,
+    _packet: recv::Langue,
     _packet_id: u8,
 ) -> Result {
     // let lang_id = data.read_i8();
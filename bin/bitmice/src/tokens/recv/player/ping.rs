@@ -3,14 +3,16 @@
 
 use std::{sync::Arc, time::UNIX_EPOCH};
 
-use crate::{tokens, Client, Result, Server};
-use bitmice_utils::ByteArray;
+use crate::{
+    packets::{recv, send},
+    Client, Result, Server,
+};
 use tokio::sync::Mutex;
 
 pub async fn handle(
     client: Arc<Mutex<Client>>,
     _server: Arc<Mutex<Server>>,
-    _data: ByteArray,
+    _packet: recv::Ping,
     _packet_id: u8,
 ) -> Result {
     let mut client = client.lock().await;
@@ -21,10 +23,11 @@ pub async fn handle(
         client.ping.1 = UNIX_EPOCH.elapsed().unwrap().as_millis();
         client.last_ping = !client.last_ping;
 
-        let b = ByteArray::new()
-            .write_u8(client.ping.0)
-            .write_bool(client.last_ping);
-        client.send_data(tokens::send::PING, b).await?;
+        let ping = send::Ping {
+            ping_count: client.ping.0,
+            last_ping: client.last_ping,
+        };
+        client.send_packet(ping).await?;
     }
 
     Ok(())
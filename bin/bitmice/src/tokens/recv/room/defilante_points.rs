@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{sync::Arc, time::UNIX_EPOCH};
+
+use crate::{defilante, packets::recv, room::RoomType, Client, Result, Server};
+use tokio::sync::Mutex;
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    _packet: recv::DefilantePoints,
+    _packet_id: u8,
+) -> Result {
+    let c = client.lock().await;
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    let is_dead = c.is_dead;
+    drop(c);
+
+    let now = UNIX_EPOCH.elapsed().unwrap().as_millis();
+    let r = room.lock().await;
+    if is_dead || r.room_type != RoomType::Defilante || !defilante::has_started(&r, now) {
+        return Ok(());
+    }
+    drop(r);
+
+    let mut c = client.lock().await;
+    if now.saturating_sub(c.last_defilante_point) < defilante::MIN_POINT_INTERVAL {
+        return Ok(());
+    }
+    c.last_defilante_point = now;
+    c.defilante_points = c.defilante_points.saturating_add(1);
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{sync::Arc, time::UNIX_EPOCH};
+
+use crate::{
+    events, minigame,
+    packets::{recv, send},
+    racing,
+    room::{self, RoomType},
+    shaman, Client, Result, Server,
+};
+use tokio::sync::Mutex;
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::EnterHole,
+    _packet_id: u8,
+) -> Result {
+    let c = client.lock().await;
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    drop(c);
+
+    let last_round_code = room.lock().await.last_round_code as i32;
+    let mut c = client.lock().await;
+    if packet.round_code != last_round_code || !c.has_cheese || c.is_dead {
+        return Ok(());
+    }
+
+    c.has_cheese = false;
+    c.is_dead = true;
+
+    let now = UNIX_EPOCH.elapsed().unwrap().as_millis();
+    let time = (now.saturating_sub(c.start_time) / 10).min(u16::MAX as u128) as u16;
+    drop(c);
+
+    let mut r = room.lock().await;
+    r.finished = r.finished.saturating_add(1);
+    let place = r.finished;
+    let counts_stats = r.players().len() >= room::MIN_PLAYERS_FOR_STATS;
+    let is_dual_shaman = r.shamans.len() > 1;
+    let room_type = r.room_type;
+    let rewards = events::rewards(&r, place);
+    drop(r);
+
+    let mut c = client.lock().await;
+    c.score = c.score.saturating_add(room::place_points(place));
+    if counts_stats {
+        c.stats.cheese += 1;
+        if place == 1 {
+            c.stats.first += 1;
+        }
+        if room_type == RoomType::Bootcamp {
+            c.stats.bootcamp += 1;
+        }
+    }
+    c.send_packet(send::GiveCurrency {
+        kind: send::currency::CHEESE,
+        amount: 1,
+    })
+    .await?;
+    events::give_rewards(&mut c, rewards, counts_stats).await?;
+    let client_id = c.id;
+    let score = c.score;
+    let name = c.full_name();
+    drop(c);
+
+    let r = room.lock().await;
+    r.send_packet(send::PlayerWin {
+        kind: if is_dual_shaman { packet.hole_type } else { 0 },
+        player_id: client_id,
+        score,
+        place,
+        time,
+    })
+    .await?;
+    drop(r);
+
+    shaman::count_save(&room, client_id, packet.hole_type).await;
+
+    if room_type == RoomType::Racing {
+        racing::finish(&client, &server, &room, place, time as u32).await?;
+    }
+
+    // in milliseconds for the scripts
+    minigame::emit(
+        &room,
+        "eventPlayerWon",
+        vec![name.into(), (time as i64 * 10).into()],
+    )
+    .await?;
+
+    Ok(())
+}
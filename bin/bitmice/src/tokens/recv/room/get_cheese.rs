@@ -3,30 +3,32 @@
 
 use std::sync::Arc;
 
-use crate::{Client, Result, Server};
-use bitmice_utils::ByteArray;
+use crate::{client, minigame, packets::recv, Client, Result, Server};
 use tokio::sync::Mutex;
 
 pub async fn handle(
     client: Arc<Mutex<Client>>,
     _server: Arc<Mutex<Server>>,
-    mut data: ByteArray,
+    packet: recv::GetCheese,
     _packet_id: u8,
 ) -> Result {
-    let round_code = data.read_i32();
-    let _cheese_x = data.read_i16();
-    let _cheese_y = data.read_i16();
-    let _distance = data.read_i16();
+    let c = client.lock().await;
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    let name = c.full_name();
+    let has_cheese = c.has_cheese;
+    drop(c);
 
-    let mut client = client.lock().await;
-    let room = client.room.as_ref().unwrap().lock().await;
+    let last_round_code = room.lock().await.last_round_code as i32;
+    if packet.round_code != last_round_code || has_cheese {
+        return Ok(());
+    }
 
-    let last_round_code = room.last_round_code as i32;
-    drop(room);
+    client::get_cheese(client).await?;
 
-    if round_code == last_round_code {
-        client.get_cheese().await?;
-    }
+    minigame::emit(&room, "eventPlayerGetCheese", vec![name.into()]).await?;
 
     Ok(())
 }
@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use crate::{
+    packets::{recv, send},
+    Client, Result, Server,
+};
+use tokio::sync::Mutex;
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    _packet: recv::MusicPlaylist,
+    _packet_id: u8,
+) -> Result {
+    let mut c = client.lock().await;
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+
+    let videos = room
+        .lock()
+        .await
+        .playlist
+        .videos()
+        .map(|v| send::MusicEntry {
+            title: v.id.clone(),
+            by: v.by.clone(),
+        })
+        .collect();
+
+    c.send_packet(send::MusicPlaylist { videos }).await
+}
@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{sync::Arc, time::UNIX_EPOCH};
+
+use crate::{
+    packets::{recv, send},
+    room::RoomType,
+    survivor, Client, Result, Server,
+};
+use tokio::sync::Mutex;
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    packet: recv::PlaceObject,
+    _packet_id: u8,
+) -> Result {
+    let c = client.lock().await;
+    if !c.is_shaman || c.is_dead {
+        return Ok(());
+    }
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    let client_id = c.id;
+    // the room locks its players, it can't be locked with the client
+    drop(c);
+
+    let r = room.lock().await;
+    if packet.round_code != r.last_round_code {
+        return Ok(());
+    }
+    let mode = match r.shamans.iter().find(|s| s.id == client_id) {
+        Some(s) => s.mode,
+        None => return Ok(()),
+    };
+    if mode.forbidden_objects().contains(&packet.code) {
+        return Ok(());
+    }
+    if r.room_type == RoomType::Survivor && !survivor::is_cannon(packet.code) {
+        return Ok(());
+    }
+
+    r.send_packet_except(
+        client_id,
+        send::SpawnObject {
+            object_id: packet.object_id,
+            code: packet.code,
+            position_x: packet.position_x,
+            position_y: packet.position_y,
+            angle: packet.angle,
+            speed_x: packet.speed_x,
+            speed_y: packet.speed_y,
+            is_ghost: packet.is_ghost,
+            is_origin: packet.is_origin,
+            unknown: 0,
+        },
+    )
+    .await?;
+    drop(r);
+
+    let mut c = client.lock().await;
+    c.has_summoned = true;
+    c.last_summon = UNIX_EPOCH.elapsed().unwrap().as_millis();
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{sync::Arc, time::UNIX_EPOCH};
+
+use crate::{
+    commands::{self, Reply},
+    music::{self, QueueError, Video},
+    packets::recv,
+    Client, Result, Server,
+};
+use tokio::sync::Mutex;
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::SendMusic,
+    _packet_id: u8,
+) -> Result {
+    let c = client.lock().await;
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    if c.is_guest || !music::is_music_room(&c.last_room) {
+        return Ok(());
+    }
+    let video = match music::parse_video_id(&packet.video_id) {
+        Some(id) => Video {
+            id,
+            by: c.full_name(),
+            by_id: c.id,
+        },
+        None => {
+            drop(c);
+            return commands::reply(&client, Reply::InvalidVideo).await;
+        }
+    };
+    drop(c);
+
+    let config = server.lock().await.config.music.clone();
+    let now = UNIX_EPOCH.elapsed().unwrap().as_millis();
+    let pushed = room
+        .lock()
+        .await
+        .playlist
+        .push(video, config.max_queue, now);
+
+    match pushed {
+        Ok(true) => music::play(&room, config.max_duration).await,
+        Ok(false) => commands::reply(&client, Reply::VideoQueued).await,
+        Err(QueueError::AlreadyQueued) => commands::reply(&client, Reply::VideoAlreadyQueued).await,
+        Err(QueueError::Full) => commands::reply(&client, Reply::PlaylistFull).await,
+    }
+}
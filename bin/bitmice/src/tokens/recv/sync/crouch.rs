@@ -3,31 +3,35 @@
 
 use std::sync::Arc;
 
-use bitmice_utils::ByteArray;
 use tokio::sync::Mutex;
 
-use crate::{tokens, Client, Result, Server};
+use crate::{
+    packets::{recv, send},
+    Client, Result, Server,
+};
 
 pub async fn handle(
     client: Arc<Mutex<Client>>,
     _server: Arc<Mutex<Server>>,
-    mut data: ByteArray,
+    packet: recv::Crouch,
     _packet_id: u8,
 ) -> Result {
-    let crouch = data.read_i8();
-
     let client = client.lock().await;
-    let room = Arc::clone(&client.room.as_ref().unwrap());
+    let room = match client.room.as_ref() {
+        Some(r) => Arc::clone(r),
+        None => return Ok(()),
+    };
     let r = room.lock().await;
 
-    let b = ByteArray::new()
-        .write_u32(client.id)
-        .write_i8(crouch)
-        .write_u8(0);
+    let crouch = send::Crouch {
+        player_id: client.id,
+        crouch: packet.crouch,
+        unknown: 0,
+    };
 
     drop(client);
 
-    r.send_data(tokens::send::CROUCH, b).await?;
+    r.send_packet(crouch).await?;
 
     Ok(())
 }
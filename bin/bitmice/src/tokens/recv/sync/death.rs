@@ -1,30 +1,36 @@
 // SPDX-License-Identifier: BSD-3-Clause
 // Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
 
-use std::sync::Arc;
+use std::{sync::Arc, time::UNIX_EPOCH};
 
-use crate::{client, Client, Result, Server};
-use bitmice_utils::ByteArray;
+use crate::{client, minigame, packets::recv, survivor, Client, Result, Server};
 use tokio::sync::Mutex;
 
 pub async fn handle(
     client: Arc<Mutex<Client>>,
     _server: Arc<Mutex<Server>>,
-    mut data: ByteArray,
+    packet: recv::Death,
     _packet_id: u8,
 ) -> Result {
-    let round_code = data.read_i32();
-
     let c = client.lock().await;
-    let room = c.room.as_ref().unwrap();
-    let r = room.lock().await;
-
-    let last_round_code = r.last_round_code as i32;
-    drop(r);
+    let room = match c.room.clone() {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    let name = c.full_name();
+    let (client_id, was_dead, is_shaman) = (c.id, c.is_dead, c.is_shaman);
     drop(c);
 
-    if round_code == last_round_code {
+    let last_round_code = room.lock().await.last_round_code as i32;
+
+    if packet.round_code == last_round_code {
         client::die(Arc::clone(&client)).await?;
+        // only the deaths on the map can be caused by a cannon
+        if !was_dead && !is_shaman {
+            let now = UNIX_EPOCH.elapsed().unwrap().as_millis();
+            survivor::count_kill(&room, client_id, now).await;
+        }
+        minigame::emit(&room, "eventPlayerDied", vec![name.into()]).await?;
     }
 
     Ok(())
@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{collections::HashSet, sync::Arc};
+
+use crate::{
+    packets::tribulle::{
+        recv,
+        send::{self, result},
+    },
+    storage,
+    tribulle::{self, MAX_FRIENDS},
+    Client, Result, Server,
+};
+use bitmice_database::Relation;
+use tokio::sync::Mutex;
+
+pub async fn add(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::AddFriend,
+) -> Result {
+    let recv::AddFriend { tribulle_id, name } = packet;
+
+    let c = client.lock().await;
+    let account_id = c.account_id;
+    let friends = c.friends.len();
+    drop(c);
+
+    let target = match storage::run(&server, move |s| tribulle::find_account(s, &name)).await? {
+        Some(a) if account_id != 0 => a,
+        _ => return send_add_result(&client, tribulle_id, result::NOT_FOUND).await,
+    };
+
+    let target_id = target.id;
+    if target_id == account_id {
+        return send_add_result(&client, tribulle_id, result::YOURSELF).await;
+    } else if friends >= MAX_FRIENDS {
+        return send_add_result(&client, tribulle_id, result::LIST_FULL).await;
+    } else if !storage::run(&server, move |s| {
+        s.add_relation(account_id, target_id, Relation::Friend)
+    })
+    .await?
+    {
+        return send_add_result(&client, tribulle_id, result::ALREADY_ADDED).await;
+    }
+
+    // the friend sees our room now if it's mutual
+    let online = tribulle::online_players(&client, &server, |p| p.account_id == target_id).await;
+
+    let mut c = client.lock().await;
+    c.friends.insert(target.id);
+    let info = c.friend_info();
+    drop(c);
+
+    let mut friend = tribulle::offline_info(&target, false);
+    if let Some(player) = online.first() {
+        let mut p = player.lock().await;
+
+        let is_mutual = p.friends.contains(&account_id);
+        friend = p.friend_info();
+        friend.is_mutual = is_mutual;
+        if !is_mutual {
+            friend.room.clear();
+        } else {
+            p.send_tribulle(send::FriendUpdated {
+                friend: send::FriendInfo {
+                    is_mutual: true,
+                    ..info
+                },
+            })
+            .await?;
+        }
+    } else {
+        friend.is_mutual =
+            storage::run(&server, move |s| s.related_by(account_id, Relation::Friend))
+                .await?
+                .contains(&target.id);
+    }
+
+    send_add_result(&client, tribulle_id, result::SUCCESS).await?;
+    client
+        .lock()
+        .await
+        .send_tribulle(send::FriendAdded { friend })
+        .await
+}
+
+pub async fn remove(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::RemoveFriend,
+) -> Result {
+    let recv::RemoveFriend { tribulle_id, name } = packet;
+
+    let account_id = client.lock().await.account_id;
+
+    let removed = storage::run(&server, move |s| match tribulle::find_account(s, &name)? {
+        Some(a) if s.remove_relation(account_id, a.id, Relation::Friend)? => Ok(Some(a)),
+        _ => Ok(None),
+    })
+    .await?;
+    let target = match removed {
+        Some(a) => a,
+        None => {
+            return client
+                .lock()
+                .await
+                .send_tribulle(send::RemoveFriendResult {
+                    tribulle_id,
+                    result: result::NOT_FOUND,
+                })
+                .await;
+        }
+    };
+
+    // our room is hidden again
+    let target_id = target.id;
+    let online = tribulle::online_players(&client, &server, |p| {
+        p.account_id == target_id && p.friends.contains(&account_id)
+    })
+    .await;
+
+    let mut c = client.lock().await;
+    c.friends.remove(&target.id);
+    let mut info = c.friend_info();
+    c.send_tribulle(send::RemoveFriendResult {
+        tribulle_id,
+        result: result::SUCCESS,
+    })
+    .await?;
+    c.send_tribulle(send::FriendRemoved { id: target.id })
+        .await?;
+    drop(c);
+
+    info.room.clear();
+    for player in online {
+        player
+            .lock()
+            .await
+            .send_tribulle(send::FriendUpdated {
+                friend: info.clone(),
+            })
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn open_list(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    _packet: recv::OpenFriendList,
+) -> Result {
+    let account_id = client.lock().await.account_id;
+    if account_id == 0 {
+        return client
+            .lock()
+            .await
+            .send_tribulle(send::FriendList {
+                friends: Vec::new(),
+            })
+            .await;
+    }
+
+    let (accounts, mutual) = storage::run(&server, move |s| {
+        Ok((
+            s.relations(account_id, Relation::Friend)?,
+            s.related_by(account_id, Relation::Friend)?,
+        ))
+    })
+    .await?;
+    let mutual = mutual.into_iter().collect::<HashSet<u32>>();
+
+    let ids = accounts.iter().map(|a| a.id).collect::<HashSet<u32>>();
+    let online = tribulle::online_players(&client, &server, |p| ids.contains(&p.account_id)).await;
+    let mut online_infos = Vec::new();
+    for player in online {
+        online_infos.push(player.lock().await.friend_info());
+    }
+
+    let friends = accounts
+        .iter()
+        .map(|account| {
+            let is_mutual = mutual.contains(&account.id);
+
+            match online_infos.iter().find(|i| i.id == account.id) {
+                Some(info) => send::FriendInfo {
+                    is_mutual,
+                    room: if is_mutual {
+                        info.room.clone()
+                    } else {
+                        String::new()
+                    },
+                    ..info.clone()
+                },
+                None => tribulle::offline_info(account, is_mutual),
+            }
+        })
+        .collect();
+
+    client
+        .lock()
+        .await
+        .send_tribulle(send::FriendList { friends })
+        .await
+}
+
+pub async fn close_list(
+    _client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    _packet: recv::CloseFriendList,
+) -> Result {
+    Ok(())
+}
+
+async fn send_add_result(client: &Arc<Mutex<Client>>, tribulle_id: i32, result: u8) -> Result {
+    client
+        .lock()
+        .await
+        .send_tribulle(send::AddFriendResult {
+            tribulle_id,
+            result,
+        })
+        .await
+}
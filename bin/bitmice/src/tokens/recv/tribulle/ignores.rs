@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use crate::{
+    packets::tribulle::{
+        recv,
+        send::{self, result},
+    },
+    storage, tribulle, Client, Result, Server,
+};
+use bitmice_database::Relation;
+use tokio::sync::Mutex;
+
+pub async fn add(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::AddIgnore,
+) -> Result {
+    let recv::AddIgnore { tribulle_id, name } = packet;
+
+    let account_id = client.lock().await.account_id;
+
+    let added = storage::run(&server, move |s| match tribulle::find_account(s, &name)? {
+        Some(_) if account_id == 0 => Ok(Err(result::NOT_ALLOWED)),
+        Some(a) if a.id == account_id => Ok(Err(result::YOURSELF)),
+        Some(a) if s.add_relation(account_id, a.id, Relation::Ignore)? => Ok(Ok(a.id)),
+        Some(_) => Ok(Err(result::ALREADY_ADDED)),
+        None => Ok(Err(result::NOT_FOUND)),
+    })
+    .await?;
+    let result = match added {
+        Ok(id) => {
+            client.lock().await.ignored.insert(id);
+            result::SUCCESS
+        }
+        Err(result) => result,
+    };
+
+    client
+        .lock()
+        .await
+        .send_tribulle(send::AddIgnoreResult {
+            tribulle_id,
+            result,
+        })
+        .await
+}
+
+pub async fn remove(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::RemoveIgnore,
+) -> Result {
+    let recv::RemoveIgnore { tribulle_id, name } = packet;
+
+    let account_id = client.lock().await.account_id;
+
+    let removed = storage::run(&server, move |s| match tribulle::find_account(s, &name)? {
+        Some(a) if s.remove_relation(account_id, a.id, Relation::Ignore)? => Ok(Some(a.id)),
+        _ => Ok(None),
+    })
+    .await?;
+    let result = match removed {
+        Some(id) => {
+            client.lock().await.ignored.remove(&id);
+            result::SUCCESS
+        }
+        None => result::NOT_FOUND,
+    };
+
+    client
+        .lock()
+        .await
+        .send_tribulle(send::RemoveIgnoreResult {
+            tribulle_id,
+            result,
+        })
+        .await
+}
+
+pub async fn open_list(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    _packet: recv::OpenIgnoreList,
+) -> Result {
+    let account_id = client.lock().await.account_id;
+
+    let names = match account_id {
+        0 => Vec::new(),
+        _ => storage::run(&server, move |s| s.relations(account_id, Relation::Ignore))
+            .await?
+            .iter()
+            .map(|a| a.full_name())
+            .collect(),
+    };
+
+    client
+        .lock()
+        .await
+        .send_tribulle(send::IgnoreList { names })
+        .await
+}
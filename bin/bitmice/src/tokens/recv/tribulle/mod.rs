@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+mod friends;
+mod ignores;
+mod tribes;
+mod whispers;
+
+use std::sync::Arc;
+
+use crate::{
+    packets::{recv, tribulle::recv as tribulle},
+    Client, Result, Server,
+};
+use bitmice_utils::{packet::Field, ByteArray};
+use tokio::sync::Mutex;
+
+/// Decodes the tribulle packet bound to `code` and hands it to its handler.
+macro_rules! route {
+    ($client:ident, $server:ident, $code:ident, $data:ident {
+        $($packet:ty => $handler:path,)*
+    }) => {
+        match $code {
+            $(
+                <$packet as crate::packets::tribulle::Tribulle>::CODE => {
+                    let packet = <$packet as Field>::read(&mut $data)?;
+                    $handler($client, $server, packet).await
+                }
+            )*
+            _ => {
+                log::debug!("tribulle code {} not identified\ndata = [{:?}]", $code, $data);
+                Ok(())
+            }
+        }
+    };
+}
+
+pub async fn handle(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::Tribulle,
+    _packet_id: u8,
+) -> Result {
+    let recv::Tribulle { code, data } = packet;
+    let mut data = ByteArray::with(data.0);
+
+    route!(client, server, code, data {
+        tribulle::AddFriend => friends::add,
+        tribulle::RemoveFriend => friends::remove,
+        tribulle::OpenFriendList => friends::open_list,
+        tribulle::CloseFriendList => friends::close_list,
+
+        tribulle::AddIgnore => ignores::add,
+        tribulle::RemoveIgnore => ignores::remove,
+        tribulle::OpenIgnoreList => ignores::open_list,
+
+        tribulle::Whisper => whispers::whisper,
+        tribulle::SetWhisperState => whispers::set_state,
+
+        tribulle::TribeMessage => tribes::message,
+        tribulle::InviteToTribe => tribes::invite,
+        tribulle::AnswerTribeInvite => tribes::answer_invite,
+        tribulle::LeaveTribe => tribes::leave,
+        tribulle::CreateTribe => tribes::create,
+        tribulle::SetTribeMessage => tribes::set_message,
+        tribulle::SetTribeHouseMap => tribes::set_house_map,
+        tribulle::KickTribeMember => tribes::kick,
+        tribulle::OpenTribe => tribes::open,
+        tribulle::CloseTribe => tribes::close,
+        tribulle::SetTribeRank => tribes::set_rank,
+    })
+}
+
+pub async fn handle_old(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::OldTribulle,
+    packet_id: u8,
+) -> Result {
+    let recv::OldTribulle { code, data } = packet;
+
+    handle(client, server, recv::Tribulle { code, data }, packet_id).await
+}
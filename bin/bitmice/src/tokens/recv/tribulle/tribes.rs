@@ -0,0 +1,650 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    chat,
+    packets::tribulle::{
+        recv,
+        send::{self, result},
+        Tribulle,
+    },
+    storage, tribe, tribulle, Client, Result, Server,
+};
+use bitmice_database::{DatabaseError, TribePermission, TribeRank};
+use tokio::sync::Mutex;
+
+const MAX_MESSAGE_LENGTH: usize = 255;
+const MAX_HOUSE_MAP_LENGTH: usize = 60000;
+
+/// Result code of a request, or the error that stopped it.
+type Outcome = std::result::Result<u8, Box<dyn std::error::Error + Send + Sync>>;
+
+pub async fn message(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::TribeMessage,
+) -> Result {
+    let config = server.lock().await.config.chat.clone();
+
+    let mut c = client.lock().await;
+    let tribe_id = c.tribe_id;
+    let name = c.full_name();
+    let can_chat = tribe_id != 0 && c.can_chat(&config).await?;
+    drop(c);
+
+    // filtered the same way as the room chat
+    let message = packet
+        .message
+        .trim()
+        .chars()
+        .take(MAX_MESSAGE_LENGTH)
+        .collect::<String>();
+    let message = chat::escape(&chat::censor(&message, &config.banned_words));
+
+    let result = if !can_chat {
+        result::NOT_ALLOWED
+    } else {
+        if !message.is_empty() {
+            tribe::send_to_members(
+                &server,
+                tribe_id,
+                send::TribeMessageReceived { name, message },
+            )
+            .await?;
+        }
+
+        result::SUCCESS
+    };
+
+    respond(
+        &client,
+        send::TribeMessageResult {
+            tribulle_id: packet.tribulle_id,
+            result,
+        },
+    )
+    .await
+}
+
+pub async fn invite(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::InviteToTribe,
+) -> Result {
+    let result = invite_player(&client, &server, &packet.name).await?;
+
+    respond(
+        &client,
+        send::InviteToTribeResult {
+            tribulle_id: packet.tribulle_id,
+            result,
+        },
+    )
+    .await
+}
+
+async fn invite_player(
+    client: &Arc<Mutex<Client>>,
+    server: &Arc<Mutex<Server>>,
+    name: &str,
+) -> Outcome {
+    let c = client.lock().await;
+    if !c.can_in_tribe(TribePermission::Invite) {
+        return Ok(result::NOT_ALLOWED);
+    }
+    let tribe_id = c.tribe_id;
+    let tribe_name = c.tribe_name.clone();
+    let inviter = c.full_name();
+    drop(c);
+
+    let target = tribulle::online_players(client, server, |p| p.has_name(name)).await;
+    let target = match target.first() {
+        Some(t) => t,
+        None => return Ok(result::NOT_FOUND),
+    };
+
+    let mut t = target.lock().await;
+    if t.account_id == 0 {
+        return Ok(result::NOT_ALLOWED);
+    } else if t.tribe_id != 0 {
+        return Ok(result::ALREADY_ADDED);
+    }
+
+    t.tribe_invites.insert(inviter.clone(), tribe_id);
+    t.send_tribulle(send::TribeInvitation {
+        inviter,
+        tribe: tribe_name,
+    })
+    .await?;
+
+    Ok(result::SUCCESS)
+}
+
+pub async fn answer_invite(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::AnswerTribeInvite,
+) -> Result {
+    let result = join(&client, &server, &packet.inviter, packet.accept).await?;
+
+    respond(
+        &client,
+        send::AnswerTribeInviteResult {
+            tribulle_id: packet.tribulle_id,
+            result,
+        },
+    )
+    .await
+}
+
+async fn join(
+    client: &Arc<Mutex<Client>>,
+    server: &Arc<Mutex<Server>>,
+    inviter: &str,
+    accept: bool,
+) -> Outcome {
+    let mut c = client.lock().await;
+    let tribe_id = match c.tribe_invites.remove(inviter) {
+        Some(id) => id,
+        None => return Ok(result::NOT_FOUND),
+    };
+
+    if !accept {
+        return Ok(result::SUCCESS);
+    } else if c.tribe_id != 0 {
+        return Ok(result::ALREADY_ADDED);
+    }
+    let account_id = c.account_id;
+    let name = c.full_name();
+    drop(c);
+
+    let tribe = storage::run(server, move |s| {
+        let tribe = s.get_tribe(tribe_id)?;
+        if let Some(tribe) = &tribe {
+            s.set_tribe_member(tribe.id, account_id, TribeRank::Initiate)?;
+        }
+
+        Ok(tribe)
+    })
+    .await?;
+    let tribe = match tribe {
+        Some(t) => t,
+        None => return Ok(result::NOT_FOUND),
+    };
+
+    let mut c = client.lock().await;
+    c.join_tribe(&tribe, TribeRank::Initiate);
+    c.tribe_invites.clear();
+    drop(c);
+
+    tribe::send_to_members(server, tribe.id, send::TribeMemberJoined { name }).await?;
+
+    Ok(result::SUCCESS)
+}
+
+pub async fn leave(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::LeaveTribe,
+) -> Result {
+    let result = leave_tribe(&client, &server).await?;
+
+    respond(
+        &client,
+        send::LeaveTribeResult {
+            tribulle_id: packet.tribulle_id,
+            result,
+        },
+    )
+    .await
+}
+
+async fn leave_tribe(client: &Arc<Mutex<Client>>, server: &Arc<Mutex<Server>>) -> Outcome {
+    let c = client.lock().await;
+    let tribe_id = c.tribe_id;
+    let rank = c.tribe_rank;
+    let account_id = c.account_id;
+    let name = c.full_name();
+    drop(c);
+
+    if tribe_id == 0 {
+        return Ok(result::NOT_ALLOWED);
+    }
+
+    let has_left = storage::run(server, move |s| {
+        if rank == TribeRank::Leader {
+            // the leadership must be given to someone else first
+            if s.tribe_members(tribe_id)?.len() > 1 {
+                return Ok(false);
+            }
+
+            s.delete_tribe(tribe_id)?;
+        } else {
+            s.remove_tribe_member(account_id)?;
+        }
+
+        Ok(true)
+    })
+    .await?;
+    if !has_left {
+        return Ok(result::NOT_ALLOWED);
+    }
+
+    client.lock().await.leave_tribe();
+    tribe::send_to_members(server, tribe_id, send::TribeMemberLeft { name }).await?;
+
+    Ok(result::SUCCESS)
+}
+
+pub async fn create(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::CreateTribe,
+) -> Result {
+    let result = create_tribe(&client, &server, &packet.name).await?;
+
+    respond(
+        &client,
+        send::CreateTribeResult {
+            tribulle_id: packet.tribulle_id,
+            result,
+        },
+    )
+    .await
+}
+
+async fn create_tribe(
+    client: &Arc<Mutex<Client>>,
+    server: &Arc<Mutex<Server>>,
+    name: &str,
+) -> Outcome {
+    let c = client.lock().await;
+    let account_id = c.account_id;
+    let tribe_id = c.tribe_id;
+    drop(c);
+
+    if account_id == 0 {
+        return Ok(result::NOT_ALLOWED);
+    } else if tribe_id != 0 {
+        return Ok(result::ALREADY_ADDED);
+    } else if !tribe::is_valid_name(name) {
+        return Ok(result::INVALID_NAME);
+    }
+
+    let name = name.to_string();
+    let tribe = match storage::run(server, move |s| s.create_tribe(&name, account_id)).await {
+        Ok(t) => t,
+        Err(DatabaseError::AlreadyExists(_)) => return Ok(result::NAME_TAKEN),
+        Err(e) => return Err(e.into()),
+    };
+    log::info!("new tribe [{}] created", tribe.name);
+
+    client.lock().await.join_tribe(&tribe, TribeRank::Leader);
+
+    Ok(result::SUCCESS)
+}
+
+pub async fn set_message(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::SetTribeMessage,
+) -> Result {
+    let result = change_message(&client, &server, &packet.message).await?;
+
+    respond(
+        &client,
+        send::SetTribeMessageResult {
+            tribulle_id: packet.tribulle_id,
+            result,
+        },
+    )
+    .await
+}
+
+async fn change_message(
+    client: &Arc<Mutex<Client>>,
+    server: &Arc<Mutex<Server>>,
+    message: &str,
+) -> Outcome {
+    let config = server.lock().await.config.chat.clone();
+
+    let mut c = client.lock().await;
+    if !c.can_in_tribe(TribePermission::ChangeMessage) || !c.can_chat(&config).await? {
+        return Ok(result::NOT_ALLOWED);
+    }
+    let tribe_id = c.tribe_id;
+    let name = c.full_name();
+    drop(c);
+
+    let message = message
+        .trim()
+        .chars()
+        .take(MAX_MESSAGE_LENGTH)
+        .collect::<String>();
+    let message = chat::escape(&chat::censor(&message, &config.banned_words));
+
+    let tribe = storage::run(server, move |s| {
+        let mut tribe = match s.get_tribe(tribe_id)? {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        tribe.message = message;
+        s.save_tribe(&tribe)?;
+
+        Ok(Some(tribe))
+    })
+    .await?;
+    let tribe = match tribe {
+        Some(t) => t,
+        None => return Ok(result::NOT_FOUND),
+    };
+
+    tribe::send_to_members(
+        server,
+        tribe_id,
+        send::TribeMessageChanged {
+            name,
+            message: tribe.message,
+        },
+    )
+    .await?;
+
+    Ok(result::SUCCESS)
+}
+
+pub async fn set_house_map(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::SetTribeHouseMap,
+) -> Result {
+    let result = change_house_map(&client, &server, packet.xml).await?;
+
+    respond(
+        &client,
+        send::SetTribeHouseMapResult {
+            tribulle_id: packet.tribulle_id,
+            result,
+        },
+    )
+    .await
+}
+
+async fn change_house_map(
+    client: &Arc<Mutex<Client>>,
+    server: &Arc<Mutex<Server>>,
+    xml: String,
+) -> Outcome {
+    let c = client.lock().await;
+    if !c.can_in_tribe(TribePermission::ChangeHouseMap) {
+        return Ok(result::NOT_ALLOWED);
+    }
+    let tribe_id = c.tribe_id;
+    let name = c.full_name();
+    drop(c);
+
+    if !xml.starts_with("<C>") || xml.len() > MAX_HOUSE_MAP_LENGTH {
+        return Ok(result::NOT_ALLOWED);
+    }
+
+    let tribe = storage::run(server, move |s| {
+        let mut tribe = match s.get_tribe(tribe_id)? {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        tribe.house_map = xml;
+        s.save_tribe(&tribe)?;
+
+        Ok(Some(tribe))
+    })
+    .await?;
+    let tribe = match tribe {
+        Some(t) => t,
+        None => return Ok(result::NOT_FOUND),
+    };
+
+    tribe::reload_house(server, &tribe).await?;
+    tribe::send_to_members(server, tribe_id, send::TribeHouseMapChanged { name }).await?;
+
+    Ok(result::SUCCESS)
+}
+
+pub async fn kick(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::KickTribeMember,
+) -> Result {
+    let result = kick_member(&client, &server, &packet.name).await?;
+
+    respond(
+        &client,
+        send::KickTribeMemberResult {
+            tribulle_id: packet.tribulle_id,
+            result,
+        },
+    )
+    .await
+}
+
+async fn kick_member(
+    client: &Arc<Mutex<Client>>,
+    server: &Arc<Mutex<Server>>,
+    name: &str,
+) -> Outcome {
+    let c = client.lock().await;
+    if !c.can_in_tribe(TribePermission::Kick) {
+        return Ok(result::NOT_ALLOWED);
+    }
+    let tribe_id = c.tribe_id;
+    let rank = c.tribe_rank;
+    let account_id = c.account_id;
+    let by = c.full_name();
+    drop(c);
+
+    let name = name.to_string();
+    let kicked = storage::run(server, move |s| {
+        let target = match tribulle::find_account(s, &name)? {
+            Some(a) => a,
+            None => return Ok(Err(result::NOT_FOUND)),
+        };
+        if target.id == account_id {
+            return Ok(Err(result::YOURSELF));
+        }
+
+        match s.account_tribe(target.id)? {
+            Some((t, target_rank)) if t.id == tribe_id => {
+                // only lower ranks can be kicked
+                if target_rank >= rank {
+                    return Ok(Err(result::NOT_ALLOWED));
+                }
+            }
+            _ => return Ok(Err(result::NOT_FOUND)),
+        }
+        s.remove_tribe_member(target.id)?;
+
+        Ok(Ok(target))
+    })
+    .await?;
+    let target = match kicked {
+        Ok(a) => a,
+        Err(result) => return Ok(result),
+    };
+
+    let online = tribe::online_member(server, tribe_id, target.id).await;
+    tribe::send_to_members(
+        server,
+        tribe_id,
+        send::TribeMemberKicked {
+            name: target.full_name(),
+            by,
+        },
+    )
+    .await?;
+    if let Some(player) = online {
+        player.lock().await.leave_tribe();
+    }
+
+    Ok(result::SUCCESS)
+}
+
+pub async fn open(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    _packet: recv::OpenTribe,
+) -> Result {
+    let tribe_id = client.lock().await.tribe_id;
+    if tribe_id == 0 {
+        return Ok(());
+    }
+
+    let tribe = storage::run(&server, move |s| match s.get_tribe(tribe_id)? {
+        Some(tribe) => Ok(Some((tribe, s.tribe_members(tribe_id)?))),
+        None => Ok(None),
+    })
+    .await?;
+    let (tribe, members) = match tribe {
+        Some(t) => t,
+        None => return Ok(()),
+    };
+
+    // rooms of the online members
+    let c = client.lock().await;
+    let mut online = HashMap::from([(c.account_id, c.last_room.clone())]);
+    drop(c);
+    let players = tribulle::online_players(&client, &server, |p| p.tribe_id == tribe_id).await;
+    for player in players {
+        let p = player.lock().await;
+        online.insert(p.account_id, p.last_room.clone());
+    }
+
+    let members = members
+        .iter()
+        .map(|(account, rank)| {
+            let mut info = tribe::member_info(account, *rank);
+            if let Some(room) = online.get(&account.id) {
+                info.is_connected = true;
+                info.room = room.clone();
+            }
+
+            info
+        })
+        .collect();
+
+    respond(
+        &client,
+        send::TribeInfo {
+            id: tribe.id,
+            name: tribe.name,
+            message: tribe.message,
+            ranks: TribeRank::ALL
+                .iter()
+                .map(|r| r.name().to_string())
+                .collect(),
+            members,
+        },
+    )
+    .await
+}
+
+pub async fn close(
+    _client: Arc<Mutex<Client>>,
+    _server: Arc<Mutex<Server>>,
+    _packet: recv::CloseTribe,
+) -> Result {
+    Ok(())
+}
+
+pub async fn set_rank(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::SetTribeRank,
+) -> Result {
+    let result = change_rank(&client, &server, &packet.name, packet.rank).await?;
+
+    respond(
+        &client,
+        send::SetTribeRankResult {
+            tribulle_id: packet.tribulle_id,
+            result,
+        },
+    )
+    .await
+}
+
+async fn change_rank(
+    client: &Arc<Mutex<Client>>,
+    server: &Arc<Mutex<Server>>,
+    name: &str,
+    rank: u8,
+) -> Outcome {
+    let c = client.lock().await;
+    if !c.can_in_tribe(TribePermission::ChangeRanks) {
+        return Ok(result::NOT_ALLOWED);
+    }
+    let tribe_id = c.tribe_id;
+    let account_id = c.account_id;
+    let by = c.full_name();
+    drop(c);
+
+    let rank = match TribeRank::from_id(rank) {
+        Some(r) => r,
+        None => return Ok(result::NOT_ALLOWED),
+    };
+
+    let name = name.to_string();
+    let target = storage::run(server, move |s| {
+        let target = match tribulle::find_account(s, &name)? {
+            Some(a) => a,
+            None => return Ok(Err(result::NOT_FOUND)),
+        };
+        if target.id == account_id {
+            return Ok(Err(result::YOURSELF));
+        }
+        match s.account_tribe(target.id)? {
+            Some((t, _)) if t.id == tribe_id => Ok(Ok(target)),
+            _ => Ok(Err(result::NOT_FOUND)),
+        }
+    })
+    .await?;
+    let target = match target {
+        Ok(a) => a,
+        Err(result) => return Ok(result),
+    };
+
+    let mut changes = vec![(target.id, target.full_name(), rank)];
+    // there is only one leader, it becomes an officer
+    if rank == TribeRank::Leader {
+        changes.push((account_id, by.clone(), TribeRank::Officer));
+    }
+
+    for (id, name, rank) in changes {
+        storage::run(server, move |s| s.set_tribe_member(tribe_id, id, rank)).await?;
+
+        let online = if id == account_id {
+            Some(Arc::clone(client))
+        } else {
+            tribe::online_member(server, tribe_id, id).await
+        };
+        if let Some(player) = online {
+            player.lock().await.tribe_rank = rank;
+        }
+
+        tribe::send_to_members(
+            server,
+            tribe_id,
+            send::TribeMemberRankChanged {
+                name,
+                rank: rank.id(),
+                by: by.clone(),
+            },
+        )
+        .await?;
+    }
+
+    Ok(result::SUCCESS)
+}
+
+async fn respond<T: Tribulle>(client: &Arc<Mutex<Client>>, packet: T) -> Result {
+    client.lock().await.send_tribulle(packet).await
+}
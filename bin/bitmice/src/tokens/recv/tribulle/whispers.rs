@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use crate::{
+    chat,
+    packets::tribulle::{
+        recv,
+        send::{self, result},
+    },
+    tribulle::{self, WhisperState},
+    Client, Result, Server,
+};
+use bitmice_utils::language_id;
+use tokio::sync::Mutex;
+
+const MAX_MESSAGE_LENGTH: usize = 255;
+
+pub async fn whisper(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::Whisper,
+) -> Result {
+    let recv::Whisper {
+        tribulle_id,
+        name,
+        message,
+    } = packet;
+
+    let message = message
+        .trim()
+        .chars()
+        .take(MAX_MESSAGE_LENGTH)
+        .collect::<String>();
+    if message.is_empty() {
+        return Ok(());
+    }
+    // filtered and limited the same way as the room chat
+    let config = server.lock().await.config.chat.clone();
+    let message = chat::escape(&chat::censor(&message, &config.banned_words));
+
+    let mut c = client.lock().await;
+    let can_chat = c.can_chat(&config).await?;
+    let account_id = c.account_id;
+    let sender = c.full_name();
+    let community = language_id(&c.lang) as u32;
+    drop(c);
+
+    let target = tribulle::online_players(&client, &server, |p| p.has_name(&name)).await;
+    let result = match target.first() {
+        _ if account_id == 0 || !can_chat => result::NOT_ALLOWED,
+        Some(target) => {
+            let mut t = target.lock().await;
+
+            let is_allowed = !t.ignored.contains(&account_id)
+                && match t.whisper_state {
+                    WhisperState::Enabled => true,
+                    WhisperState::Disabled => false,
+                    WhisperState::FriendsOnly => t.friends.contains(&account_id),
+                };
+            if is_allowed {
+                let packet = send::WhisperReceived {
+                    sender,
+                    community,
+                    receiver: t.full_name(),
+                    message,
+                };
+                t.send_tribulle(packet.clone()).await?;
+                drop(t);
+
+                // the sender sees its own message too
+                client.lock().await.send_tribulle(packet).await?;
+                result::SUCCESS
+            } else {
+                // away message
+                if t.whisper_state == WhisperState::Disabled && !t.whisper_message.is_empty() {
+                    let packet = send::WhisperReceived {
+                        sender: t.full_name(),
+                        community: language_id(&t.lang) as u32,
+                        receiver: sender,
+                        message: t.whisper_message.clone(),
+                    };
+                    drop(t);
+
+                    client.lock().await.send_tribulle(packet).await?;
+                }
+
+                result::NOT_ALLOWED
+            }
+        }
+        None if client.lock().await.has_name(&name) => result::YOURSELF,
+        None => result::NOT_FOUND,
+    };
+
+    client
+        .lock()
+        .await
+        .send_tribulle(send::WhisperResult {
+            tribulle_id,
+            result,
+        })
+        .await
+}
+
+pub async fn set_state(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    packet: recv::SetWhisperState,
+) -> Result {
+    let recv::SetWhisperState {
+        tribulle_id,
+        state,
+        message,
+    } = packet;
+
+    let banned_words = server.lock().await.config.chat.banned_words.clone();
+    let message = message.chars().take(MAX_MESSAGE_LENGTH).collect::<String>();
+
+    let mut c = client.lock().await;
+    let result = match WhisperState::from_u8(state) {
+        Some(state) => {
+            c.whisper_state = state;
+            c.whisper_message = chat::escape(&chat::censor(&message, &banned_words));
+            result::SUCCESS
+        }
+        None => result::NOT_ALLOWED,
+    };
+
+    c.send_tribulle(send::SetWhisperStateResult {
+        tribulle_id,
+        result,
+    })
+    .await
+}
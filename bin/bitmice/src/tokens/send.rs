@@ -6,10 +6,22 @@ pub const CROUCH: (u8, u8) = (4, 9);
 
 pub const NEW_MAP: (u8, u8) = (5, 2);
 pub const MAP_START_TIMER: (u8, u8) = (5, 10);
+pub const SPAWN_OBJECT: (u8, u8) = (5, 20);
 pub const ENTER_ROOM: (u8, u8) = (5, 21);
 pub const ROUND_TIME: (u8, u8) = (5, 22);
+pub const MUSIC_VIDEO: (u8, u8) = (5, 72);
+pub const MUSIC_PLAYLIST: (u8, u8) = (5, 73);
 pub const TUTORIAL: (u8, u8) = (5, 90);
 
+pub const ROOM_MESSAGE: (u8, u8) = (6, 6);
+pub const MESSAGE: (u8, u8) = (6, 9);
+
+pub const MOVE_PLAYER: (u8, u8) = (8, 3);
+pub const PLAYER_WIN: (u8, u8) = (8, 6);
+pub const PLAYER_SCORE: (u8, u8) = (8, 7);
+pub const SHAMAN_INFO: (u8, u8) = (8, 11);
+pub const NPC: (u8, u8) = (8, 30);
+
 pub const ROOM_SERVER: (u8, u8) = (7, 1);
 pub const ROOM_TYPE: (u8, u8) = (7, 30);
 
@@ -23,6 +35,15 @@ pub const LOGIN_SOURIS: (u8, u8) = (26, 33);
 
 pub const PING: (u8, u8) = (28, 6);
 
+pub const ADD_IMAGE: (u8, u8) = (29, 19);
+pub const ADD_TEXT_AREA: (u8, u8) = (29, 20);
+pub const UPDATE_TEXT_AREA: (u8, u8) = (29, 21);
+pub const REMOVE_TEXT_AREA: (u8, u8) = (29, 22);
+pub const ADD_POPUP: (u8, u8) = (29, 23);
+
+pub const TRIBULLE: (u8, u8) = (60, 3);
+
+pub const GIVE_CURRENCY: (u8, u8) = (100, 40);
 pub const IMAGE_LOGIN: (u8, u8) = (100, 99);
 
 pub const PLAYER_LIST: (u8, u8) = (144, 1);
@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::sync::Arc;
+
+use bitmice_database::{Account, Tribe, TribeRank};
+use tokio::sync::Mutex;
+
+use crate::{
+    packets::tribulle::{send, Tribulle},
+    room, storage, tribulle, Client, Result, Server,
+};
+
+/// Prefix of the tribe house rooms, followed by the tribe name.
+pub const HOUSE_PREFIX: &str = "\x03[Tribe] ";
+/// Community of the tribe houses, members of any community share them.
+pub const HOUSE_COMMUNITY: &str = "int";
+/// Map of the tribe houses that were never changed.
+pub const DEFAULT_HOUSE_MAP: &str = r#"<C><P /><Z><S><S L="800" H="40" X="400" Y="380" T="0" P="0,0,0.3,0.2,0,0,0,0" /></S><D><DS X="400" Y="340" /></D><O /></Z></C>"#;
+
+const MAX_NAME_LENGTH: usize = 50;
+
+pub fn house_name(tribe: &str) -> String {
+    format!("{}{}", HOUSE_PREFIX, tribe)
+}
+
+pub fn house_map(tribe: &Tribe) -> String {
+    if tribe.house_map.is_empty() {
+        DEFAULT_HOUSE_MAP.to_string()
+    } else {
+        tribe.house_map.clone()
+    }
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    let length = name.chars().count();
+
+    (2..=MAX_NAME_LENGTH).contains(&length)
+        && name.trim() == name
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '_' || c == '-')
+}
+
+/// Loads the tribe of an account that just logged in.
+pub async fn connect(client: Arc<Mutex<Client>>, server: Arc<Mutex<Server>>) -> Result {
+    let account_id = client.lock().await.account_id;
+    if account_id == 0 {
+        return Ok(());
+    }
+
+    if let Some((tribe, rank)) = storage::run(&server, move |s| s.account_tribe(account_id)).await?
+    {
+        client.lock().await.join_tribe(&tribe, rank);
+    }
+
+    Ok(())
+}
+
+/// Sends a packet to every online member of a tribe.
+pub async fn send_to_members<T: Tribulle + Clone>(
+    server: &Arc<Mutex<Server>>,
+    tribe_id: u32,
+    packet: T,
+) -> Result {
+    let players = server.lock().await.players().await;
+
+    for player in players {
+        let mut p = player.lock().await;
+        if p.tribe_id == tribe_id {
+            p.send_tribulle(packet.clone()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Online client of a tribe member.
+pub async fn online_member(
+    server: &Arc<Mutex<Server>>,
+    tribe_id: u32,
+    account_id: u32,
+) -> Option<Arc<Mutex<Client>>> {
+    let players = server.lock().await.players().await;
+
+    for player in players {
+        let p = player.lock().await;
+        if p.tribe_id == tribe_id && p.account_id == account_id {
+            drop(p);
+            return Some(player);
+        }
+    }
+
+    None
+}
+
+/// Moves a client to the house of its tribe, the room is created with the
+/// map saved by the tribe.
+pub async fn enter_house(client: Arc<Mutex<Client>>, server: Arc<Mutex<Server>>) -> Result {
+    let mut c = client.lock().await;
+    let tribe_id = c.tribe_id;
+    if tribe_id == 0 {
+        return Ok(());
+    }
+    let client_id = c.id;
+    let old_room = c.room.take();
+    drop(c);
+
+    let tribe = match storage::run(&server, move |s| s.get_tribe(tribe_id)).await? {
+        Some(t) => t,
+        None => return Ok(()),
+    };
+
+    if let Some(room) = old_room {
+        room.lock().await.remove_client(client_id).await;
+    }
+
+    let mut c = client.lock().await;
+    c.enter_room(&house_name(&tribe.name)).await?;
+    let room = Arc::clone(c.room.as_ref().unwrap());
+    drop(c);
+
+    let mut r = room.lock().await;
+    if r.is_new {
+        r.map_xml = house_map(&tribe);
+    }
+    r.add_client(Arc::clone(&client)).await?;
+    let is_new = r.is_new;
+    drop(r);
+
+    if is_new {
+        room::trigger(Arc::clone(&room)).await?;
+    } else {
+        crate::client::start_play(Arc::clone(&client)).await?;
+    }
+
+    tribulle::update(client, server).await
+}
+
+/// Reloads the house of a tribe, if someone is on it, after its map changed.
+pub async fn reload_house(server: &Arc<Mutex<Server>>, tribe: &Tribe) -> Result {
+    let s = server.lock().await;
+    let room = s
+        .get_room(house_name(&tribe.name), HOUSE_COMMUNITY.to_string())
+        .await;
+    drop(s);
+
+    if let Some(room) = room {
+        let mut r = room.lock().await;
+        r.map_xml = house_map(tribe);
+        drop(r);
+
+        room::change_map(room).await?;
+    }
+
+    Ok(())
+}
+
+/// How an offline tribe member is shown in the tribe info.
+pub fn member_info(account: &Account, rank: TribeRank) -> send::TribeMemberInfo {
+    send::TribeMemberInfo {
+        id: account.id,
+        name: account.full_name(),
+        gender: account.gender,
+        rank: rank.id(),
+        is_connected: false,
+        room: String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_valid_name;
+
+    #[test]
+    fn validate_names() {
+        assert!(is_valid_name("Mice"));
+        assert!(is_valid_name("The Mice_2"));
+        assert!(!is_valid_name("M"));
+        assert!(!is_valid_name(" Mice"));
+        assert!(!is_valid_name("<b>Mice</b>"));
+        assert!(!is_valid_name("\x03[Tribe] Mice"));
+        assert!(!is_valid_name(&"a".repeat(51)));
+    }
+}
@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{collections::HashSet, sync::Arc};
+
+use bitmice_database::{Account, Relation, Storage};
+use tokio::sync::Mutex;
+
+use crate::{packets::tribulle::send, storage, Client, Result, Server};
+
+pub const MAX_FRIENDS: usize = 500;
+
+/// Who can whisper to a player.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WhisperState {
+    #[default]
+    Enabled,
+    Disabled,
+    FriendsOnly,
+}
+
+impl WhisperState {
+    pub fn from_u8(state: u8) -> Option<Self> {
+        match state {
+            1 => Some(Self::Enabled),
+            2 => Some(Self::Disabled),
+            3 => Some(Self::FriendsOnly),
+            _ => None,
+        }
+    }
+}
+
+enum Presence {
+    Connected,
+    Updated,
+    Disconnected,
+}
+
+/// Gets an account by a name typed by a player, the tag is optional.
+pub fn find_account(
+    storage: &dyn Storage,
+    name: &str,
+) -> bitmice_database::Result<Option<Account>> {
+    let (name, tag) = match name.split_once('#') {
+        Some((name, tag)) => (name, Some(tag)),
+        None => (name, None),
+    };
+
+    storage.get_account(name.trim_start_matches('+'), tag)
+}
+
+/// How an offline account is shown in a friend list.
+pub fn offline_info(account: &Account, is_mutual: bool) -> send::FriendInfo {
+    send::FriendInfo {
+        id: account.id,
+        name: account.full_name(),
+        gender: account.gender,
+        is_mutual,
+        ..Default::default()
+    }
+}
+
+/// Online players, except `client`, that match `filter`.
+pub async fn online_players<F>(
+    client: &Arc<Mutex<Client>>,
+    server: &Arc<Mutex<Server>>,
+    filter: F,
+) -> Vec<Arc<Mutex<Client>>>
+where
+    F: Fn(&Client) -> bool,
+{
+    let players = server.lock().await.players().await;
+
+    let mut ps = Vec::new();
+    for player in players {
+        if Arc::ptr_eq(&player, client) {
+            continue;
+        }
+
+        if filter(&*player.lock().await) {
+            ps.push(player);
+        }
+    }
+
+    ps
+}
+
+/// Loads the relations of an account that just logged in and tells its
+/// friends about it.
+pub async fn connect(client: Arc<Mutex<Client>>, server: Arc<Mutex<Server>>) -> Result {
+    let account_id = client.lock().await.account_id;
+    if account_id == 0 {
+        return Ok(());
+    }
+
+    let (friends, ignored) = storage::run(&server, move |s| {
+        Ok((
+            s.relations(account_id, Relation::Friend)?,
+            s.relations(account_id, Relation::Ignore)?,
+        ))
+    })
+    .await?;
+    let friends = friends.iter().map(|a| a.id).collect::<HashSet<u32>>();
+    let ignored = ignored.iter().map(|a| a.id).collect::<HashSet<u32>>();
+
+    let mut c = client.lock().await;
+    c.friends = friends;
+    c.ignored = ignored;
+    drop(c);
+
+    notify(client, server, Presence::Connected).await
+}
+
+/// Updates the room shown to the friends.
+pub async fn update(client: Arc<Mutex<Client>>, server: Arc<Mutex<Server>>) -> Result {
+    notify(client, server, Presence::Updated).await
+}
+
+pub async fn disconnect(client: Arc<Mutex<Client>>, server: Arc<Mutex<Server>>) -> Result {
+    notify(client, server, Presence::Disconnected).await
+}
+
+async fn notify(
+    client: Arc<Mutex<Client>>,
+    server: Arc<Mutex<Server>>,
+    presence: Presence,
+) -> Result {
+    let c = client.lock().await;
+    let account_id = c.account_id;
+    let friends = c.friends.clone();
+    let info = c.friend_info();
+    drop(c);
+
+    if account_id == 0 {
+        return Ok(());
+    }
+
+    let watchers = online_players(&client, &server, |p| p.friends.contains(&account_id)).await;
+    for watcher in watchers {
+        let mut w = watcher.lock().await;
+
+        let mut info = info.clone();
+        info.is_mutual = friends.contains(&w.account_id);
+        if !info.is_mutual {
+            info.room.clear();
+        }
+
+        match presence {
+            Presence::Connected => {
+                w.send_tribulle(send::FriendConnected {
+                    name: info.name.clone(),
+                })
+                .await?;
+                w.send_tribulle(send::FriendUpdated { friend: info })
+                    .await?;
+            }
+            Presence::Updated => {
+                w.send_tribulle(send::FriendUpdated { friend: info })
+                    .await?;
+            }
+            Presence::Disconnected => {
+                w.send_tribulle(send::FriendDisconnected { name: info.name })
+                    .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
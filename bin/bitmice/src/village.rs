@@ -0,0 +1,254 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{collections::HashMap, sync::Arc};
+
+use bitmice_database::Stats;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use crate::{
+    commands::{self, Reply},
+    config::{ConfigError, Validate},
+    packets::send,
+    storage, Client, Result, Server,
+};
+
+pub const VILLAGE_PATH: &str = "./assets/village.toml";
+const VILLAGE_MAP: &str = "./assets/maps/village.xml";
+
+/// Rooms played on the village map, without rounds.
+pub const ROOM_PREFIX: &str = "village";
+
+/// Text by language, english is used when there's no translation.
+pub type Text = HashMap<String, String>;
+
+pub fn translate<'a>(text: &'a Text, lang: &str) -> &'a str {
+    text.get(lang)
+        .or_else(|| text.get("en"))
+        .map(String::as_str)
+        .unwrap_or_default()
+}
+
+/// Mice of the village, controlled by the server.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Village {
+    #[serde(default, rename = "npc")]
+    pub npcs: Vec<Npc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Npc {
+    pub name: String,
+    #[serde(default)]
+    pub title: u16,
+    #[serde(default)]
+    pub is_feminine: bool,
+    pub look: String,
+    pub x: i16,
+    pub y: i16,
+    #[serde(default)]
+    pub facing_right: bool,
+    /// Said to the players that talk to it.
+    pub greeting: Text,
+    /// Given one at a time, in order.
+    #[serde(default, rename = "quest")]
+    pub quests: Vec<Quest>,
+}
+
+/// Reach `amount` on the counter `stat` of the profile.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Quest {
+    pub id: String,
+    pub description: Text,
+    pub stat: String,
+    pub amount: u32,
+    /// Title given when the quest is completed.
+    #[serde(default)]
+    pub reward_title: Option<u16>,
+}
+
+impl Validate for Village {
+    fn validate(&self) -> std::result::Result<(), ConfigError> {
+        for (i, npc) in self.npcs.iter().enumerate() {
+            let field = format!("npc[{}]", i);
+            if npc.name.trim().is_empty() {
+                return Err(ConfigError::invalid(field, "the name must not be empty"));
+            }
+            if self.npcs[..i].iter().any(|n| n.name == npc.name) {
+                return Err(ConfigError::invalid(field, "the name is used twice"));
+            }
+            if !npc.greeting.contains_key("en") {
+                return Err(ConfigError::invalid(
+                    field,
+                    "the greeting needs an english text",
+                ));
+            }
+
+            for (j, quest) in npc.quests.iter().enumerate() {
+                let field = format!("{}.quest[{}]", field, j);
+                if Stats::default().get(&quest.stat).is_none() {
+                    return Err(ConfigError::invalid(
+                        field,
+                        format!("unknown stat \"{}\"", quest.stat),
+                    ));
+                }
+                if quest.amount == 0 {
+                    return Err(ConfigError::invalid(field, "amount must be greater than 0"));
+                }
+                if self
+                    .npcs
+                    .iter()
+                    .flat_map(|n| &n.quests)
+                    .filter(|q| q.id == quest.id)
+                    .count()
+                    > 1
+                {
+                    return Err(ConfigError::invalid(field, "the id is used twice"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Village {
+    pub fn npc(&self, name: &str) -> Option<&Npc> {
+        self.npcs.iter().find(|n| n.name == name)
+    }
+}
+
+impl Npc {
+    /// Ids of the NPCs are negative, so they don't collide with the players.
+    pub fn packet(&self, index: usize) -> send::Npc {
+        send::Npc {
+            id: -(index as i32 + 1),
+            name: self.name.clone(),
+            title: self.title,
+            is_feminine: self.is_feminine,
+            look: self.look.clone(),
+            position_x: self.x,
+            position_y: self.y,
+            emote: 0,
+            facing_right: self.facing_right,
+            faces_player: true,
+            interface: 0,
+            message: String::new(),
+        }
+    }
+
+    /// First quest not `completed` yet.
+    pub fn next_quest(&self, completed: &[String]) -> Option<&Quest> {
+        self.quests.iter().find(|q| !completed.contains(&q.id))
+    }
+}
+
+pub fn map_xml() -> String {
+    std::fs::read_to_string(VILLAGE_MAP).unwrap_or_else(|e| {
+        log::warn!("couldn't read the village map [{}]: {}", VILLAGE_MAP, e);
+        String::from("<C><P /><Z><S /><D /><O /></Z></C>")
+    })
+}
+
+/// Shows the NPCs of the village to a client.
+pub async fn send_npcs(client: &mut Client, village: &Village) -> Result {
+    for (i, npc) in village.npcs.iter().enumerate() {
+        client.send_packet(npc.packet(i)).await?;
+    }
+
+    Ok(())
+}
+
+/// A client talked to an NPC: it greets the client and gives its quest, or the
+/// reward when the quest is done.
+pub async fn talk(client: Arc<Mutex<Client>>, server: Arc<Mutex<Server>>, name: &str) -> Result {
+    let village = Arc::clone(&server.lock().await.village);
+    let npc = match village.npc(name) {
+        Some(n) => n,
+        None => return Ok(()),
+    };
+
+    let c = client.lock().await;
+    if !c.last_room.starts_with(ROOM_PREFIX) {
+        return Ok(());
+    }
+    let lang = c.lang.clone();
+    let account_id = c.account_id;
+    let stats = c.stats.clone();
+    drop(c);
+
+    let greeting = translate(&npc.greeting, &lang).to_string();
+    commands::reply(&client, Reply::NpcTalk(npc.name.clone(), greeting)).await?;
+
+    // guests can't save their progress
+    if account_id == 0 {
+        return Ok(());
+    }
+    let completed = storage::run(&server, move |s| s.completed_quests(account_id)).await?;
+    let quest = match npc.next_quest(&completed) {
+        Some(q) => q,
+        None => return Ok(()),
+    };
+    let description = translate(&quest.description, &lang).to_string();
+    let progress = stats.get(&quest.stat).unwrap_or_default();
+    if progress < quest.amount {
+        let reply = Reply::QuestProgress(description, progress, quest.amount);
+        return commands::reply(&client, reply).await;
+    }
+
+    let quest_id = quest.id.clone();
+    if storage::run(&server, move |s| s.complete_quest(account_id, &quest_id)).await? {
+        if let Some(title) = quest.reward_title {
+            client.lock().await.title_number = title;
+        }
+        commands::reply(&client, Reply::QuestCompleted(description)).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{translate, Village};
+    use crate::config::{invalid_field, parse_toml};
+
+    const VILLAGE: &str = r#"
+        [[npc]]
+        name = "Papaille"
+        look = "1;0,0,0,0,0,0,0,0,0,0,0"
+        x = 400
+        y = 350
+        greeting = { en = "Hello!", br = "Olá!" }
+
+        [[npc.quest]]
+        id = "cheese"
+        description = { en = "Bring cheese to the hole." }
+        stat = "cheese"
+        amount = 10
+    "#;
+
+    #[test]
+    fn parse_npcs() {
+        let village = parse_toml::<Village>(VILLAGE).unwrap();
+        let npc = village.npc("Papaille").unwrap();
+
+        assert_eq!(translate(&npc.greeting, "br"), "Olá!");
+        assert_eq!(translate(&npc.greeting, "fr"), "Hello!");
+        assert_eq!(npc.next_quest(&[]).map(|q| q.amount), Some(10));
+        assert!(npc.next_quest(&[String::from("cheese")]).is_none());
+        assert_eq!(npc.packet(0).id, -1);
+    }
+
+    #[test]
+    fn reject_bad_quests() {
+        let content = VILLAGE.replace("stat = \"cheese\"", "stat = \"unknown\"");
+        assert_eq!(invalid_field::<Village>(&content), "npc[0].quest[0]");
+
+        let content = VILLAGE.replace("amount = 10", "amount = 0");
+        assert_eq!(invalid_field::<Village>(&content), "npc[0].quest[0]");
+
+        let content = VILLAGE.replace("en = \"Hello!\", ", "");
+        assert_eq!(invalid_field::<Village>(&content), "npc[0]");
+    }
+}
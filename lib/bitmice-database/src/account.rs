@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+#[derive(Debug, Clone)]
+pub struct Account {
+    pub id: u32,
+    pub name: String,
+    pub tag: String,
+    pub email: String,
+    /// Password hash, see [`crate::password`].
+    pub password: String,
+
+    pub color: String,
+    pub look: String,
+    pub nick_color: String,
+    pub shaman_color: String,
+
+    pub gender: u8,
+    pub priv_level: i8,
+    pub score: u16,
+    pub time_played: u64,
+    pub title_number: u16,
+    pub title_stars: u8,
+    pub created_at: u64,
+    /// Preferred shaman mode, see `ShamanMode` on the server.
+    pub shaman_mode: u8,
+}
+
+impl Account {
+    pub fn new(name: &str, tag: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            tag: tag.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn full_name(&self) -> String {
+        format!("{}#{}", self.name, self.tag)
+    }
+}
+
+impl Default for Account {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            name: String::new(),
+            tag: String::from("0000"),
+            email: String::new(),
+            password: String::new(),
+
+            color: String::from("95d9d6"),
+            look: String::from("1;0,0,0,0,0,0,0,0,0,0,0"),
+            nick_color: String::from("953"),
+            shaman_color: String::from("95fe3f"),
+
+            gender: 2,
+            priv_level: 1,
+            score: 0,
+            time_played: 0,
+            title_number: 5,
+            title_stars: 3,
+            created_at: 0,
+            shaman_mode: 0,
+        }
+    }
+}
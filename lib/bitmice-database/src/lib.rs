@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+mod account;
+mod error;
+pub mod password;
+mod record;
+mod relation;
+mod sqlite;
+mod stats;
+mod tribe;
+
+pub use account::Account;
+pub use error::DatabaseError;
+pub use record::MapRecord;
+pub use relation::Relation;
+pub use sqlite::SqliteStorage;
+pub use stats::Stats;
+pub use tribe::{Tribe, TribePermission, TribeRank};
+
+pub type Result<T> = std::result::Result<T, DatabaseError>;
+
+/// Persistent storage used by the server, every backend must be safe to
+/// share between the client tasks.
+pub trait Storage: Send + Sync {
+    /// Creates a new account, hashing the given password, and returns it with its id.
+    fn create_account(&self, account: Account, password: &str) -> Result<Account>;
+
+    /// Gets an account by its name and tag, if the tag is `None` the oldest
+    /// account with that name is returned.
+    fn get_account(&self, name: &str, tag: Option<&str>) -> Result<Option<Account>>;
+
+    /// Gets the tags already used by accounts with the given name.
+    fn account_tags(&self, name: &str) -> Result<Vec<String>>;
+
+    /// Saves the profile of an already created account.
+    fn save_account(&self, account: &Account) -> Result<()>;
+
+    /// Gets the stats of an account, all zeroed if it never had any.
+    fn stats(&self, account_id: u32) -> Result<Stats>;
+
+    fn save_stats(&self, account_id: u32, stats: &Stats) -> Result<()>;
+
+    /// Fastest time a map was finished in, if it was ever finished.
+    fn map_record(&self, map_code: i32) -> Result<Option<MapRecord>>;
+
+    /// Saves the time of an account on a map if it beats the record, returns
+    /// `false` if it didn't.
+    fn save_map_record(&self, map_code: i32, account_id: u32, time: u32) -> Result<bool>;
+
+    /// Ids of the quests an account completed.
+    fn completed_quests(&self, account_id: u32) -> Result<Vec<String>>;
+
+    /// Marks a quest as completed, returns `false` if it already was.
+    fn complete_quest(&self, account_id: u32, quest: &str) -> Result<bool>;
+
+    fn account_exists(&self, name: &str, tag: &str) -> Result<bool> {
+        Ok(self.get_account(name, Some(tag))?.is_some())
+    }
+
+    /// Adds a relation from `account_id` to `target_id`, returns `false` if
+    /// it already exists.
+    fn add_relation(&self, account_id: u32, target_id: u32, relation: Relation) -> Result<bool>;
+
+    /// Removes a relation, returns `false` if it didn't exist.
+    fn remove_relation(&self, account_id: u32, target_id: u32, relation: Relation) -> Result<bool>;
+
+    /// Accounts related to `account_id`, sorted by name.
+    fn relations(&self, account_id: u32, relation: Relation) -> Result<Vec<Account>>;
+
+    /// Ids of the accounts that have a relation with `target_id`.
+    fn related_by(&self, target_id: u32, relation: Relation) -> Result<Vec<u32>>;
+
+    /// Creates a tribe with `leader_id` as its leader.
+    fn create_tribe(&self, name: &str, leader_id: u32) -> Result<Tribe>;
+
+    fn get_tribe(&self, id: u32) -> Result<Option<Tribe>>;
+
+    /// Saves the message and the house map of a tribe.
+    fn save_tribe(&self, tribe: &Tribe) -> Result<()>;
+
+    /// Deletes a tribe and removes all its members.
+    fn delete_tribe(&self, id: u32) -> Result<()>;
+
+    /// Members of a tribe, from the highest rank to the lowest.
+    fn tribe_members(&self, tribe_id: u32) -> Result<Vec<(Account, TribeRank)>>;
+
+    /// Tribe of an account, if any, and its rank on it.
+    fn account_tribe(&self, account_id: u32) -> Result<Option<(Tribe, TribeRank)>>;
+
+    /// Adds an account to a tribe or changes its rank, an account is member of
+    /// one tribe at most.
+    fn set_tribe_member(&self, tribe_id: u32, account_id: u32, rank: TribeRank) -> Result<()>;
+
+    /// Removes an account from its tribe, returns `false` if it had none.
+    fn remove_tribe_member(&self, account_id: u32) -> Result<bool>;
+
+    /// Gets an account and checks its password.
+    fn login(&self, name: &str, tag: Option<&str>, password: &str) -> Result<Option<Account>> {
+        match self.get_account(name, tag)? {
+            Some(account) if password::verify(password, &account.password) => Ok(Some(account)),
+            _ => Ok(None),
+        }
+    }
+}
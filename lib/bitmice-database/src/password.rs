@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+//! Password hashing, stored as `pbkdf2-sha256$<rounds>$<salt>$<hash>`.
+
+use rand::RngCore;
+use sha2::Sha256;
+
+const PREFIX: &str = "pbkdf2-sha256";
+const ROUNDS: u32 = 10_000;
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 32;
+
+pub fn hash(password: &str) -> String {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+
+    let hash = derive(password, &salt, ROUNDS);
+    format!("{}${}${}${}", PREFIX, ROUNDS, to_hex(&salt), to_hex(&hash))
+}
+
+pub fn verify(password: &str, stored: &str) -> bool {
+    let parts = stored.split('$').collect::<Vec<&str>>();
+    if parts.len() != 4 || parts[0] != PREFIX {
+        return false;
+    }
+
+    let (rounds, salt, expected) = match (
+        parts[1].parse::<u32>(),
+        from_hex(parts[2]),
+        from_hex(parts[3]),
+    ) {
+        (Ok(r), Some(s), Some(e)) => (r, s, e),
+        _ => return false,
+    };
+
+    let hash = derive(password, &salt, rounds);
+    if hash.len() != expected.len() {
+        return false;
+    }
+
+    // constant time comparison
+    hash.iter()
+        .zip(expected.iter())
+        .fold(0, |acc, (a, b)| acc | (a ^ b))
+        == 0
+}
+
+fn derive(password: &str, salt: &[u8], rounds: u32) -> [u8; HASH_LEN] {
+    let mut hash = [0u8; HASH_LEN];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, rounds, &mut hash);
+
+    hash
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+/// Fastest time a map was finished in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapRecord {
+    pub map_code: i32,
+    pub account_id: u32,
+    /// Full name of the account, with its tag.
+    pub name: String,
+    /// In centiseconds.
+    pub time: u32,
+    pub created_at: u64,
+}
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+/// Relation of an account with another one, it's one-sided: a friendship is
+/// mutual only if both accounts added each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Relation {
+    Friend,
+    Ignore,
+}
+
+impl Relation {
+    pub(crate) fn id(self) -> u8 {
+        match self {
+            Self::Friend => 0,
+            Self::Ignore => 1,
+        }
+    }
+}
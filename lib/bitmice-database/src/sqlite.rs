@@ -0,0 +1,871 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{path::Path, sync::Mutex, time::UNIX_EPOCH};
+
+use rusqlite::{params, Connection, ErrorCode, OptionalExtension, Row};
+
+use crate::{
+    password, Account, DatabaseError, MapRecord, Relation, Result, Stats, Storage, Tribe, TribeRank,
+};
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS accounts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    email TEXT NOT NULL DEFAULT '',
+    password TEXT NOT NULL,
+
+    color TEXT NOT NULL,
+    look TEXT NOT NULL,
+    nick_color TEXT NOT NULL,
+    shaman_color TEXT NOT NULL,
+
+    gender INTEGER NOT NULL,
+    priv_level INTEGER NOT NULL,
+    score INTEGER NOT NULL,
+    time_played INTEGER NOT NULL,
+    title_number INTEGER NOT NULL,
+    title_stars INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    shaman_mode INTEGER NOT NULL DEFAULT 0,
+
+    UNIQUE (name COLLATE NOCASE, tag)
+);
+
+CREATE TABLE IF NOT EXISTS stats (
+    account_id INTEGER NOT NULL REFERENCES accounts (id),
+    name TEXT NOT NULL,
+    value INTEGER NOT NULL,
+
+    PRIMARY KEY (account_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS map_records (
+    map_code INTEGER PRIMARY KEY,
+    account_id INTEGER NOT NULL REFERENCES accounts (id),
+    time INTEGER NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS quests (
+    account_id INTEGER NOT NULL REFERENCES accounts (id),
+    quest TEXT NOT NULL,
+    completed_at INTEGER NOT NULL,
+
+    PRIMARY KEY (account_id, quest)
+);
+
+CREATE TABLE IF NOT EXISTS relations (
+    account_id INTEGER NOT NULL REFERENCES accounts (id),
+    target_id INTEGER NOT NULL REFERENCES accounts (id),
+    kind INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+
+    PRIMARY KEY (account_id, target_id, kind)
+);
+
+CREATE TABLE IF NOT EXISTS tribes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    message TEXT NOT NULL DEFAULT '',
+    house_map TEXT NOT NULL DEFAULT '',
+    created_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS tribe_members (
+    account_id INTEGER PRIMARY KEY REFERENCES accounts (id),
+    tribe_id INTEGER NOT NULL REFERENCES tribes (id),
+    rank INTEGER NOT NULL
+);
+"#;
+
+/// Columns added after the tables were first created, they're added to the
+/// databases that don't have them yet.
+const ADDED_COLUMNS: [(&str, &str, &str); 1] =
+    [("accounts", "shaman_mode", "INTEGER NOT NULL DEFAULT 0")];
+
+const ACCOUNT_COLUMNS: &str = "id, name, tag, email, password, color, look, nick_color, \
+    shaman_color, gender, priv_level, score, time_played, title_number, title_stars, created_at, \
+    shaman_mode";
+
+const TRIBE_COLUMNS: &str = "id, name, message, house_map, created_at";
+
+/// [`Storage`] backed by an embedded SQLite database.
+pub struct SqliteStorage {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        for (table, column, definition) in ADDED_COLUMNS {
+            let exists = connection.query_row(
+                "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+                params![table, column],
+                |row| row.get::<_, u32>(0),
+            )? > 0;
+
+            if !exists {
+                connection.execute_batch(&format!(
+                    "ALTER TABLE {} ADD COLUMN {} {}",
+                    table, column, definition
+                ))?;
+            }
+        }
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    fn connection(&self) -> std::sync::MutexGuard<'_, Connection> {
+        // a panic while holding the lock can't leave the connection in a bad state
+        self.connection
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn create_account(&self, mut account: Account, password: &str) -> Result<Account> {
+        account.password = password::hash(password);
+        account.created_at = UNIX_EPOCH.elapsed().unwrap_or_default().as_secs();
+
+        let connection = self.connection();
+        let result = connection.execute(
+            "INSERT INTO accounts (name, tag, email, password, color, look, nick_color, \
+                shaman_color, gender, priv_level, score, time_played, title_number, \
+                title_stars, created_at, shaman_mode) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            params![
+                account.name,
+                account.tag,
+                account.email,
+                account.password,
+                account.color,
+                account.look,
+                account.nick_color,
+                account.shaman_color,
+                account.gender,
+                account.priv_level,
+                account.score,
+                account.time_played as i64,
+                account.title_number,
+                account.title_stars,
+                account.created_at as i64,
+                account.shaman_mode,
+            ],
+        );
+        // the name and tag are unique, two registrations can't both pass
+        if let Err(e) = result {
+            return Err(match e.sqlite_error_code() {
+                Some(ErrorCode::ConstraintViolation) => {
+                    DatabaseError::AlreadyExists(account.full_name())
+                }
+                _ => e.into(),
+            });
+        }
+        account.id = connection.last_insert_rowid() as u32;
+
+        Ok(account)
+    }
+
+    fn get_account(&self, name: &str, tag: Option<&str>) -> Result<Option<Account>> {
+        let connection = self.connection();
+
+        let account = match tag {
+            Some(tag) => connection
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM accounts WHERE name = ?1 COLLATE NOCASE AND tag = ?2",
+                        ACCOUNT_COLUMNS
+                    ),
+                    params![name, tag],
+                    account_from_row,
+                )
+                .optional()?,
+            None => connection
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM accounts WHERE name = ?1 COLLATE NOCASE ORDER BY id LIMIT 1",
+                        ACCOUNT_COLUMNS
+                    ),
+                    params![name],
+                    account_from_row,
+                )
+                .optional()?,
+        };
+
+        Ok(account)
+    }
+
+    fn account_tags(&self, name: &str) -> Result<Vec<String>> {
+        let connection = self.connection();
+
+        let mut statement =
+            connection.prepare("SELECT tag FROM accounts WHERE name = ?1 COLLATE NOCASE")?;
+        let tags = statement
+            .query_map(params![name], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+
+        Ok(tags)
+    }
+
+    fn save_account(&self, account: &Account) -> Result<()> {
+        let updated = self.connection().execute(
+            "UPDATE accounts SET color = ?2, look = ?3, nick_color = ?4, shaman_color = ?5, \
+                gender = ?6, priv_level = ?7, score = ?8, time_played = ?9, title_number = ?10, \
+                title_stars = ?11, shaman_mode = ?12 \
+             WHERE id = ?1",
+            params![
+                account.id,
+                account.color,
+                account.look,
+                account.nick_color,
+                account.shaman_color,
+                account.gender,
+                account.priv_level,
+                account.score,
+                account.time_played as i64,
+                account.title_number,
+                account.title_stars,
+                account.shaman_mode,
+            ],
+        )?;
+
+        if updated == 0 {
+            return Err(DatabaseError::NotFound(account.full_name()));
+        }
+
+        Ok(())
+    }
+
+    fn stats(&self, account_id: u32) -> Result<Stats> {
+        let connection = self.connection();
+
+        let mut statement =
+            connection.prepare("SELECT name, value FROM stats WHERE account_id = ?1")?;
+        let counters = statement
+            .query_map(params![account_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<(String, u32)>>>()?;
+
+        let mut stats = Stats::default();
+        for (name, value) in counters {
+            stats.set(&name, value);
+        }
+
+        Ok(stats)
+    }
+
+    fn save_stats(&self, account_id: u32, stats: &Stats) -> Result<()> {
+        let mut connection = self.connection();
+
+        let transaction = connection.transaction()?;
+        for (name, value) in stats.counters() {
+            transaction.execute(
+                "INSERT OR REPLACE INTO stats (account_id, name, value) VALUES (?1, ?2, ?3)",
+                params![account_id, name, value],
+            )?;
+        }
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    fn map_record(&self, map_code: i32) -> Result<Option<MapRecord>> {
+        let record = self
+            .connection()
+            .query_row(
+                "SELECT r.map_code, r.account_id, a.name || '#' || a.tag, r.time, r.created_at \
+                 FROM map_records r JOIN accounts a ON a.id = r.account_id WHERE r.map_code = ?1",
+                params![map_code],
+                |row| {
+                    Ok(MapRecord {
+                        map_code: row.get(0)?,
+                        account_id: row.get(1)?,
+                        name: row.get(2)?,
+                        time: row.get(3)?,
+                        created_at: row.get::<_, i64>(4)? as u64,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(record)
+    }
+
+    fn save_map_record(&self, map_code: i32, account_id: u32, time: u32) -> Result<bool> {
+        let saved = self.connection().execute(
+            "INSERT INTO map_records (map_code, account_id, time, created_at) \
+             VALUES (?1, ?2, ?3, ?4) \
+             ON CONFLICT (map_code) DO UPDATE SET account_id = excluded.account_id, \
+                time = excluded.time, created_at = excluded.created_at \
+             WHERE excluded.time < map_records.time",
+            params![
+                map_code,
+                account_id,
+                time,
+                UNIX_EPOCH.elapsed().unwrap_or_default().as_secs() as i64,
+            ],
+        )?;
+
+        Ok(saved > 0)
+    }
+
+    fn completed_quests(&self, account_id: u32) -> Result<Vec<String>> {
+        let connection = self.connection();
+
+        let mut statement = connection
+            .prepare("SELECT quest FROM quests WHERE account_id = ?1 ORDER BY completed_at")?;
+        let quests = statement
+            .query_map(params![account_id], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+
+        Ok(quests)
+    }
+
+    fn complete_quest(&self, account_id: u32, quest: &str) -> Result<bool> {
+        let inserted = self.connection().execute(
+            "INSERT OR IGNORE INTO quests (account_id, quest, completed_at) VALUES (?1, ?2, ?3)",
+            params![
+                account_id,
+                quest,
+                UNIX_EPOCH.elapsed().unwrap_or_default().as_secs() as i64,
+            ],
+        )?;
+
+        Ok(inserted > 0)
+    }
+
+    fn add_relation(&self, account_id: u32, target_id: u32, relation: Relation) -> Result<bool> {
+        let inserted = self.connection().execute(
+            "INSERT OR IGNORE INTO relations (account_id, target_id, kind, created_at) \
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                account_id,
+                target_id,
+                relation.id(),
+                UNIX_EPOCH.elapsed().unwrap_or_default().as_secs() as i64,
+            ],
+        )?;
+
+        Ok(inserted > 0)
+    }
+
+    fn remove_relation(&self, account_id: u32, target_id: u32, relation: Relation) -> Result<bool> {
+        let deleted = self.connection().execute(
+            "DELETE FROM relations WHERE account_id = ?1 AND target_id = ?2 AND kind = ?3",
+            params![account_id, target_id, relation.id()],
+        )?;
+
+        Ok(deleted > 0)
+    }
+
+    fn relations(&self, account_id: u32, relation: Relation) -> Result<Vec<Account>> {
+        let connection = self.connection();
+
+        let mut statement = connection.prepare(&format!(
+            "SELECT {} FROM relations r JOIN accounts a ON a.id = r.target_id \
+             WHERE r.account_id = ?1 AND r.kind = ?2 ORDER BY a.name COLLATE NOCASE, a.tag",
+            prefixed(ACCOUNT_COLUMNS, "a")
+        ))?;
+        let accounts = statement
+            .query_map(params![account_id, relation.id()], account_from_row)?
+            .collect::<rusqlite::Result<Vec<Account>>>()?;
+
+        Ok(accounts)
+    }
+
+    fn related_by(&self, target_id: u32, relation: Relation) -> Result<Vec<u32>> {
+        let connection = self.connection();
+
+        let mut statement = connection
+            .prepare("SELECT account_id FROM relations WHERE target_id = ?1 AND kind = ?2")?;
+        let ids = statement
+            .query_map(params![target_id, relation.id()], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<u32>>>()?;
+
+        Ok(ids)
+    }
+
+    fn create_tribe(&self, name: &str, leader_id: u32) -> Result<Tribe> {
+        if self.tribe_exists(name)? {
+            return Err(DatabaseError::AlreadyExists(name.to_string()));
+        }
+
+        let tribe = Tribe {
+            name: name.to_string(),
+            created_at: UNIX_EPOCH.elapsed().unwrap_or_default().as_secs(),
+            ..Default::default()
+        };
+
+        let mut connection = self.connection();
+        let transaction = connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO tribes (name, message, house_map, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                tribe.name,
+                tribe.message,
+                tribe.house_map,
+                tribe.created_at as i64
+            ],
+        )?;
+        let id = transaction.last_insert_rowid() as u32;
+        transaction.execute(
+            "INSERT OR REPLACE INTO tribe_members (account_id, tribe_id, rank) VALUES (?1, ?2, ?3)",
+            params![leader_id, id, TribeRank::Leader.id()],
+        )?;
+        transaction.commit()?;
+
+        Ok(Tribe { id, ..tribe })
+    }
+
+    fn get_tribe(&self, id: u32) -> Result<Option<Tribe>> {
+        let tribe = self
+            .connection()
+            .query_row(
+                &format!("SELECT {} FROM tribes WHERE id = ?1", TRIBE_COLUMNS),
+                params![id],
+                tribe_from_row,
+            )
+            .optional()?;
+
+        Ok(tribe)
+    }
+
+    fn save_tribe(&self, tribe: &Tribe) -> Result<()> {
+        let updated = self.connection().execute(
+            "UPDATE tribes SET message = ?2, house_map = ?3 WHERE id = ?1",
+            params![tribe.id, tribe.message, tribe.house_map],
+        )?;
+
+        if updated == 0 {
+            return Err(DatabaseError::NotFound(tribe.name.clone()));
+        }
+
+        Ok(())
+    }
+
+    fn delete_tribe(&self, id: u32) -> Result<()> {
+        let mut connection = self.connection();
+
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM tribe_members WHERE tribe_id = ?1", params![id])?;
+        transaction.execute("DELETE FROM tribes WHERE id = ?1", params![id])?;
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    fn tribe_members(&self, tribe_id: u32) -> Result<Vec<(Account, TribeRank)>> {
+        let connection = self.connection();
+
+        let mut statement = connection.prepare(&format!(
+            "SELECT {}, m.rank FROM tribe_members m JOIN accounts a ON a.id = m.account_id \
+             WHERE m.tribe_id = ?1 ORDER BY m.rank DESC, a.name COLLATE NOCASE, a.tag",
+            prefixed(ACCOUNT_COLUMNS, "a")
+        ))?;
+        let members = statement
+            .query_map(params![tribe_id], |row| {
+                Ok((account_from_row(row)?, rank_from_row(row, 17)?))
+            })?
+            .collect::<rusqlite::Result<Vec<(Account, TribeRank)>>>()?;
+
+        Ok(members)
+    }
+
+    fn account_tribe(&self, account_id: u32) -> Result<Option<(Tribe, TribeRank)>> {
+        let tribe = self
+            .connection()
+            .query_row(
+                &format!(
+                    "SELECT {}, m.rank FROM tribe_members m JOIN tribes t ON t.id = m.tribe_id \
+                     WHERE m.account_id = ?1",
+                    prefixed(TRIBE_COLUMNS, "t")
+                ),
+                params![account_id],
+                |row| Ok((tribe_from_row(row)?, rank_from_row(row, 5)?)),
+            )
+            .optional()?;
+
+        Ok(tribe)
+    }
+
+    fn set_tribe_member(&self, tribe_id: u32, account_id: u32, rank: TribeRank) -> Result<()> {
+        self.connection().execute(
+            "INSERT OR REPLACE INTO tribe_members (account_id, tribe_id, rank) VALUES (?1, ?2, ?3)",
+            params![account_id, tribe_id, rank.id()],
+        )?;
+
+        Ok(())
+    }
+
+    fn remove_tribe_member(&self, account_id: u32) -> Result<bool> {
+        let deleted = self.connection().execute(
+            "DELETE FROM tribe_members WHERE account_id = ?1",
+            params![account_id],
+        )?;
+
+        Ok(deleted > 0)
+    }
+}
+
+impl SqliteStorage {
+    fn tribe_exists(&self, name: &str) -> Result<bool> {
+        let exists = self
+            .connection()
+            .query_row(
+                "SELECT 1 FROM tribes WHERE name = ?1 COLLATE NOCASE",
+                params![name],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+
+        Ok(exists)
+    }
+}
+
+/// Columns of a joined table.
+fn prefixed(columns: &str, table: &str) -> String {
+    columns
+        .split(", ")
+        .map(|c| format!("{}.{}", table, c))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn tribe_from_row(row: &Row) -> rusqlite::Result<Tribe> {
+    Ok(Tribe {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        message: row.get(2)?,
+        house_map: row.get(3)?,
+        created_at: row.get::<_, i64>(4)? as u64,
+    })
+}
+
+fn rank_from_row(row: &Row, index: usize) -> rusqlite::Result<TribeRank> {
+    // unknown ranks come from a newer schema, treat them as the lowest one
+    Ok(TribeRank::from_id(row.get(index)?).unwrap_or_default())
+}
+
+fn account_from_row(row: &Row) -> rusqlite::Result<Account> {
+    Ok(Account {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        tag: row.get(2)?,
+        email: row.get(3)?,
+        password: row.get(4)?,
+
+        color: row.get(5)?,
+        look: row.get(6)?,
+        nick_color: row.get(7)?,
+        shaman_color: row.get(8)?,
+
+        gender: row.get(9)?,
+        priv_level: row.get(10)?,
+        score: row.get(11)?,
+        time_played: row.get::<_, i64>(12)? as u64,
+        title_number: row.get(13)?,
+        title_stars: row.get(14)?,
+        created_at: row.get::<_, i64>(15)? as u64,
+        shaman_mode: row.get(16)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use super::SqliteStorage;
+    use crate::{Account, DatabaseError, Relation, Stats, Storage, TribeRank};
+
+    #[test]
+    fn create_and_login() {
+        let storage = SqliteStorage::in_memory().unwrap();
+
+        let account = storage
+            .create_account(Account::new("Andriel", "0001"), "secret")
+            .unwrap();
+        assert_ne!(account.id, 0);
+        assert_ne!(account.password, "secret");
+
+        assert!(storage.account_exists("andriel", "0001").unwrap());
+        assert!(storage
+            .login("Andriel", Some("0001"), "secret")
+            .unwrap()
+            .is_some());
+        assert!(storage.login("Andriel", None, "secret").unwrap().is_some());
+        assert!(storage
+            .login("Andriel", Some("0001"), "wrong")
+            .unwrap()
+            .is_none());
+        assert!(storage
+            .login("Andriel", Some("0002"), "secret")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn add_missing_columns() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE accounts (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, \
+                    tag TEXT NOT NULL, email TEXT NOT NULL DEFAULT '', password TEXT NOT NULL, \
+                    color TEXT NOT NULL, look TEXT NOT NULL, nick_color TEXT NOT NULL, \
+                    shaman_color TEXT NOT NULL, gender INTEGER NOT NULL, \
+                    priv_level INTEGER NOT NULL, score INTEGER NOT NULL, \
+                    time_played INTEGER NOT NULL, title_number INTEGER NOT NULL, \
+                    title_stars INTEGER NOT NULL, created_at INTEGER NOT NULL)",
+            )
+            .unwrap();
+        let storage = SqliteStorage::with_connection(connection).unwrap();
+
+        let mut account = storage
+            .create_account(Account::new("Andriel", "0001"), "secret")
+            .unwrap();
+        account.shaman_mode = 2;
+        storage.save_account(&account).unwrap();
+
+        let account = storage
+            .get_account("Andriel", Some("0001"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.shaman_mode, 2);
+    }
+
+    #[test]
+    fn reject_duplicated_account() {
+        let storage = SqliteStorage::in_memory().unwrap();
+
+        storage
+            .create_account(Account::new("Andriel", "0001"), "secret")
+            .unwrap();
+        let result = storage.create_account(Account::new("ANDRIEL", "0001"), "secret");
+        assert!(matches!(result, Err(DatabaseError::AlreadyExists(_))));
+
+        assert!(storage
+            .create_account(Account::new("Andriel", "0002"), "secret")
+            .is_ok());
+
+        let mut tags = storage.account_tags("andriel").unwrap();
+        tags.sort();
+        assert_eq!(tags, vec!["0001", "0002"]);
+    }
+
+    #[test]
+    fn save_profile() {
+        let storage = SqliteStorage::in_memory().unwrap();
+
+        let mut account = storage
+            .create_account(Account::new("Andriel", "0001"), "secret")
+            .unwrap();
+        account.score = 42;
+        account.look = String::from("5;0,0,0,0,0,0,0,0,0,0,0");
+        account.priv_level = 9;
+        storage.save_account(&account).unwrap();
+
+        let loaded = storage
+            .get_account("Andriel", Some("0001"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.score, 42);
+        assert_eq!(loaded.look, account.look);
+        assert_eq!(loaded.priv_level, 9);
+        assert_eq!(loaded.password, account.password);
+    }
+
+    #[test]
+    fn relations() {
+        let storage = SqliteStorage::in_memory().unwrap();
+
+        let andriel = storage
+            .create_account(Account::new("Andriel", "0001"), "secret")
+            .unwrap();
+        let bob = storage
+            .create_account(Account::new("Bob", "0001"), "secret")
+            .unwrap();
+        let alice = storage
+            .create_account(Account::new("Alice", "0001"), "secret")
+            .unwrap();
+
+        assert!(storage
+            .add_relation(andriel.id, bob.id, Relation::Friend)
+            .unwrap());
+        assert!(!storage
+            .add_relation(andriel.id, bob.id, Relation::Friend)
+            .unwrap());
+        storage
+            .add_relation(andriel.id, alice.id, Relation::Friend)
+            .unwrap();
+        storage
+            .add_relation(andriel.id, bob.id, Relation::Ignore)
+            .unwrap();
+        storage
+            .add_relation(bob.id, andriel.id, Relation::Friend)
+            .unwrap();
+
+        let friends = storage.relations(andriel.id, Relation::Friend).unwrap();
+        let names = friends.iter().map(|a| a.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["Alice", "Bob"]);
+        assert_eq!(
+            storage.related_by(andriel.id, Relation::Friend).unwrap(),
+            vec![bob.id]
+        );
+        assert_eq!(
+            storage.relations(andriel.id, Relation::Ignore).unwrap()[0].id,
+            bob.id
+        );
+
+        assert!(storage
+            .remove_relation(andriel.id, bob.id, Relation::Friend)
+            .unwrap());
+        assert!(!storage
+            .remove_relation(andriel.id, bob.id, Relation::Friend)
+            .unwrap());
+        assert_eq!(
+            storage
+                .relations(andriel.id, Relation::Friend)
+                .unwrap()
+                .len(),
+            1
+        );
+        // the other kind of relation is kept
+        assert_eq!(
+            storage
+                .relations(andriel.id, Relation::Ignore)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn stats() {
+        let storage = SqliteStorage::in_memory().unwrap();
+
+        let andriel = storage
+            .create_account(Account::new("Andriel", "0001"), "secret")
+            .unwrap();
+        assert_eq!(storage.stats(andriel.id).unwrap(), Stats::default());
+
+        let mut stats = Stats {
+            saves: 3,
+            hard_saves: 1,
+            cheese: 10,
+            first: 2,
+            ..Default::default()
+        };
+        storage.save_stats(andriel.id, &stats).unwrap();
+        assert_eq!(storage.stats(andriel.id).unwrap(), stats);
+
+        stats.saves += 1;
+        stats.first += 1;
+        storage.save_stats(andriel.id, &stats).unwrap();
+        assert_eq!(storage.stats(andriel.id).unwrap(), stats);
+    }
+
+    #[test]
+    fn map_records() {
+        let storage = SqliteStorage::in_memory().unwrap();
+
+        let andriel = storage
+            .create_account(Account::new("Andriel", "0001"), "secret")
+            .unwrap();
+        let bob = storage
+            .create_account(Account::new("Bob", "0001"), "secret")
+            .unwrap();
+        assert!(storage.map_record(1).unwrap().is_none());
+
+        assert!(storage.save_map_record(1, andriel.id, 1500).unwrap());
+        assert!(!storage.save_map_record(1, bob.id, 1600).unwrap());
+        assert!(!storage.save_map_record(1, bob.id, 1500).unwrap());
+
+        let record = storage.map_record(1).unwrap().unwrap();
+        assert_eq!((record.name.as_str(), record.time), ("Andriel#0001", 1500));
+
+        assert!(storage.save_map_record(1, bob.id, 1400).unwrap());
+        let record = storage.map_record(1).unwrap().unwrap();
+        assert_eq!((record.account_id, record.time), (bob.id, 1400));
+        // records are kept by map
+        assert!(storage.map_record(2).unwrap().is_none());
+    }
+
+    #[test]
+    fn quests() {
+        let storage = SqliteStorage::in_memory().unwrap();
+
+        let andriel = storage
+            .create_account(Account::new("Andriel", "0001"), "secret")
+            .unwrap();
+        assert!(storage.completed_quests(andriel.id).unwrap().is_empty());
+
+        assert!(storage.complete_quest(andriel.id, "cheese").unwrap());
+        assert!(!storage.complete_quest(andriel.id, "cheese").unwrap());
+        assert_eq!(
+            storage.completed_quests(andriel.id).unwrap(),
+            vec![String::from("cheese")]
+        );
+    }
+
+    #[test]
+    fn tribes() {
+        let storage = SqliteStorage::in_memory().unwrap();
+
+        let andriel = storage
+            .create_account(Account::new("Andriel", "0001"), "secret")
+            .unwrap();
+        let bob = storage
+            .create_account(Account::new("Bob", "0001"), "secret")
+            .unwrap();
+
+        let mut tribe = storage.create_tribe("Mice", andriel.id).unwrap();
+        assert!(matches!(
+            storage.create_tribe("mice", bob.id),
+            Err(DatabaseError::AlreadyExists(_))
+        ));
+
+        storage
+            .set_tribe_member(tribe.id, bob.id, TribeRank::Initiate)
+            .unwrap();
+        let members = storage.tribe_members(tribe.id).unwrap();
+        let members = members
+            .iter()
+            .map(|(a, r)| (a.name.as_str(), *r))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            members,
+            vec![("Andriel", TribeRank::Leader), ("Bob", TribeRank::Initiate)]
+        );
+
+        tribe.message = String::from("welcome");
+        tribe.house_map = String::from("<C><P /><Z><S /><D /><O /></Z></C>");
+        storage.save_tribe(&tribe).unwrap();
+        let (saved, rank) = storage.account_tribe(bob.id).unwrap().unwrap();
+        assert_eq!(saved.message, "welcome");
+        assert_eq!(saved.house_map, tribe.house_map);
+        assert_eq!(rank, TribeRank::Initiate);
+
+        assert!(storage.remove_tribe_member(bob.id).unwrap());
+        assert!(!storage.remove_tribe_member(bob.id).unwrap());
+        assert!(storage.account_tribe(bob.id).unwrap().is_none());
+
+        storage.delete_tribe(tribe.id).unwrap();
+        assert!(storage.get_tribe(tribe.id).unwrap().is_none());
+        assert!(storage.account_tribe(andriel.id).unwrap().is_none());
+    }
+}
@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+/// Counters shown on the profile of an account.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Mice that entered the hole with the help of the account as shaman.
+    pub saves: u32,
+    pub hard_saves: u32,
+    pub divine_saves: u32,
+    /// Earned by the saves, the harder the shaman mode the more it's worth.
+    pub shaman_experience: u32,
+    /// Cheese brought to the hole.
+    pub cheese: u32,
+    /// Rounds where the account was the first to enter the hole.
+    pub first: u32,
+
+    pub racing_rounds: u32,
+    pub racing_finishes: u32,
+    /// Racing rounds finished on one of the first three places.
+    pub racing_podiums: u32,
+    pub racing_firsts: u32,
+
+    /// Bootcamp maps completed.
+    pub bootcamp: u32,
+
+    /// Survivor rounds played as a mouse.
+    pub survivor_rounds: u32,
+    pub survivor_survived: u32,
+    /// Mice killed while the account was the survivor shaman.
+    pub survivor_kills: u32,
+
+    pub defilante_rounds: u32,
+    /// Points collected on defilante maps.
+    pub defilante_points: u32,
+}
+
+impl Stats {
+    /// Every counter with the name it's stored by, names that aren't known
+    /// anymore are ignored when loading.
+    pub(crate) fn counters(&self) -> Vec<(&'static str, u32)> {
+        vec![
+            ("saves", self.saves),
+            ("hard_saves", self.hard_saves),
+            ("divine_saves", self.divine_saves),
+            ("shaman_experience", self.shaman_experience),
+            ("cheese", self.cheese),
+            ("first", self.first),
+            ("racing_rounds", self.racing_rounds),
+            ("racing_finishes", self.racing_finishes),
+            ("racing_podiums", self.racing_podiums),
+            ("racing_firsts", self.racing_firsts),
+            ("bootcamp", self.bootcamp),
+            ("survivor_rounds", self.survivor_rounds),
+            ("survivor_survived", self.survivor_survived),
+            ("survivor_kills", self.survivor_kills),
+            ("defilante_rounds", self.defilante_rounds),
+            ("defilante_points", self.defilante_points),
+        ]
+    }
+
+    /// Counter stored by `name`, `None` if there is no such counter.
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.counters()
+            .into_iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value)
+    }
+
+    pub(crate) fn set(&mut self, name: &str, value: u32) {
+        match name {
+            "saves" => self.saves = value,
+            "hard_saves" => self.hard_saves = value,
+            "divine_saves" => self.divine_saves = value,
+            "shaman_experience" => self.shaman_experience = value,
+            "cheese" => self.cheese = value,
+            "first" => self.first = value,
+            "racing_rounds" => self.racing_rounds = value,
+            "racing_finishes" => self.racing_finishes = value,
+            "racing_podiums" => self.racing_podiums = value,
+            "racing_firsts" => self.racing_firsts = value,
+            "bootcamp" => self.bootcamp = value,
+            "survivor_rounds" => self.survivor_rounds = value,
+            "survivor_survived" => self.survivor_survived = value,
+            "survivor_kills" => self.survivor_kills = value,
+            "defilante_rounds" => self.defilante_rounds = value,
+            "defilante_points" => self.defilante_points = value,
+            _ => {}
+        }
+    }
+}
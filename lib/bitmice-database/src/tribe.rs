@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+#[derive(Debug, Clone, Default)]
+pub struct Tribe {
+    pub id: u32,
+    pub name: String,
+    /// Message of the day.
+    pub message: String,
+    /// Xml of the tribe house map, empty for the default one.
+    pub house_map: String,
+    pub created_at: u64,
+}
+
+/// Ranks of the tribe members, from the lowest to the highest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TribeRank {
+    #[default]
+    Initiate,
+    Member,
+    Recruiter,
+    Officer,
+    Leader,
+}
+
+/// What a rank allows a member to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TribePermission {
+    ChangeHouseMap,
+    Invite,
+    Kick,
+    ChangeMessage,
+    ChangeRanks,
+}
+
+impl TribeRank {
+    pub const ALL: [TribeRank; 5] = [
+        Self::Initiate,
+        Self::Member,
+        Self::Recruiter,
+        Self::Officer,
+        Self::Leader,
+    ];
+
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(id as usize).copied()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Initiate => "Initiate",
+            Self::Member => "Member",
+            Self::Recruiter => "Recruiter",
+            Self::Officer => "Officer",
+            Self::Leader => "Leader",
+        }
+    }
+
+    pub fn can(self, permission: TribePermission) -> bool {
+        let required = match permission {
+            TribePermission::ChangeHouseMap => Self::Member,
+            TribePermission::Invite => Self::Recruiter,
+            TribePermission::Kick | TribePermission::ChangeMessage => Self::Officer,
+            TribePermission::ChangeRanks => Self::Leader,
+        };
+
+        self >= required
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TribePermission, TribeRank};
+
+    #[test]
+    fn rank_permissions() {
+        assert!(!TribeRank::Initiate.can(TribePermission::ChangeHouseMap));
+        assert!(TribeRank::Member.can(TribePermission::ChangeHouseMap));
+        assert!(!TribeRank::Member.can(TribePermission::Invite));
+        assert!(TribeRank::Recruiter.can(TribePermission::Invite));
+        assert!(!TribeRank::Recruiter.can(TribePermission::Kick));
+        assert!(TribeRank::Officer.can(TribePermission::ChangeMessage));
+        assert!(!TribeRank::Officer.can(TribePermission::ChangeRanks));
+        assert!(TribeRank::Leader.can(TribePermission::ChangeRanks));
+    }
+
+    #[test]
+    fn rank_ids() {
+        for rank in TribeRank::ALL {
+            assert_eq!(TribeRank::from_id(rank.id()), Some(rank));
+        }
+        assert_eq!(TribeRank::from_id(5), None);
+    }
+}
@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+//! Derive macros for `bitmice_utils::packet`, use them through the
+//! re-exports in that module.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Expr, Fields};
+
+/// Encodes and decodes every field of a struct in declaration order.
+#[proc_macro_derive(Field)]
+pub fn derive_field(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match impl_field(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Same as `Field`, also binding the struct to the tokens given by
+/// `#[packet(tokens = <expr>)]`.
+#[proc_macro_derive(Packet, attributes(packet))]
+pub fn derive_packet(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let field = match impl_field(&input) {
+        Ok(tokens) => tokens,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let tokens = match packet_tokens(&input) {
+        Ok(tokens) => tokens,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        #field
+
+        impl #impl_generics ::bitmice_utils::packet::Packet for #name #ty_generics #where_clause {
+            const TOKENS: (u8, u8) = #tokens;
+        }
+    }
+    .into()
+}
+
+fn impl_field(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(s) => &s.fields,
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "only structs can be encoded as packet fields",
+            ))
+        }
+    };
+
+    let (write, read) = match fields {
+        Fields::Named(named) => {
+            let idents = named
+                .named
+                .iter()
+                .map(|f| f.ident.as_ref().unwrap())
+                .collect::<Vec<_>>();
+
+            (
+                quote! { #( let data = ::bitmice_utils::packet::Field::write(&self.#idents, data); )* },
+                quote! { Self { #( #idents: ::bitmice_utils::packet::Field::read(data)?, )* } },
+            )
+        }
+        Fields::Unnamed(unnamed) => {
+            let indexes = (0..unnamed.unnamed.len())
+                .map(syn::Index::from)
+                .collect::<Vec<_>>();
+            let reads = indexes
+                .iter()
+                .map(|_| quote! { ::bitmice_utils::packet::Field::read(data)? });
+
+            (
+                quote! { #( let data = ::bitmice_utils::packet::Field::write(&self.#indexes, data); )* },
+                quote! { Self ( #( #reads, )* ) },
+            )
+        }
+        Fields::Unit => (quote! {}, quote! { Self }),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::bitmice_utils::packet::Field for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn write(&self, data: ::bitmice_utils::ByteArray) -> ::bitmice_utils::ByteArray {
+                #write
+                data
+            }
+
+            #[allow(unused_variables)]
+            fn read(
+                data: &mut ::bitmice_utils::ByteArray,
+            ) -> ::std::result::Result<Self, ::bitmice_utils::DecodeError> {
+                Ok(#read)
+            }
+        }
+    })
+}
+
+fn packet_tokens(input: &DeriveInput) -> Result<Expr, Error> {
+    let mut tokens = None;
+
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("packet")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tokens") {
+                tokens = Some(meta.value()?.parse::<Expr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `tokens = <expr>`"))
+            }
+        })?;
+    }
+
+    tokens.ok_or_else(|| {
+        Error::new(
+            input.ident.span(),
+            "missing `#[packet(tokens = <expr>)]` attribute",
+        )
+    })
+}
@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+/// Room type matching the rooms of any type.
+pub const ALL_ROOMS: &str = "all";
+
+/// An event running on the rooms of some types between two dates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub name: String,
+    pub enabled: bool,
+    /// Seconds since the unix epoch.
+    pub start_time: u64,
+    pub end_time: u64,
+    /// Types of the rooms of the event, `all` is any of them.
+    pub room_types: Vec<String>,
+    /// The rooms of the event play its own maps in place of their rotation.
+    pub has_specific_map: bool,
+}
+
+impl Event {
+    /// Whether the event runs at `now`, the end is excluded.
+    pub fn is_running(&self, now: u64) -> bool {
+        self.enabled && self.start_time <= now && now < self.end_time
+    }
+
+    pub fn is_for(&self, room_type: &str) -> bool {
+        self.room_types
+            .iter()
+            .any(|t| t == ALL_ROOMS || t == room_type)
+    }
+}
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use crate::Event;
+
+/// Something given to a player by an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reward {
+    /// Cheese on top of the one brought to the hole.
+    Cheese(u8),
+    /// Points on top of the ones of the place.
+    Score(u16),
+}
+
+/// What an event does besides its maps, every hook does nothing by default.
+pub trait Hooks: Send + Sync {
+    fn started(&self, _event: &Event) {}
+
+    fn ended(&self, _event: &Event) {}
+
+    /// Rewards of a player who entered the hole on `place` on a room of the
+    /// event.
+    fn rewards(&self, _event: &Event, _place: u8) -> Vec<Reward> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,10 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+mod event;
+mod hooks;
+mod scheduler;
+
+pub use event::Event;
+pub use hooks::{Hooks, Reward};
+pub use scheduler::{Change, Scheduler};
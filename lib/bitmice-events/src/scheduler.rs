@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{Event, Hooks, Reward};
+
+/// An event that started or ended on an update of the scheduler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Started(String),
+    Ended(String),
+}
+
+/// Starts and ends the events by their dates.
+#[derive(Default)]
+pub struct Scheduler {
+    events: Vec<Event>,
+    hooks: HashMap<String, Box<dyn Hooks>>,
+    running: HashSet<String>,
+}
+
+impl Scheduler {
+    /// The events are sorted by name, the first one running on a room wins.
+    pub fn new(mut events: Vec<Event>) -> Self {
+        events.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            events,
+            ..Default::default()
+        }
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn set_hooks(&mut self, name: &str, hooks: impl Hooks + 'static) {
+        self.hooks.insert(name.to_string(), Box::new(hooks));
+    }
+
+    /// Starts and ends the events by `now`, in seconds since the unix epoch.
+    pub fn update(&mut self, now: u64) -> Vec<Change> {
+        let mut changes = Vec::new();
+
+        for event in &self.events {
+            let is_running = event.is_running(now);
+            if is_running == self.running.contains(&event.name) {
+                continue;
+            }
+
+            let hooks = self.hooks.get(&event.name);
+            if is_running {
+                self.running.insert(event.name.clone());
+                if let Some(hooks) = hooks {
+                    hooks.started(event);
+                }
+                changes.push(Change::Started(event.name.clone()));
+            } else {
+                self.running.remove(&event.name);
+                if let Some(hooks) = hooks {
+                    hooks.ended(event);
+                }
+                changes.push(Change::Ended(event.name.clone()));
+            }
+        }
+
+        changes
+    }
+
+    pub fn running(&self) -> impl Iterator<Item = &Event> {
+        self.events
+            .iter()
+            .filter(|e| self.running.contains(&e.name))
+    }
+
+    /// The event running on the rooms of `room_type`.
+    pub fn room_event(&self, room_type: &str) -> Option<&Event> {
+        self.running().find(|e| e.is_for(room_type))
+    }
+
+    /// Rewards of the events running on a room of `room_type` for entering
+    /// the hole on `place`.
+    pub fn rewards(&self, room_type: &str, place: u8) -> Vec<Reward> {
+        self.running()
+            .filter(|e| e.is_for(room_type))
+            .filter_map(|e| Some(self.hooks.get(&e.name)?.rewards(e, place)))
+            .flatten()
+            .collect()
+    }
+}
+
+impl fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("events", &self.events)
+            .field("running", &self.running)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::{Change, Scheduler};
+    use crate::{Event, Hooks, Reward};
+
+    fn event(name: &str, start_time: u64, end_time: u64, room_types: &[&str]) -> Event {
+        Event {
+            name: name.to_string(),
+            enabled: true,
+            start_time,
+            end_time,
+            room_types: room_types.iter().map(|t| t.to_string()).collect(),
+            has_specific_map: true,
+        }
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        started: Arc<AtomicUsize>,
+    }
+
+    impl Hooks for Counter {
+        fn started(&self, _event: &Event) {
+            self.started.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn rewards(&self, _event: &Event, place: u8) -> Vec<Reward> {
+            match place {
+                1 => vec![Reward::Cheese(2), Reward::Score(5)],
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    #[test]
+    fn time_windows() {
+        let mut scheduler = Scheduler::new(vec![
+            event("fishing", 10, 20, &["all"]),
+            event("halloween", 15, 30, &["vanilla"]),
+        ]);
+
+        assert!(scheduler.update(5).is_empty());
+        assert_eq!(
+            scheduler.update(10),
+            vec![Change::Started(String::from("fishing"))]
+        );
+        assert!(scheduler.update(12).is_empty());
+        assert_eq!(
+            scheduler.update(20),
+            vec![
+                Change::Ended(String::from("fishing")),
+                Change::Started(String::from("halloween")),
+            ]
+        );
+        assert_eq!(
+            scheduler.update(40),
+            vec![Change::Ended(String::from("halloween"))]
+        );
+        assert_eq!(scheduler.running().count(), 0);
+    }
+
+    #[test]
+    fn disabled_events() {
+        let mut disabled = event("fishing", 0, 100, &["all"]);
+        disabled.enabled = false;
+        let mut scheduler = Scheduler::new(vec![disabled]);
+
+        assert!(scheduler.update(50).is_empty());
+        assert!(scheduler.room_event("vanilla").is_none());
+    }
+
+    #[test]
+    fn room_types() {
+        let mut scheduler = Scheduler::new(vec![
+            event("racing_cup", 0, 100, &["racing"]),
+            event("fishing", 0, 100, &["vanilla", "survivor"]),
+        ]);
+        scheduler.update(50);
+
+        assert_eq!(scheduler.room_event("racing").unwrap().name, "racing_cup");
+        assert_eq!(scheduler.room_event("survivor").unwrap().name, "fishing");
+        assert!(scheduler.room_event("bootcamp").is_none());
+    }
+
+    #[test]
+    fn hooks() {
+        let counter = Counter::default();
+        let started = Arc::clone(&counter.started);
+        let mut scheduler = Scheduler::new(vec![
+            event("fishing", 0, 100, &["all"]),
+            event("halloween", 0, 100, &["all"]),
+        ]);
+        scheduler.set_hooks("fishing", counter);
+
+        scheduler.update(50);
+        scheduler.update(60);
+        assert_eq!(started.load(Ordering::Relaxed), 1);
+
+        assert_eq!(
+            scheduler.rewards("vanilla", 1),
+            vec![Reward::Cheese(2), Reward::Score(5)]
+        );
+        assert!(scheduler.rewards("vanilla", 2).is_empty());
+    }
+}
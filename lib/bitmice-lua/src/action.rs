@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+/// Something a script asked for through `tfm.exec`, the server applies them
+/// on the room of the module once the call returns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Starts a new round on `map`, a code, an `@code` or a xml, as read by
+    /// the next map of a room. `-1` picks one of the rotation.
+    NewGame(String),
+    KillPlayer(String),
+    /// Sets the score of a player, or adds to it if `add` is set.
+    SetPlayerScore {
+        name: String,
+        score: i32,
+        add: bool,
+    },
+    /// Moves a player, by offsets of its current position and speed when the
+    /// offset flags are set.
+    MovePlayer {
+        name: String,
+        x: i16,
+        y: i16,
+        offset: bool,
+        speed_x: i16,
+        speed_y: i16,
+        speed_offset: bool,
+    },
+    /// Shows a text area to `player`, or to everyone on the room.
+    AddTextArea {
+        id: i32,
+        text: String,
+        player: Option<String>,
+        x: i16,
+        y: i16,
+        width: i16,
+        height: i16,
+        background_color: u32,
+        border_color: u32,
+        /// From 0 to 100.
+        background_alpha: u8,
+        fixed: bool,
+    },
+    UpdateTextArea {
+        id: i32,
+        text: String,
+        player: Option<String>,
+    },
+    RemoveTextArea {
+        id: i32,
+        player: Option<String>,
+    },
+    /// Shows a popup, `kind` 0 is a message, 1 a yes or no question and 2
+    /// asks for a text.
+    AddPopup {
+        id: i32,
+        kind: u8,
+        text: String,
+        player: Option<String>,
+        x: i16,
+        y: i16,
+        width: i16,
+        fixed: bool,
+    },
+    /// Shows `image` on `target`, which is `#object`, `$player`, `%player` or
+    /// a layer and its depth, `?depth`, `_depth`, `!depth`, `&depth` or
+    /// `:depth`.
+    AddImage {
+        id: i32,
+        image: String,
+        target: String,
+        x: i16,
+        y: i16,
+        player: Option<String>,
+    },
+    /// Text given to `print`.
+    Print(String),
+}
@@ -0,0 +1,470 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc, Mutex,
+    },
+    time::UNIX_EPOCH,
+};
+
+use mlua::{Function, Lua, MultiValue, Table, Value, Variadic};
+
+use crate::{Action, Player};
+
+/// Actions a single call can ask for, more fail the call.
+const MAX_ACTIONS: usize = 1000;
+/// Bytes of the strings built by `string.rep` and `table.concat`.
+const MAX_STRING_LENGTH: usize = 1024 * 1024;
+/// Steps a pattern can take to match a string, see [`pattern_cost`].
+const MAX_PATTERN_COST: u128 = 100_000_000;
+
+/// Actions asked by the running call, drained once it returns.
+pub(crate) type Actions = Arc<Mutex<Queue>>;
+
+#[derive(Debug, Default)]
+pub(crate) struct Queue {
+    pub actions: Vec<Action>,
+    /// The call asked for more than [`MAX_ACTIONS`], even if the script
+    /// caught the error.
+    pub overflowed: bool,
+}
+
+/// Name, position, position offset, speed and speed offset given to
+/// `tfm.exec.movePlayer`, only the name is required.
+type MoveArgs = (
+    String,
+    Option<f64>,
+    Option<f64>,
+    Option<bool>,
+    Option<f64>,
+    Option<f64>,
+    Option<bool>,
+);
+
+/// Id, text, player, position, size, background color, border color,
+/// background alpha and fixed position given to `ui.addTextArea`.
+type TextAreaArgs = (
+    i32,
+    String,
+    Option<String>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    Option<u32>,
+    Option<u32>,
+    Option<f64>,
+    Option<bool>,
+);
+
+/// Id, type, text, player, position, width and fixed position given to
+/// `ui.addPopup`.
+type PopupArgs = (
+    i32,
+    u8,
+    String,
+    Option<String>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    Option<bool>,
+);
+
+/// Image, target, position and player given to `tfm.exec.addImage`.
+type ImageArgs = (String, String, Option<f64>, Option<f64>, Option<String>);
+
+/// Globals of the base library that reach the file system or load code.
+const UNSAFE_GLOBALS: &[&str] = &["dofile", "loadfile", "load", "require"];
+
+/// Removes what a script must not reach from the standard libraries.
+pub(crate) fn sandbox(lua: &Lua) -> mlua::Result<()> {
+    let globals = lua.globals();
+    for name in UNSAFE_GLOBALS {
+        globals.raw_remove(*name)?;
+    }
+
+    let string: Table = globals.get("string")?;
+    string.raw_remove("dump")?;
+    limit_strings(lua, &string)?;
+
+    // only the clock of the os library, in milliseconds
+    let os = lua.create_table()?;
+    os.set(
+        "time",
+        lua.create_function(|_, ()| {
+            Ok(UNIX_EPOCH
+                .elapsed()
+                .map(|t| t.as_millis() as i64)
+                .unwrap_or_default())
+        })?,
+    )?;
+    globals.set("os", os)?;
+
+    Ok(())
+}
+
+/// The time limit is only checked while lua code runs, the functions of the
+/// standard library building or matching strings are capped before running.
+fn limit_strings(lua: &Lua, string: &Table) -> mlua::Result<()> {
+    let rep: Function = string.get("rep")?;
+    let rep = lua.create_registry_value(rep)?;
+    string.set(
+        "rep",
+        lua.create_function(
+            move |lua, (s, n, sep): (mlua::String, i64, Option<mlua::String>)| {
+                let n = n.max(0) as usize;
+                let sep_len = sep.as_ref().map(|s| s.as_bytes().len()).unwrap_or(0);
+                if (s.as_bytes().len() + sep_len).saturating_mul(n) > MAX_STRING_LENGTH {
+                    return Err(mlua::Error::runtime("string too long"));
+                }
+
+                lua.registry_value::<Function>(&rep)?
+                    .call::<_, mlua::String>((s, n, sep))
+            },
+        )?,
+    )?;
+
+    // the pattern is the second argument, `find` also takes a plain search
+    for name in ["find", "match", "gmatch", "gsub"] {
+        let function: Function = string.get(name)?;
+        let function = lua.create_registry_value(function)?;
+        string.set(
+            name,
+            lua.create_function(move |lua, args: Variadic<Value>| {
+                let plain =
+                    name == "find" && args.get(3).map(Value::as_boolean) == Some(Some(true));
+                if let (Some(Value::String(s)), Some(Value::String(pattern))) =
+                    (args.first(), args.get(1))
+                {
+                    if !plain
+                        && pattern_cost(s.as_bytes().len(), pattern.as_bytes()) > MAX_PATTERN_COST
+                    {
+                        return Err(mlua::Error::runtime("pattern too complex"));
+                    }
+                }
+
+                lua.registry_value::<Function>(&function)?
+                    .call::<_, MultiValue>(args)
+            })?,
+        )?;
+    }
+
+    let table: Table = lua.globals().get("table")?;
+    let concat: Function = table.get("concat")?;
+    let concat = lua.create_registry_value(concat)?;
+    table.set(
+        "concat",
+        lua.create_function(
+            move |lua, (list, sep, i, j): (Table, Option<mlua::String>, Option<i64>, Option<i64>)| {
+                let sep_len = sep.as_ref().map(|s| s.as_bytes().len()).unwrap_or(0);
+                let (i, j) = (i.unwrap_or(1), j.unwrap_or(list.raw_len() as i64));
+                let mut length = 0usize;
+                for index in i..=j {
+                    if let Value::String(s) = list.raw_get(index)? {
+                        length += s.as_bytes().len();
+                    }
+                    length += sep_len;
+                    if length > MAX_STRING_LENGTH {
+                        return Err(mlua::Error::runtime("string too long"));
+                    }
+                }
+
+                lua.registry_value::<Function>(&concat)?
+                    .call::<_, mlua::String>((list, sep, i, j))
+            },
+        )?,
+    )?;
+
+    Ok(())
+}
+
+/// Upper bound of the steps taken to match `pattern` on a string of `length`
+/// bytes. Each repeated item can backtrack over the whole string and the
+/// match is tried from every position.
+fn pattern_cost(length: usize, pattern: &[u8]) -> u128 {
+    let mut repeats = 0;
+    let mut i = 0;
+    while i < pattern.len() {
+        match pattern[i] {
+            b'%' => {
+                match pattern.get(i + 1) {
+                    // balanced match, scans the string
+                    Some(b'b') => {
+                        repeats += 1;
+                        i += 2;
+                    }
+                    Some(c) if c.is_ascii_digit() => repeats += 1,
+                    _ => {}
+                }
+                i += 1;
+            }
+            b'[' => {
+                // a set, `-` means a range inside of it
+                i += 1;
+                if pattern.get(i) == Some(&b'^') {
+                    i += 1;
+                }
+                if pattern.get(i) == Some(&b']') {
+                    i += 1;
+                }
+                while i < pattern.len() && pattern[i] != b']' {
+                    if pattern[i] == b'%' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'*' | b'+' | b'-' | b'?' => repeats += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    (length as u128 + 1).saturating_pow(repeats + 1)
+}
+
+/// Creates the `tfm` and `ui` tables and the `print` function.
+pub(crate) fn register(lua: &Lua, actions: &Actions) -> mlua::Result<()> {
+    let exec = lua.create_table()?;
+
+    let queue = Arc::clone(actions);
+    exec.set(
+        "newGame",
+        lua.create_function(move |_, map: Value| {
+            let map = match map {
+                Value::Nil => String::from("-1"),
+                Value::Integer(code) => code.to_string(),
+                Value::Number(code) => (code as i64).to_string(),
+                Value::String(map) => map.to_str()?.to_string(),
+                other => {
+                    return Err(mlua::Error::runtime(format!(
+                        "invalid map: {}",
+                        other.type_name()
+                    )))
+                }
+            };
+            push(&queue, Action::NewGame(map))?;
+
+            Ok(())
+        })?,
+    )?;
+
+    let queue = Arc::clone(actions);
+    exec.set(
+        "killPlayer",
+        lua.create_function(move |_, name: String| {
+            push(&queue, Action::KillPlayer(name))?;
+
+            Ok(())
+        })?,
+    )?;
+
+    let queue = Arc::clone(actions);
+    exec.set(
+        "setPlayerScore",
+        lua.create_function(move |_, (name, score, add): (String, i32, Option<bool>)| {
+            push(
+                &queue,
+                Action::SetPlayerScore {
+                    name,
+                    score,
+                    add: add.unwrap_or_default(),
+                },
+            )?;
+
+            Ok(())
+        })?,
+    )?;
+
+    let queue = Arc::clone(actions);
+    exec.set(
+        "movePlayer",
+        lua.create_function(
+            move |_, (name, x, y, offset, speed_x, speed_y, speed_offset): MoveArgs| {
+                push(
+                    &queue,
+                    Action::MovePlayer {
+                        name,
+                        x: coord(x, 0),
+                        y: coord(y, 0),
+                        offset: offset.unwrap_or_default(),
+                        speed_x: coord(speed_x, 0),
+                        speed_y: coord(speed_y, 0),
+                        speed_offset: speed_offset.unwrap_or_default(),
+                    },
+                )?;
+
+                Ok(())
+            },
+        )?,
+    )?;
+
+    // ids of the images, returned to the script
+    let last_image = Arc::new(AtomicI32::new(0));
+    let queue = Arc::clone(actions);
+    exec.set(
+        "addImage",
+        lua.create_function(move |_, (image, target, x, y, player): ImageArgs| {
+            let id = last_image.fetch_add(1, Ordering::Relaxed) + 1;
+            push(
+                &queue,
+                Action::AddImage {
+                    id,
+                    image,
+                    target,
+                    x: coord(x, 0),
+                    y: coord(y, 0),
+                    player,
+                },
+            )?;
+
+            Ok(id)
+        })?,
+    )?;
+
+    let ui = lua.create_table()?;
+
+    let queue = Arc::clone(actions);
+    ui.set(
+        "addTextArea",
+        lua.create_function(move |_, args: TextAreaArgs| {
+            let (id, text, player, x, y, width, height, background, border, alpha, fixed) = args;
+            push(
+                &queue,
+                Action::AddTextArea {
+                    id,
+                    text,
+                    player,
+                    x: coord(x, 50),
+                    y: coord(y, 50),
+                    width: coord(width, 0),
+                    height: coord(height, 0),
+                    background_color: background.unwrap_or(0x324650),
+                    border_color: border.unwrap_or_default(),
+                    background_alpha: (alpha.unwrap_or(1.0) * 100.0).clamp(0.0, 100.0) as u8,
+                    fixed: fixed.unwrap_or_default(),
+                },
+            )?;
+
+            Ok(())
+        })?,
+    )?;
+
+    let queue = Arc::clone(actions);
+    ui.set(
+        "updateTextArea",
+        lua.create_function(
+            move |_, (id, text, player): (i32, String, Option<String>)| {
+                push(&queue, Action::UpdateTextArea { id, text, player })?;
+
+                Ok(())
+            },
+        )?,
+    )?;
+
+    let queue = Arc::clone(actions);
+    ui.set(
+        "removeTextArea",
+        lua.create_function(move |_, (id, player): (i32, Option<String>)| {
+            push(&queue, Action::RemoveTextArea { id, player })?;
+
+            Ok(())
+        })?,
+    )?;
+
+    let queue = Arc::clone(actions);
+    ui.set(
+        "addPopup",
+        lua.create_function(
+            move |_, (id, kind, text, player, x, y, width, fixed): PopupArgs| {
+                if kind > 2 {
+                    return Err(mlua::Error::runtime(format!(
+                        "invalid popup type: {}",
+                        kind
+                    )));
+                }
+                push(
+                    &queue,
+                    Action::AddPopup {
+                        id,
+                        kind,
+                        text,
+                        player,
+                        x: coord(x, 50),
+                        y: coord(y, 50),
+                        width: coord(width, 0),
+                        fixed: fixed.unwrap_or_default(),
+                    },
+                )?;
+
+                Ok(())
+            },
+        )?,
+    )?;
+    lua.globals().set("ui", ui)?;
+
+    let room = lua.create_table()?;
+    room.set("playerList", lua.create_table()?)?;
+    let get = lua.create_table()?;
+    get.set("room", room)?;
+
+    let tfm = lua.create_table()?;
+    tfm.set("exec", exec)?;
+    tfm.set("get", get)?;
+    lua.globals().set("tfm", tfm)?;
+
+    let queue = Arc::clone(actions);
+    lua.globals().set(
+        "print",
+        lua.create_function(move |_, values: Variadic<Value>| {
+            let text = values
+                .iter()
+                .map(Value::to_string)
+                .collect::<mlua::Result<Vec<String>>>()?;
+            push(&queue, Action::Print(text.join("\t")))?;
+
+            Ok(())
+        })?,
+    )?;
+
+    Ok(())
+}
+
+/// Replaces `tfm.get.room.playerList`, keyed by the names of the players.
+pub(crate) fn set_players(lua: &Lua, players: &[Player]) -> mlua::Result<()> {
+    let list = lua.create_table()?;
+    for player in players {
+        let entry = lua.create_table()?;
+        entry.set("playerName", player.name.as_str())?;
+        entry.set("id", player.id)?;
+        entry.set("score", player.score)?;
+        entry.set("isDead", player.is_dead)?;
+        entry.set("isShaman", player.is_shaman)?;
+        entry.set("hasCheese", player.has_cheese)?;
+        list.set(player.name.as_str(), entry)?;
+    }
+
+    let tfm: Table = lua.globals().get("tfm")?;
+    let get: Table = tfm.get("get")?;
+    let room: Table = get.get("room")?;
+
+    room.set("playerList", list)
+}
+
+/// Positions and sizes come as any number.
+fn coord(value: Option<f64>, default: i16) -> i16 {
+    value.map(|v| v as i16).unwrap_or(default)
+}
+
+fn push(actions: &Actions, action: Action) -> mlua::Result<()> {
+    let mut queue = actions.lock().unwrap();
+    if queue.actions.len() >= MAX_ACTIONS {
+        queue.overflowed = true;
+        return Err(mlua::Error::runtime("too many actions"));
+    }
+
+    queue.actions.push(action);
+    Ok(())
+}
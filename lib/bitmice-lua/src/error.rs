@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ScriptError {
+    /// A single call of the script ran for longer than allowed.
+    TimeLimit,
+    /// The script allocated more memory than allowed.
+    MemoryLimit,
+    /// A single call of the script asked for too many actions.
+    ActionLimit,
+    Lua(mlua::Error),
+}
+
+impl ScriptError {
+    /// Whether the script broke one of its limits, such a module shouldn't
+    /// keep running.
+    pub fn is_limit(&self) -> bool {
+        matches!(self, Self::TimeLimit | Self::MemoryLimit)
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TimeLimit => write!(f, "time limit exceeded"),
+            Self::MemoryLimit => write!(f, "memory limit exceeded"),
+            Self::ActionLimit => write!(f, "action limit exceeded"),
+            Self::Lua(error) => write!(f, "lua error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Lua(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<mlua::Error> for ScriptError {
+    fn from(error: mlua::Error) -> Self {
+        // errors raised inside the api functions come wrapped
+        let mut cause = &error;
+        while let mlua::Error::CallbackError { cause: inner, .. } = cause {
+            cause = inner;
+        }
+
+        match cause {
+            mlua::Error::MemoryError(_) => Self::MemoryLimit,
+            _ => Self::Lua(error),
+        }
+    }
+}
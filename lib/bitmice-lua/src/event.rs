@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use mlua::{IntoLua, Lua, Value};
+
+/// Argument given to an event callback.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Arg {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+}
+
+impl From<&str> for Arg {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+impl From<String> for Arg {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl From<i64> for Arg {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<bool> for Arg {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl<'lua> IntoLua<'lua> for Arg {
+    fn into_lua(self, lua: &'lua Lua) -> mlua::Result<Value<'lua>> {
+        match self {
+            Self::Str(value) => value.into_lua(lua),
+            Self::Int(value) => Ok(Value::Integer(value)),
+            Self::Bool(value) => Ok(Value::Boolean(value)),
+        }
+    }
+}
+
+/// A player of the room, as seen on `tfm.get.room.playerList`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Player {
+    pub name: String,
+    pub id: u32,
+    pub score: i32,
+    pub is_dead: bool,
+    pub is_shaman: bool,
+    pub has_cheese: bool,
+}
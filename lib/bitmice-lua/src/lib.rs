@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+mod action;
+mod api;
+mod error;
+mod event;
+mod module;
+
+pub use action::Action;
+pub use error::ScriptError;
+pub use event::{Arg, Player};
+pub use module::{Limits, Module};
+
+pub type Result<T> = std::result::Result<T, ScriptError>;
@@ -0,0 +1,432 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use mlua::{HookTriggers, Lua, LuaOptions, StdLib, Value, Variadic};
+
+use crate::{
+    api::{self, Actions},
+    Action, Arg, Player, Result, ScriptError,
+};
+
+/// Instructions run between two checks of the time limit.
+const HOOK_INSTRUCTIONS: u32 = 1000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Bytes the script can allocate.
+    pub memory: usize,
+    /// Time a single call of the script can run for.
+    pub call_time: Duration,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            memory: 16 * 1024 * 1024,
+            call_time: Duration::from_millis(100),
+        }
+    }
+}
+
+/// A minigame script, running on its own sandboxed state. The coroutines are
+/// left out as the time limit can't follow them.
+pub struct Module {
+    name: String,
+    lua: Lua,
+    actions: Actions,
+    deadline: Arc<Mutex<Instant>>,
+    call_time: Duration,
+}
+
+impl Module {
+    /// Creates the sandbox of a module, its code is run by [`Module::exec`].
+    pub fn new(name: &str, limits: Limits) -> Result<Self> {
+        let lua = Lua::new_with(
+            StdLib::TABLE | StdLib::STRING | StdLib::MATH | StdLib::UTF8,
+            LuaOptions::new(),
+        )?;
+        let actions = Actions::default();
+        api::sandbox(&lua)?;
+        api::register(&lua, &actions)?;
+        lua.set_memory_limit(limits.memory)?;
+
+        let deadline = Arc::new(Mutex::new(Instant::now()));
+        let call_deadline = Arc::clone(&deadline);
+        lua.set_hook(
+            HookTriggers::new().every_nth_instruction(HOOK_INSTRUCTIONS),
+            move |_, _| {
+                if Instant::now() > *call_deadline.lock().unwrap() {
+                    return Err(mlua::Error::runtime("time limit exceeded"));
+                }
+
+                Ok(())
+            },
+        );
+
+        Ok(Self {
+            name: name.to_string(),
+            lua,
+            actions,
+            deadline,
+            call_time: limits.call_time,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Runs the code of the script, which usually defines its events.
+    pub fn exec(&self, source: &str) -> Result<Vec<Action>> {
+        let chunk = self.lua.load(source).set_name(self.name.as_str());
+
+        self.call(|| chunk.exec())
+    }
+
+    /// Calls the global function `event` if the script defined it, returning
+    /// the actions it asked for.
+    pub fn emit(&self, event: &str, args: Vec<Arg>) -> Result<Vec<Action>> {
+        match self.lua.globals().get(event)? {
+            Value::Function(callback) => {
+                self.call(|| callback.call::<_, ()>(Variadic::from_iter(args)))
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Updates the players seen by the script.
+    pub fn set_players(&self, players: &[Player]) -> Result<()> {
+        Ok(api::set_players(&self.lua, players)?)
+    }
+
+    /// Runs `f` under the time limit. The actions of a call that failed are
+    /// dropped.
+    fn call(&self, f: impl FnOnce() -> mlua::Result<()>) -> Result<Vec<Action>> {
+        let deadline = Instant::now() + self.call_time;
+        *self.deadline.lock().unwrap() = deadline;
+
+        let result = f();
+        let queue = std::mem::take(&mut *self.actions.lock().unwrap());
+        match result {
+            _ if queue.overflowed => Err(ScriptError::ActionLimit),
+            Ok(()) => Ok(queue.actions),
+            Err(_) if Instant::now() > deadline => Err(ScriptError::TimeLimit),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl fmt::Debug for Module {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Module")
+            .field("name", &self.name)
+            .field("call_time", &self.call_time)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Limits, Module};
+    use crate::{Action, Arg, Player, ScriptError};
+
+    fn module(source: &str) -> Module {
+        let module = Module::new("test", Limits::default()).unwrap();
+        module.exec(source).unwrap();
+
+        module
+    }
+
+    #[test]
+    fn exec_actions() {
+        let module = Module::new("test", Limits::default()).unwrap();
+        let actions = module
+            .exec(
+                r#"
+                tfm.exec.newGame("@123")
+                tfm.exec.newGame(7)
+                tfm.exec.newGame()
+                tfm.exec.killPlayer("Souris#0000")
+                tfm.exec.setPlayerScore("Souris#0000", 5, true)
+                tfm.exec.movePlayer("Souris#0000", 100.5, 200)
+                print("hello", 1, true)
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            actions,
+            vec![
+                Action::NewGame(String::from("@123")),
+                Action::NewGame(String::from("7")),
+                Action::NewGame(String::from("-1")),
+                Action::KillPlayer(String::from("Souris#0000")),
+                Action::SetPlayerScore {
+                    name: String::from("Souris#0000"),
+                    score: 5,
+                    add: true,
+                },
+                Action::MovePlayer {
+                    name: String::from("Souris#0000"),
+                    x: 100,
+                    y: 200,
+                    offset: false,
+                    speed_x: 0,
+                    speed_y: 0,
+                    speed_offset: false,
+                },
+                Action::Print(String::from("hello\t1\ttrue")),
+            ]
+        );
+    }
+
+    #[test]
+    fn ui_actions() {
+        let module = Module::new("test", Limits::default()).unwrap();
+        let actions = module
+            .exec(
+                r#"
+                ui.addTextArea(1, "<a href='event:play'>Play</a>", nil, 10, 20, 100, 50, 0, 0, 0.5)
+                ui.updateTextArea(1, "Playing", "Souris#0000")
+                ui.removeTextArea(1)
+                ui.addPopup(2, 1, "Ready?", "Souris#0000")
+                assert(tfm.exec.addImage("a.png", "&1", 5, 5) == 1)
+                assert(tfm.exec.addImage("b.png", "$Souris#0000") == 2)
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(actions.len(), 6);
+        assert_eq!(
+            actions[0],
+            Action::AddTextArea {
+                id: 1,
+                text: String::from("<a href='event:play'>Play</a>"),
+                player: None,
+                x: 10,
+                y: 20,
+                width: 100,
+                height: 50,
+                background_color: 0,
+                border_color: 0,
+                background_alpha: 50,
+                fixed: false,
+            }
+        );
+        assert_eq!(
+            actions[3],
+            Action::AddPopup {
+                id: 2,
+                kind: 1,
+                text: String::from("Ready?"),
+                player: Some(String::from("Souris#0000")),
+                x: 50,
+                y: 50,
+                width: 0,
+                fixed: false,
+            }
+        );
+        assert!(matches!(&actions[5], Action::AddImage { id: 2, x: 0, .. }));
+
+        assert!(module.exec("ui.addPopup(3, 7, 'Invalid')").is_err());
+    }
+
+    #[test]
+    fn emit_events() {
+        let module = module(
+            r#"
+            function eventChatCommand(name, command)
+                if command == "kill" then
+                    tfm.exec.killPlayer(name)
+                end
+            end
+            "#,
+        );
+
+        let actions = module
+            .emit(
+                "eventChatCommand",
+                vec!["Souris#0000".into(), "kill".into()],
+            )
+            .unwrap();
+        assert_eq!(
+            actions,
+            vec![Action::KillPlayer(String::from("Souris#0000"))]
+        );
+
+        // events the script doesn't handle
+        assert!(module
+            .emit("eventLoop", vec![Arg::Int(500), Arg::Int(1000)])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn player_list() {
+        let module = module(
+            r#"
+            function eventNewGame()
+                for name, player in pairs(tfm.get.room.playerList) do
+                    if not player.isDead then
+                        tfm.exec.setPlayerScore(name, player.score + 1)
+                    end
+                end
+            end
+            "#,
+        );
+        module
+            .set_players(&[
+                Player {
+                    name: String::from("Alive#0000"),
+                    score: 2,
+                    ..Default::default()
+                },
+                Player {
+                    name: String::from("Dead#0000"),
+                    is_dead: true,
+                    ..Default::default()
+                },
+            ])
+            .unwrap();
+
+        assert_eq!(
+            module.emit("eventNewGame", Vec::new()).unwrap(),
+            vec![Action::SetPlayerScore {
+                name: String::from("Alive#0000"),
+                score: 3,
+                add: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn sandboxed() {
+        let module = module(
+            r#"
+            function eventLoop()
+                assert(dofile == nil and loadfile == nil and load == nil)
+                assert(require == nil and io == nil and debug == nil)
+                assert(string.dump == nil and os.execute == nil)
+                assert(os.time() > 0)
+            end
+            "#,
+        );
+
+        assert!(module.emit("eventLoop", Vec::new()).is_ok());
+    }
+
+    #[test]
+    fn errors_drop_actions() {
+        let module = module(
+            r#"
+            function eventLoop()
+                tfm.exec.newGame(0)
+                error("broken")
+            end
+            "#,
+        );
+
+        assert!(matches!(
+            module.emit("eventLoop", Vec::new()),
+            Err(ScriptError::Lua(_))
+        ));
+        assert_eq!(
+            module.exec("tfm.exec.killPlayer('Souris#0000')").unwrap(),
+            vec![Action::KillPlayer(String::from("Souris#0000"))]
+        );
+    }
+
+    #[test]
+    fn time_limit() {
+        let limits = Limits {
+            call_time: Duration::from_millis(20),
+            ..Default::default()
+        };
+        let module = Module::new("test", limits).unwrap();
+
+        let error = module.exec("while true do end").unwrap_err();
+        assert!(matches!(error, ScriptError::TimeLimit));
+        assert!(error.is_limit());
+
+        // the next calls have their own time
+        assert!(module
+            .exec("local x = 0 for i = 1, 1000 do x = x + i end")
+            .is_ok());
+    }
+
+    #[test]
+    fn string_limits() {
+        let module = module("");
+
+        assert!(module
+            .exec("assert(('ab'):rep(3, ',') == 'ab,ab,ab')")
+            .is_ok());
+        assert!(module.exec("string.rep('x', 1e9)").is_err());
+
+        assert!(module
+            .exec("assert(('a.b'):find('.', 1, true) == 2)")
+            .is_ok());
+        assert!(module
+            .exec("assert(string.gsub('hello', 'l+', 'L') == 'heLo')")
+            .is_ok());
+        let error = module
+            .exec("string.find(string.rep('a', 5000), '.-.-.-.-b')")
+            .unwrap_err();
+        assert!(error.to_string().contains("pattern too complex"));
+        // searching the pattern as plain text is fine
+        assert!(module
+            .exec("string.find(string.rep('a', 5000), '.-.-.-.-b', 1, true)")
+            .is_ok());
+
+        assert!(module
+            .exec("assert(table.concat({'a', 'b', 'c'}, '-') == 'a-b-c')")
+            .is_ok());
+        assert!(module
+            .exec(
+                "local t = {} for i = 1, 100 do t[i] = string.rep('x', 20000) end table.concat(t)"
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn action_limit() {
+        let module = module("");
+
+        assert_eq!(
+            module
+                .exec("for i = 1, 1000 do tfm.exec.killPlayer('Souris#0000') end")
+                .unwrap()
+                .len(),
+            1000
+        );
+
+        // even when the script catches the error
+        let error = module
+            .exec("for i = 1, 1001 do pcall(tfm.exec.killPlayer, 'Souris#0000') end")
+            .unwrap_err();
+        assert!(matches!(error, ScriptError::ActionLimit));
+        assert!(module.exec("tfm.exec.killPlayer('Souris#0000')").is_ok());
+    }
+
+    #[test]
+    fn memory_limit() {
+        let limits = Limits {
+            memory: 1024 * 1024,
+            ..Default::default()
+        };
+        let module = Module::new("test", limits).unwrap();
+
+        let error = module
+            .exec("local t = {} for i = 1, 1000000 do t[i] = tostring(i) end")
+            .unwrap_err();
+        assert!(matches!(error, ScriptError::MemoryLimit));
+    }
+}
@@ -4,7 +4,9 @@
 mod read;
 mod write;
 
-#[derive(Clone, Default)]
+use std::fmt;
+
+#[derive(Clone, Default, PartialEq, Eq)]
 pub struct ByteArray {
     pub(crate) bytes: Vec<u8>,
 }
@@ -34,40 +36,83 @@ impl ByteArray {
         std::str::from_utf8(&self.bytes).unwrap()
     }
 
+    pub fn try_as_str(&self) -> Result<&str, DecodeError> {
+        std::str::from_utf8(&self.bytes).map_err(|_| DecodeError::InvalidUtf8)
+    }
+
     pub fn to_vec(self) -> Vec<u8> {
         self.bytes
     }
 }
 
-impl ToString for ByteArray {
-    fn to_string(&self) -> String {
-        self.bytes.iter().map(|b| format!(r#"\u{:x}"#, b)).collect()
+impl fmt::Display for ByteArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in self.bytes.iter() {
+            write!(f, r#"\u{:x}"#, b)?;
+        }
+
+        Ok(())
     }
 }
 
-impl Into<ByteArray> for &[u8] {
-    fn into(self) -> ByteArray {
-        ByteArray {
-            bytes: self.to_owned(),
+impl From<&[u8]> for ByteArray {
+    fn from(bytes: &[u8]) -> Self {
+        Self {
+            bytes: bytes.to_owned(),
         }
     }
 }
 
-impl Into<ByteArray> for Vec<u8> {
-    fn into(self) -> ByteArray {
-        ByteArray { bytes: self }
+impl From<Vec<u8>> for ByteArray {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self { bytes }
     }
 }
 
 impl std::fmt::Debug for ByteArray {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "ByteArray [{}]", self.to_string())
+        write!(f, "ByteArray [{}]", self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidUtf8,
+    OutOfRange {
+        start: usize,
+        end: usize,
+        len: usize,
+    },
+    UnexpectedEof {
+        needed: usize,
+        remaining: usize,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidUtf8 => write!(f, "invalid utf-8 data"),
+            Self::OutOfRange { start, end, len } => write!(
+                f,
+                "range {}..{} is out of bounds for {} bytes",
+                start, end, len
+            ),
+            Self::UnexpectedEof { needed, remaining } => write!(
+                f,
+                "unexpected end of data, needed {} bytes but {} remain",
+                needed, remaining
+            ),
+        }
     }
 }
 
+impl std::error::Error for DecodeError {}
+
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
-    use super::ByteArray;
+    use super::{ByteArray, DecodeError};
 
     #[test]
     fn clear_and_is_empty() {
@@ -167,4 +212,74 @@ mod tests {
         assert_eq!(bytearray.read_utf(), "BitMice");
         assert_eq!(bytearray.len(), 0);
     }
+
+    #[test]
+    fn try_read_short_data() {
+        let mut bytearray = ByteArray::default().write_u8(1);
+
+        assert_eq!(
+            bytearray.try_read_u16(),
+            Err(DecodeError::UnexpectedEof {
+                needed: 2,
+                remaining: 1
+            })
+        );
+        // nothing is consumed on errors
+        assert_eq!(bytearray.len(), 1);
+        assert_eq!(bytearray.try_read_u8(), Ok(1));
+        assert!(bytearray.try_read_bool().is_err());
+    }
+
+    #[test]
+    fn try_read_short_utf() {
+        let mut bytearray = ByteArray::default().write_u16(10).write_u8(b'a');
+
+        assert_eq!(
+            bytearray.try_read_utf(),
+            Err(DecodeError::UnexpectedEof {
+                needed: 10,
+                remaining: 1
+            })
+        );
+        assert_eq!(bytearray.len(), 3);
+
+        // the infallible read consumes it all
+        assert_eq!(bytearray.read_utf(), format!("a{}", "\0".repeat(9)));
+        assert!(bytearray.is_empty());
+    }
+
+    #[test]
+    fn try_read_out_of_range() {
+        let mut bytearray = ByteArray::with(vec![1, 2, 3]);
+
+        assert_eq!(bytearray.try_read(2).unwrap().as_bytes(), &[1, 2]);
+        assert_eq!(
+            bytearray.try_offset(2, 5),
+            Err(DecodeError::OutOfRange {
+                start: 2,
+                end: 5,
+                len: 3
+            })
+        );
+        assert_eq!(
+            bytearray.try_offset(3, 1),
+            Err(DecodeError::OutOfRange {
+                start: 3,
+                end: 1,
+                len: 3
+            })
+        );
+    }
+
+    #[test]
+    fn try_as_str() {
+        assert_eq!(
+            ByteArray::with(b"BitMice".to_vec()).try_as_str(),
+            Ok("BitMice")
+        );
+        assert_eq!(
+            ByteArray::with(vec![0xff, 0xfe]).try_as_str(),
+            Err(DecodeError::InvalidUtf8)
+        );
+    }
 }
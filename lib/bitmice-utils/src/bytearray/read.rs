@@ -1,9 +1,7 @@
 // SPDX-License-Identifier: BSD-3-Clause
 // Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
 
-use bytes::Buf;
-
-use super::ByteArray;
+use super::{ByteArray, DecodeError};
 
 impl ByteArray {
     pub fn offset(&mut self, start: usize, end: usize) -> Self {
@@ -19,139 +17,160 @@ impl ByteArray {
     }
 
     pub fn read_i8(&mut self) -> i8 {
-        if self.len() < 1 {
-            return 0;
-        }
-
-        let mut bytes_slice = self.bytes.as_slice();
-        let byte = bytes_slice.get_i8();
-        self.bytes = bytes_slice.to_owned();
-
-        byte
+        self.try_read_i8().unwrap_or(0)
     }
 
     pub fn read_u8(&mut self) -> u8 {
-        if self.len() < 1 {
-            return 0;
-        }
-
-        let mut bytes_slice = self.bytes.as_slice();
-        let byte = bytes_slice.get_u8();
-        self.bytes = bytes_slice.to_owned();
-
-        byte
+        self.try_read_u8().unwrap_or(0)
     }
 
     pub fn read_i16(&mut self) -> i16 {
-        if self.len() < 2 {
-            return 0;
-        }
+        self.try_read_i16().unwrap_or(0)
+    }
 
-        let mut bytes_slice = self.bytes.as_slice();
-        let short = bytes_slice.get_i16();
-        self.bytes = bytes_slice.to_owned();
+    pub fn read_u16(&mut self) -> u16 {
+        self.try_read_u16().unwrap_or(0)
+    }
 
-        short
+    pub fn read_i32(&mut self) -> i32 {
+        self.try_read_i32().unwrap_or(0)
     }
 
-    pub fn read_u16(&mut self) -> u16 {
-        if self.len() < 2 {
-            return 0;
-        }
+    pub fn read_u32(&mut self) -> u32 {
+        self.try_read_u32().unwrap_or(0)
+    }
 
-        let mut bytes_slice = self.bytes.as_slice();
-        let short = bytes_slice.get_u16();
-        self.bytes = bytes_slice.to_owned();
+    pub fn read_i64(&mut self) -> i64 {
+        self.try_read_i64().unwrap_or(0)
+    }
 
-        short
+    pub fn read_u64(&mut self) -> u64 {
+        self.try_read_u64().unwrap_or(0)
     }
 
-    pub fn read_i32(&mut self) -> i32 {
-        if self.len() < 4 {
-            return 0;
-        }
+    pub fn read_i128(&mut self) -> i128 {
+        self.try_read_i128().unwrap_or(0)
+    }
 
-        let mut bytes_slice = self.bytes.as_slice();
-        let int = bytes_slice.get_i32();
-        self.bytes = bytes_slice.to_owned();
+    pub fn read_u128(&mut self) -> u128 {
+        self.try_read_u128().unwrap_or(0)
+    }
 
-        int
+    pub fn read_bool(&mut self) -> bool {
+        self.read_u8() != 0
     }
 
-    pub fn read_u32(&mut self) -> u32 {
-        if self.len() < 4 {
-            return 0;
+    /// The length is consumed even if the data is shorter, the missing bytes
+    /// are read as zeros.
+    pub fn read_utf(&mut self) -> String {
+        if self.len() < 2 {
+            return String::new();
         }
 
-        let mut bytes_slice = self.bytes.as_slice();
-        let int = bytes_slice.get_u32();
-        self.bytes = bytes_slice.to_owned();
+        let length = self.read_u16();
+
+        let mut utf_bytes = Vec::new();
+        (0..length).for_each(|_| utf_bytes.push(self.read_u8()));
 
-        int
+        String::from_utf8_lossy(&utf_bytes).to_string()
     }
 
-    pub fn read_i64(&mut self) -> i64 {
-        if self.len() < 8 {
-            return 0;
+    /// Same as [`ByteArray::offset`], but fails instead of panicking when the
+    /// range is out of bounds.
+    pub fn try_offset(&mut self, start: usize, end: usize) -> Result<Self, DecodeError> {
+        match self.bytes.get(start..end) {
+            Some(bytes) => Ok(Self {
+                bytes: bytes.to_vec(),
+            }),
+            None => Err(DecodeError::OutOfRange {
+                start,
+                end,
+                len: self.len(),
+            }),
         }
+    }
 
-        let mut bytes_slice = self.bytes.as_slice();
-        let long = bytes_slice.get_i64();
-        self.bytes = bytes_slice.to_owned();
+    /// Same as [`ByteArray::read`], the bytes are copied without being consumed.
+    pub fn try_read(&mut self, length: usize) -> Result<Self, DecodeError> {
+        self.try_offset(0, length)
+    }
 
-        long
+    pub fn try_read_i8(&mut self) -> Result<i8, DecodeError> {
+        Ok(i8::from_be_bytes(self.take()?))
     }
 
-    pub fn read_u64(&mut self) -> u64 {
-        if self.len() < 8 {
-            return 0;
-        }
+    pub fn try_read_u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(u8::from_be_bytes(self.take()?))
+    }
 
-        let mut bytes_slice = self.bytes.as_slice();
-        let long = bytes_slice.get_u64();
-        self.bytes = bytes_slice.to_owned();
+    pub fn try_read_i16(&mut self) -> Result<i16, DecodeError> {
+        Ok(i16::from_be_bytes(self.take()?))
+    }
 
-        long
+    pub fn try_read_u16(&mut self) -> Result<u16, DecodeError> {
+        Ok(u16::from_be_bytes(self.take()?))
     }
 
-    pub fn read_i128(&mut self) -> i128 {
-        if self.len() < 16 {
-            return 0;
-        }
+    pub fn try_read_i32(&mut self) -> Result<i32, DecodeError> {
+        Ok(i32::from_be_bytes(self.take()?))
+    }
 
-        let mut bytes_slice = self.bytes.as_slice();
-        let long = bytes_slice.get_i128();
-        self.bytes = bytes_slice.to_owned();
+    pub fn try_read_u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_be_bytes(self.take()?))
+    }
 
-        long
+    pub fn try_read_i64(&mut self) -> Result<i64, DecodeError> {
+        Ok(i64::from_be_bytes(self.take()?))
     }
 
-    pub fn read_u128(&mut self) -> u128 {
-        if self.len() < 16 {
-            return 0;
-        }
+    pub fn try_read_u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_be_bytes(self.take()?))
+    }
 
-        let mut bytes_slice = self.bytes.as_slice();
-        let long = bytes_slice.get_u128();
-        self.bytes = bytes_slice.to_owned();
+    pub fn try_read_i128(&mut self) -> Result<i128, DecodeError> {
+        Ok(i128::from_be_bytes(self.take()?))
+    }
 
-        long
+    pub fn try_read_u128(&mut self) -> Result<u128, DecodeError> {
+        Ok(u128::from_be_bytes(self.take()?))
     }
 
-    pub fn read_bool(&mut self) -> bool {
-        !(self.read_u8() == 0)
+    pub fn try_read_bool(&mut self) -> Result<bool, DecodeError> {
+        Ok(self.try_read_u8()? != 0)
     }
 
-    pub fn read_utf(&mut self) -> String {
-        if self.len() < 2 {
-            return String::new();
+    /// Invalid UTF-8 sequences are replaced, the client encodes some characters
+    /// in a way that isn't valid UTF-8.
+    pub fn try_read_utf(&mut self) -> Result<String, DecodeError> {
+        let length = self.try_read_u16()? as usize;
+        if self.len() < length {
+            let remaining = self.len();
+            // give the length back, nothing is consumed on errors
+            self.bytes.splice(0..0, (length as u16).to_be_bytes());
+
+            return Err(DecodeError::UnexpectedEof {
+                needed: length,
+                remaining,
+            });
         }
 
-        let length = self.read_u16();
+        let utf_bytes = self.bytes.drain(..length).collect::<Vec<u8>>();
+        Ok(String::from_utf8_lossy(&utf_bytes).to_string())
+    }
 
-        let mut utf_bytes = Vec::new();
-        (0..length).for_each(|_| utf_bytes.push(self.read_u8()));
+    /// Consumes the next `N` bytes, nothing is consumed if there aren't enough bytes.
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        if self.len() < N {
+            return Err(DecodeError::UnexpectedEof {
+                needed: N,
+                remaining: self.len(),
+            });
+        }
 
-        String::from_utf8_lossy(&utf_bytes).to_string()
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(&self.bytes[..N]);
+        self.bytes.drain(..N);
+
+        Ok(bytes)
     }
 }
@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+//! Ciphers used by the client on sensitive packets.
+//!
+//! Keys are derived from the `packet_keys` of the protocol and a name
+//! ("identification" for the login, "msg" for the others), using the 32-bit
+//! integer arithmetic of the client.
+
+/// Amount of keys derived by [`compute_keys`].
+pub const KEYS_COUNT: usize = 20;
+
+const DELTA: u32 = 0x9E3779B9;
+
+pub fn compute_keys(packet_keys: &[i32], key: &[u8]) -> Vec<i32> {
+    let mut num: i32 = 5381;
+
+    if !key.is_empty() {
+        for (i, packet_key) in packet_keys.iter().enumerate() {
+            num = (num << 5)
+                .wrapping_add(num)
+                .wrapping_add(*packet_key)
+                .wrapping_add(key[i % key.len()] as i32);
+        }
+    }
+
+    (0..KEYS_COUNT)
+        .map(|_| {
+            num ^= num << 13;
+            num ^= num >> 17;
+            num ^= num << 5;
+            num
+        })
+        .collect()
+}
+
+/// Decrypts, in place, the chunks of the login packet (XXTEA).
+pub fn decode_chunks(chunks: &mut [i32], keys: &[i32]) {
+    let n = chunks.len();
+    if n < 2 {
+        return;
+    }
+
+    let key = xxtea_key(keys);
+    let mut v = chunks.iter().map(|c| *c as u32).collect::<Vec<u32>>();
+
+    let mut rounds = 6 + 52 / n;
+    let mut sum = (rounds as u32).wrapping_mul(DELTA);
+    let mut y = v[0];
+    while rounds > 0 {
+        let e = ((sum >> 2) & 3) as usize;
+        for p in (1..n).rev() {
+            let z = v[p - 1];
+            v[p] = v[p].wrapping_sub(mx(sum, y, z, p, e, &key));
+            y = v[p];
+        }
+
+        let z = v[n - 1];
+        v[0] = v[0].wrapping_sub(mx(sum, y, z, 0, e, &key));
+        y = v[0];
+
+        sum = sum.wrapping_sub(DELTA);
+        rounds -= 1;
+    }
+
+    for (chunk, value) in chunks.iter_mut().zip(v) {
+        *chunk = value as i32;
+    }
+}
+
+/// Encrypts, in place, chunks the way the client does on login (XXTEA).
+pub fn encode_chunks(chunks: &mut [i32], keys: &[i32]) {
+    let n = chunks.len();
+    if n < 2 {
+        return;
+    }
+
+    let key = xxtea_key(keys);
+    let mut v = chunks.iter().map(|c| *c as u32).collect::<Vec<u32>>();
+
+    let mut rounds = 6 + 52 / n;
+    let mut sum: u32 = 0;
+    let mut z = v[n - 1];
+    while rounds > 0 {
+        sum = sum.wrapping_add(DELTA);
+        let e = ((sum >> 2) & 3) as usize;
+        for p in 0..n - 1 {
+            let y = v[p + 1];
+            v[p] = v[p].wrapping_add(mx(sum, y, z, p, e, &key));
+            z = v[p];
+        }
+
+        let y = v[0];
+        v[n - 1] = v[n - 1].wrapping_add(mx(sum, y, z, n - 1, e, &key));
+        z = v[n - 1];
+
+        rounds -= 1;
+    }
+
+    for (chunk, value) in chunks.iter_mut().zip(v) {
+        *chunk = value as i32;
+    }
+}
+
+/// XOR cipher of the other encrypted packets, applying it twice gives the
+/// original data back. The index of the key wraps as a byte, like the client.
+pub fn xor_cipher(data: &[u8], packet_id: u8, keys: &[i32]) -> Vec<u8> {
+    if keys.is_empty() {
+        return data.to_vec();
+    }
+
+    data.iter()
+        .enumerate()
+        .map(|(i, byte)| byte ^ keys[packet_id.wrapping_add(i as u8) as usize % keys.len()] as u8)
+        .collect()
+}
+
+fn xxtea_key(keys: &[i32]) -> [u32; 4] {
+    let mut key = [0u32; 4];
+    for (k, value) in key.iter_mut().zip(keys) {
+        *k = *value as u32;
+    }
+
+    key
+}
+
+fn mx(sum: u32, y: u32, z: u32, p: usize, e: usize, key: &[u32; 4]) -> u32 {
+    (((z >> 5) ^ (y << 2)).wrapping_add((y >> 3) ^ (z << 4)))
+        ^ ((sum ^ y).wrapping_add(key[(p & 3) ^ e] ^ z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compute_keys, decode_chunks, encode_chunks, xor_cipher, KEYS_COUNT};
+
+    // packet keys of assets/config.toml
+    const PACKET_KEYS: [i32; 20] = [
+        13, 16, 42, 55, 40, 23, 19, 43, 11, 55, 87, 74, 116, 105, 114, 77, 117, 77, 97, 93,
+    ];
+
+    #[test]
+    fn xxtea_test_vectors() {
+        let mut chunks = [0, 0];
+        encode_chunks(&mut chunks, &[0, 0, 0, 0]);
+        assert_eq!(chunks, [0x053704AB, 0x575D8C80u32 as i32]);
+
+        let key = [
+            0x01234567,
+            0x89ABCDEFu32 as i32,
+            0xFEDCBA98u32 as i32,
+            0x76543210,
+        ];
+        let mut chunks = [1, 2, 3, 4];
+        encode_chunks(&mut chunks, &key);
+        assert_eq!(chunks, [0x5EA4307C, 0x521975D1, 0x2722AAED, 0x3DC8276D]);
+
+        decode_chunks(&mut chunks, &key);
+        assert_eq!(chunks, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn derived_keys() {
+        let keys = compute_keys(&PACKET_KEYS, b"identification");
+        assert_eq!(keys.len(), KEYS_COUNT);
+        assert_eq!(
+            &keys[..4],
+            &[1573821493, 1195940145, -641928250, -1085926236]
+        );
+
+        let keys = compute_keys(&PACKET_KEYS, b"msg");
+        assert_eq!(&keys[..4], &[262158565, 523176923, 886639313, -1511579120]);
+
+        // no packet keys, only the seed is shuffled
+        assert_eq!(compute_keys(&[], b"msg")[0], 1454784245);
+    }
+
+    #[test]
+    fn login_chunks() {
+        let keys = compute_keys(&PACKET_KEYS, b"identification");
+        let original = vec![0x0005416E, 0x64726965, 0x6C000000, 0x00001234];
+
+        let mut chunks = original.clone();
+        encode_chunks(&mut chunks, &keys);
+        assert_ne!(chunks, original);
+
+        decode_chunks(&mut chunks, &keys);
+        assert_eq!(chunks, original);
+
+        // a single chunk can't be encrypted
+        let mut chunks = [7];
+        decode_chunks(&mut chunks, &keys);
+        assert_eq!(chunks, [7]);
+    }
+
+    #[test]
+    fn xor() {
+        let keys = compute_keys(&PACKET_KEYS, b"msg");
+        let data = b"\x00\x05/room".to_vec();
+
+        let encrypted = xor_cipher(&data, 3, &keys);
+        assert_eq!(encrypted[0], keys[3] as u8);
+        assert_eq!(encrypted[1], 5 ^ keys[4] as u8);
+        assert_eq!(xor_cipher(&encrypted, 3, &keys), data);
+
+        // the index wraps as a byte before picking the key
+        let encrypted = xor_cipher(&data, 255, &keys);
+        assert_eq!(encrypted[0], keys[255 % KEYS_COUNT] as u8);
+        assert_eq!(encrypted[1], 5 ^ keys[0] as u8);
+
+        let data = vec![0; 257];
+        let encrypted = xor_cipher(&data, 0, &keys);
+        assert_eq!(encrypted[255], keys[255 % KEYS_COUNT] as u8);
+        assert_eq!(encrypted[256], keys[0] as u8);
+    }
+}
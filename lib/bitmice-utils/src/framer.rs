@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+//! Splits the TCP stream sent by the client into packets.
+//!
+//! Every packet starts with its length encoded in groups of 7 bits (up to 5
+//! bytes, least significant group first), followed by the packet id and then
+//! `length` bytes holding the tokens and the packet data.
+
+use std::fmt;
+
+use crate::ByteArray;
+
+pub const DEFAULT_MAX_PACKET_SIZE: usize = 2 * 1024 * 1024; // 2 MiB
+
+const MAX_LENGTH_BYTES: usize = 5;
+
+#[derive(Debug)]
+pub struct Framer {
+    buffer: Vec<u8>,
+    max_packet_size: usize,
+}
+
+impl Framer {
+    pub fn new(max_packet_size: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            max_packet_size,
+        }
+    }
+
+    /// Appends bytes received from the stream.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Bytes waiting for the rest of their packet.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Takes the next complete packet (packet id, tokens and data) from the
+    /// buffer, `None` means that more bytes are needed.
+    pub fn next_packet(&mut self) -> Result<Option<ByteArray>, FrameError> {
+        let mut length: usize = 0;
+        let mut header_size = 0;
+
+        loop {
+            let byte = match self.buffer.get(header_size) {
+                Some(b) => *b,
+                None => return Ok(None),
+            };
+
+            length |= ((byte & 127) as usize) << (7 * header_size);
+            header_size += 1;
+
+            if byte & 128 == 0 {
+                break;
+            } else if header_size >= MAX_LENGTH_BYTES {
+                return Err(FrameError::InvalidLength);
+            }
+        }
+
+        // packet id
+        let size = length + 1;
+        if size > self.max_packet_size {
+            return Err(FrameError::TooLarge {
+                size,
+                max_size: self.max_packet_size,
+            });
+        }
+
+        if self.buffer.len() < header_size + size {
+            return Ok(None);
+        }
+
+        let packet = self.buffer[header_size..header_size + size].to_vec();
+        self.buffer.drain(..header_size + size);
+
+        Ok(Some(ByteArray::with(packet)))
+    }
+}
+
+impl Default for Framer {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_PACKET_SIZE)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FrameError {
+    InvalidLength,
+    TooLarge { size: usize, max_size: usize },
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength => write!(f, "invalid packet length"),
+            Self::TooLarge { size, max_size } => write!(
+                f,
+                "packet of {} bytes exceeds the maximum of {} bytes",
+                size, max_size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{FrameError, Framer};
+
+    fn frame(packet_id: u8, tokens: (u8, u8), data: &[u8]) -> Vec<u8> {
+        let mut length = data.len() + 2;
+        let mut bytes = Vec::new();
+        while length >> 7 != 0 {
+            bytes.push(((length & 127) | 128) as u8);
+            length >>= 7;
+        }
+        bytes.push(length as u8);
+
+        bytes.extend_from_slice(&[packet_id, tokens.0, tokens.1]);
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn single_packet() {
+        let mut framer = Framer::default();
+        framer.push(&frame(1, (28, 1), &[1, 2, 3]));
+
+        let packet = framer.next_packet().unwrap().unwrap();
+        assert_eq!(packet.as_bytes(), &[1, 28, 1, 1, 2, 3]);
+        assert_eq!(framer.next_packet().unwrap(), None);
+        assert_eq!(framer.buffered(), 0);
+    }
+
+    #[test]
+    fn packet_split_across_reads() {
+        let bytes = frame(2, (4, 4), &[0u8; 300]);
+        let mut framer = Framer::default();
+
+        // split inside the length header
+        framer.push(&bytes[..1]);
+        assert_eq!(framer.next_packet().unwrap(), None);
+
+        framer.push(&bytes[1..100]);
+        assert_eq!(framer.next_packet().unwrap(), None);
+
+        framer.push(&bytes[100..]);
+        let packet = framer.next_packet().unwrap().unwrap();
+        assert_eq!(packet.len(), 303);
+        assert_eq!(framer.buffered(), 0);
+    }
+
+    #[test]
+    fn several_packets_in_one_read() {
+        let mut bytes = frame(1, (28, 1), &[1]);
+        bytes.extend(frame(2, (26, 8), &[2, 2]));
+        bytes.extend(frame(3, (4, 4), &[3, 3, 3]));
+        // start of a fourth packet
+        bytes.extend(&frame(4, (5, 38), &[4; 10])[..5]);
+
+        let mut framer = Framer::default();
+        framer.push(&bytes);
+
+        let mut packets = Vec::new();
+        while let Some(packet) = framer.next_packet().unwrap() {
+            packets.push(packet.to_vec());
+        }
+
+        assert_eq!(
+            packets,
+            vec![
+                vec![1, 28, 1, 1],
+                vec![2, 26, 8, 2, 2],
+                vec![3, 4, 4, 3, 3, 3]
+            ]
+        );
+        assert_eq!(framer.buffered(), 5);
+    }
+
+    #[test]
+    fn reject_large_packet() {
+        let mut framer = Framer::new(64);
+        framer.push(&frame(1, (4, 4), &[0u8; 100])[..10]);
+
+        assert_eq!(
+            framer.next_packet(),
+            Err(FrameError::TooLarge {
+                size: 103,
+                max_size: 64
+            })
+        );
+    }
+
+    #[test]
+    fn reject_invalid_length() {
+        let mut framer = Framer::default();
+        framer.push(&[255, 255, 255, 255, 255, 1]);
+
+        assert_eq!(framer.next_packet(), Err(FrameError::InvalidLength));
+    }
+}
@@ -5,11 +5,13 @@ extern crate bytes;
 
 mod bytearray;
 pub mod crypt;
+pub mod framer;
+pub mod packet;
 
 use std::io::Write;
 
 use ab_glyph::{FontRef, PxScale};
-pub use bytearray::ByteArray;
+pub use bytearray::{ByteArray, DecodeError};
 use imageproc::{
     drawing::{draw_text_mut, text_size},
     image::{imageops::overlay, ImageBuffer, Rgb, RgbImage},
@@ -64,14 +66,11 @@ pub fn generate_captcha_image(code: &str) -> (ImageBuffer<Rgb<u8>, Vec<u8>>, u32
 }
 
 pub fn bytes_to_string(bytes: &[u8]) -> String {
-    bytes.into_iter().map(|b| *b as char).collect::<String>()
+    bytes.iter().map(|b| *b as char).collect::<String>()
 }
 
 pub fn str_to_bytes(text: &str) -> Vec<u8> {
-    text.chars()
-        .into_iter()
-        .map(|c| c as u8)
-        .collect::<Vec<u8>>()
+    text.chars().map(|c| c as u8).collect::<Vec<u8>>()
 }
 
 pub fn language_code(code: &str) -> &str {
@@ -91,15 +90,13 @@ pub fn language_code(code: &str) -> &str {
         "español" => "es",
         "ekakairũ naoero" => "na",
         // TODO: add all langues
-        "english" | _ => "en",
+        _ => "en", // english
     }
 }
 
-pub fn language_id(lang: &str) -> i8 {
-    match lang {
-        // TODO: add all langues
-        "en" | _ => 1,
-    }
+pub fn language_id(_lang: &str) -> i8 {
+    // TODO: add all langues
+    1 // en
 }
 
 pub fn language_info(lang: &str) -> (&str, &str) {
@@ -119,7 +116,7 @@ pub fn language_info(lang: &str) -> (&str, &str) {
         "es" => ("Español", "es"),
         "na" => ("Ekakairũ Naoero", "nr"),
         // TODO: add all langues
-        "en" | _ => ("English", "gb"),
+        _ => ("English", "gb"), // en
     }
 }
 
@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// Copyright (c) 2022-2024 AndrielFR <https://github.com/AndrielFR>
+
+//! Typed packets, declared once and encoded/decoded field by field.
+//!
+//! ```ignore
+//! #[derive(Packet)]
+//! #[packet(tokens = (4, 9))]
+//! pub struct Crouch {
+//!     pub crouch: i8,
+//! }
+//! ```
+
+use crate::{ByteArray, DecodeError};
+
+pub use bitmice_derive::{Field, Packet};
+
+/// A value that can be written to and read from a [`ByteArray`].
+pub trait Field: Sized {
+    fn write(&self, data: ByteArray) -> ByteArray;
+
+    fn read(data: &mut ByteArray) -> Result<Self, DecodeError>;
+}
+
+pub trait Packet: Field {
+    const TOKENS: (u8, u8);
+
+    fn encode(&self) -> ByteArray {
+        self.write(ByteArray::new())
+    }
+
+    fn decode(data: &mut ByteArray) -> Result<Self, DecodeError> {
+        Self::read(data)
+    }
+}
+
+macro_rules! impl_field {
+    ($($ty:ty => $write:ident, $read:ident;)*) => {
+        $(
+            impl Field for $ty {
+                fn write(&self, data: ByteArray) -> ByteArray {
+                    data.$write(*self)
+                }
+
+                fn read(data: &mut ByteArray) -> Result<Self, DecodeError> {
+                    data.$read()
+                }
+            }
+        )*
+    };
+}
+
+impl_field! {
+    i8 => write_i8, try_read_i8;
+    u8 => write_u8, try_read_u8;
+    i16 => write_i16, try_read_i16;
+    u16 => write_u16, try_read_u16;
+    i32 => write_i32, try_read_i32;
+    u32 => write_u32, try_read_u32;
+    i64 => write_i64, try_read_i64;
+    u64 => write_u64, try_read_u64;
+    bool => write_bool, try_read_bool;
+}
+
+impl Field for String {
+    fn write(&self, data: ByteArray) -> ByteArray {
+        data.write_utf(self)
+    }
+
+    fn read(data: &mut ByteArray) -> Result<Self, DecodeError> {
+        data.try_read_utf()
+    }
+}
+
+/// Optional data at the end of a packet, it's read only when there are bytes left.
+impl<T: Field> Field for Option<T> {
+    fn write(&self, data: ByteArray) -> ByteArray {
+        match self {
+            Some(value) => value.write(data),
+            None => data,
+        }
+    }
+
+    fn read(data: &mut ByteArray) -> Result<Self, DecodeError> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+
+        T::read(data).map(Some)
+    }
+}
+
+/// List prefixed by its length as an `u16`.
+impl<T: Field> Field for Vec<T> {
+    fn write(&self, data: ByteArray) -> ByteArray {
+        self.iter()
+            .fold(data.write_u16(self.len() as u16), |data, value| {
+                value.write(data)
+            })
+    }
+
+    fn read(data: &mut ByteArray) -> Result<Self, DecodeError> {
+        let length = data.try_read_u16()?;
+
+        (0..length).map(|_| T::read(data)).collect()
+    }
+}
+
+/// List prefixed by its length as an `u8`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ByteList<T>(pub Vec<T>);
+
+impl<T: Field> Field for ByteList<T> {
+    fn write(&self, data: ByteArray) -> ByteArray {
+        self.0
+            .iter()
+            .fold(data.write_u8(self.0.len() as u8), |data, value| {
+                value.write(data)
+            })
+    }
+
+    fn read(data: &mut ByteArray) -> Result<Self, DecodeError> {
+        let length = data.try_read_u8()?;
+
+        (0..length)
+            .map(|_| T::read(data))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// List without a length, it takes everything up to the end of the packet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Rest<T>(pub Vec<T>);
+
+impl<T: Field> Field for Rest<T> {
+    fn write(&self, data: ByteArray) -> ByteArray {
+        self.0.iter().fold(data, |data, value| value.write(data))
+    }
+
+    fn read(data: &mut ByteArray) -> Result<Self, DecodeError> {
+        let mut values = Vec::new();
+        while !data.is_empty() {
+            values.push(T::read(data)?);
+        }
+
+        Ok(Self(values))
+    }
+}
+
+/// Raw bytes prefixed by their length as an `u32`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Blob(pub Vec<u8>);
+
+impl Field for Blob {
+    fn write(&self, data: ByteArray) -> ByteArray {
+        data.write_u32(self.0.len() as u32)
+            .write_bytes(self.0.as_slice())
+    }
+
+    fn read(data: &mut ByteArray) -> Result<Self, DecodeError> {
+        let length = data.try_read_u32()? as usize;
+        if data.len() < length {
+            return Err(DecodeError::UnexpectedEof {
+                needed: length,
+                remaining: data.len(),
+            });
+        }
+
+        Ok(Self(data.bytes.drain(..length).collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Blob, ByteList, Field, Rest};
+    use crate::{ByteArray, DecodeError};
+
+    fn round_trip<T: Field + PartialEq + std::fmt::Debug>(value: T, length: usize) {
+        let mut data = value.write(ByteArray::new());
+        assert_eq!(data.len(), length);
+
+        assert_eq!(T::read(&mut data).unwrap(), value);
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn primitives() {
+        round_trip(-1i8, 1);
+        round_trip(-1i16, 2);
+        round_trip(u32::MAX, 4);
+        round_trip(true, 1);
+        round_trip(String::from("BitMice"), 9);
+    }
+
+    #[test]
+    fn lists() {
+        round_trip(vec![1u16, 2, 3], 8);
+        round_trip(ByteList(vec![1i8, 2, 3]), 4);
+        round_trip(Rest(vec![1u32, 2]), 8);
+        round_trip(Blob(vec![1, 2, 3]), 7);
+    }
+
+    #[test]
+    fn option_at_the_end() {
+        round_trip(Some(5i16), 2);
+        round_trip(None::<i16>, 0);
+    }
+
+    #[test]
+    fn short_data() {
+        let mut data = ByteArray::new().write_u16(2).write_u16(1);
+        assert!(matches!(
+            Vec::<u16>::read(&mut data),
+            Err(DecodeError::UnexpectedEof { .. })
+        ));
+
+        let mut data = ByteArray::new().write_u32(10).write_u8(1);
+        assert!(Blob::read(&mut data).is_err());
+    }
+}